@@ -0,0 +1,42 @@
+//! How long the work the computer players do on every turn takes: finding every play a
+//! hand could make, and comparing cards. Run with `cargo bench`.
+
+use card_games::{
+    card::STANDARD_DECK,
+    cho_dai_di::{ChoDaiDi, FOUR_PLAYERS},
+    collections::Cards,
+};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+fn possible_plays(c: &mut Criterion) {
+    let game = ChoDaiDi::<FOUR_PLAYERS>::new_game_with_seed(0);
+    let hand = game.get_current_players_hand();
+    c.bench_function("possible_plays opening", |b| {
+        b.iter(|| black_box(&game).possible_plays(black_box(&hand)))
+    });
+
+    // Smaller plays come first, so this leads with a single
+    let mut game = game;
+    let lead = game.possible_plays(&hand).into_iter().next().unwrap();
+    game.play_cards(lead).unwrap();
+    let hand = game.get_current_players_hand();
+    c.bench_function("possible_plays following", |b| {
+        b.iter(|| black_box(&game).possible_plays(black_box(&hand)))
+    });
+}
+
+fn sort_by_precedence(c: &mut Criterion) {
+    let mut deck = STANDARD_DECK;
+    deck.reverse();
+    let deck = Cards::<ChoDaiDi>::from(deck.to_vec());
+    c.bench_function("sort_by_precedence deck", |b| {
+        b.iter_batched_ref(
+            || deck.clone(),
+            |cards| cards.sort_by_precedence(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, possible_plays, sort_by_precedence);
+criterion_main!(benches);
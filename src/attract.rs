@@ -0,0 +1,135 @@
+//! A game the computer plays against itself at the main menu once no one's touched it for
+//! a while, like an arcade machine's attract mode. It's played on its own thread while
+//! the menu waits for input, and stops as soon as someone enters anything.
+
+use anyhow::Context;
+use card_games::{
+    cho_dai_di::{self, TurnOutcome},
+    player::{ai::AiStrategy, new_ai_players},
+    r#match::{Match, Rules, Termination},
+    render::CardStyle,
+};
+use std::{
+    sync::mpsc::{self, RecvTimeoutError},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// How long to pause after each turn of the demo
+const TURN_DELAY: Duration = Duration::from_millis(400);
+/// How long to pause after a demo game before dealing the next one
+const GAME_DELAY: Duration = Duration::from_secs(3);
+
+/// A demo that starts once the menu's been left alone long enough.
+pub struct Demo {
+    /// Dropped to stop the demo
+    stop: mpsc::Sender<()>,
+    thread: JoinHandle<bool>,
+}
+
+impl Demo {
+    /// Wait `idle` before dealing demo games by `rules`, one after another, until the
+    /// demo's stopped.
+    pub fn start<const PLAYERS: usize>(
+        idle: Duration,
+        rules: Rules,
+        card_style: CardStyle,
+    ) -> Self {
+        let (stop, stopped) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            if stopped.recv_timeout(idle) != Err(RecvTimeoutError::Timeout) {
+                return false;
+            }
+            println!();
+            println!("No one's playing, so here's a demo. Press Enter to go back to the menu.");
+            // Whatever goes wrong, the menu is still there once someone's back
+            if let Err(e) = play::<PLAYERS>(rules, card_style, &stopped) {
+                println!("The demo stopped: {e:#}");
+            }
+
+            true
+        });
+
+        Demo { stop, thread }
+    }
+
+    /// Stop the demo, returning true if it had started. Any input entered while it was
+    /// playing was only to stop it.
+    pub fn stop(self) -> bool {
+        drop(self.stop);
+        self.thread.join().unwrap_or(true)
+    }
+}
+
+/// Play demo games until `stopped` hears from the menu.
+fn play<const PLAYERS: usize>(
+    rules: Rules,
+    card_style: CardStyle,
+    stopped: &mpsc::Receiver<()>,
+) -> anyhow::Result<()> {
+    // Waits for `delay`, returning true if the demo's been stopped in the meantime
+    let wait = |delay| stopped.recv_timeout(delay) != Err(RecvTimeoutError::Timeout);
+    loop {
+        let mut players = new_ai_players(PLAYERS, &[])?;
+        let names: Vec<_> = players.iter().map(|it| it.name().to_owned()).collect();
+        let r#match = Match::new(names.clone(), rules, Termination::Games(1));
+        let mut game = r#match.new_game_with_seed::<PLAYERS>(rand::random())?;
+        println!();
+        println!("Demo: {}", names.join(", "));
+
+        while !game.is_game_ended() {
+            let seat = game.whose_turn();
+            let hand = game.get_current_players_hand();
+            let action = players[seat].choose_play(&game.view(), &hand, &mut rand::thread_rng());
+            let played = match &action {
+                cho_dai_di::TurnAction::Play(cards) => Some(card_style.cards(cards)),
+                cho_dai_di::TurnAction::Pass => None,
+            };
+            let outcome = game
+                .advance(action)
+                .with_context(|| format!("{} made a bad play", names[seat]))?;
+            let left = game.hands()[seat].len();
+            match (played, outcome) {
+                (Some(shown), TurnOutcome::RoundEnded) => {
+                    println!(
+                        "{} plays {shown} ({left} left), ending the round.",
+                        names[seat]
+                    )
+                }
+                (Some(shown), _) => println!("{} plays {shown} ({left} left)", names[seat]),
+                (None, _) => println!("{} will pass", names[seat]),
+            }
+            if wait(TURN_DELAY) {
+                return Ok(());
+            }
+        }
+
+        if let Some(winner) = game.winner() {
+            println!("{} wins the demo!", names[winner]);
+        }
+        if wait(GAME_DELAY) {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Demo;
+    use card_games::{palette::Palette, r#match::Rules, render::CardStyle};
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn test_demo_only_starts_when_idle() {
+        let style = CardStyle {
+            color: false,
+            palette: Palette::Standard,
+        };
+        let demo = Demo::start::<4>(Duration::from_secs(60), Rules::default(), style);
+        assert!(!demo.stop());
+
+        let demo = Demo::start::<4>(Duration::ZERO, Rules::default(), style);
+        thread::sleep(Duration::from_millis(100));
+        assert!(demo.stop());
+    }
+}
@@ -0,0 +1,478 @@
+//! The command line: `dai-di play`, `dai-di serve`, `dai-di replay <file>`, and the
+//! other subcommands. Running `dai-di` on its own, with or without `play`'s flags, is the
+//! same as `dai-di play`.
+
+use crate::{net, preset::Preset, profile::Profile, Args};
+use card_games::{
+    cho_dai_di::{SuitPrecedence, FOUR_PLAYERS},
+    player::ai::{Difficulty, Strategy},
+};
+use clap::{ArgAction, Parser, Subcommand};
+use std::{io::IsTerminal, path::PathBuf};
+
+#[derive(Debug, Parser)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[command(flatten)]
+    pub play: PlayArgs,
+    /// Turn colors off
+    #[arg(long, global = true)]
+    pub no_color: bool,
+    /// Log more about what's happening; repeat for even more
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+    /// Only log warnings and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Play a game at this keyboard, or join one over the network
+    Play(PlayArgs),
+    /// Host a game for people to join over the network
+    #[command(alias = "server")]
+    Serve(ServeArgs),
+    /// Play back a saved replay
+    Replay {
+        /// The replay file
+        #[arg(required_unless_present = "code")]
+        file: Option<PathBuf>,
+        /// A code that someone shared a replay as, instead of a file
+        #[arg(long, conflicts_with = "file")]
+        code: Option<String>,
+    },
+    /// Write saved replays out as CSV, one row per turn
+    Export {
+        /// The replay files
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Where to write the CSV, instead of printing it
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Shrink a replay that makes the game fail into a small test case for a bug report
+    Minimize {
+        /// The replay file
+        file: PathBuf,
+        /// Where to write the test case
+        #[arg(short, long, default_value = "minimized_failure.rs")]
+        output: PathBuf,
+    },
+    /// Play games between computer players without showing them, to compare strategies
+    Simulate(SimulateArgs),
+    /// Answer questions on which plays beat which
+    Quiz,
+    /// Show everyone's record
+    Stats {
+        /// First add the games in this CSV ledger of games played on paper
+        #[arg(long, value_name = "FILE")]
+        import: Option<PathBuf>,
+    },
+    /// Chart how everyone's been doing: win rates over time, scores, and cards left
+    Dashboard {
+        /// Only chart this player
+        player: Option<String>,
+        /// The number of games each point of a chart over time covers
+        #[arg(long, default_value_t = 10)]
+        games_per_point: usize,
+    },
+    /// Save tables to play again by name, or list, edit, or delete them
+    Preset {
+        #[command(subcommand)]
+        command: PresetCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PresetCommand {
+    /// Save a table with these flags, and the rules and timers in your profile, as a
+    /// preset to start with `dai-di play --preset <name>`
+    Save {
+        /// The name to save the preset as
+        name: String,
+        #[command(flatten)]
+        table: TableArgs,
+    },
+    /// Show every saved preset
+    List,
+    /// Open a preset in the editor given by VISUAL or EDITOR
+    Edit {
+        /// The preset's name
+        name: String,
+    },
+    /// Delete a preset
+    Delete {
+        /// The preset's name
+        name: String,
+    },
+}
+
+/// Flags for setting up a table, shared by `play` and `serve`.
+#[derive(Debug, clap::Args)]
+pub struct TableArgs {
+    /// The number of seats at the table, from 2 to 4
+    #[arg(long, default_value_t = FOUR_PLAYERS)]
+    pub players: usize,
+    /// The number of seats played by people rather than the computer
+    #[arg(long, default_value_t = 1)]
+    pub humans: usize,
+    /// How well the computer plays: easy, medium, or hard
+    #[arg(long, value_name = "LEVEL")]
+    pub difficulty: Option<Difficulty>,
+    /// The number of games in the match
+    #[arg(long, default_value_t = 1)]
+    pub games: usize,
+    /// Deal the match's games from seeds counting up from this one, so the same seed
+    /// deals the same hands
+    #[arg(long)]
+    pub seed: Option<u64>,
+    #[command(flatten)]
+    pub rules: RuleArgs,
+}
+
+/// House rules to play by on top of the ones in the profile.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct RuleArgs {
+    /// Play the dragon rule, where a card of every rank from 3 through A wins on the spot
+    #[arg(long)]
+    pub instant_wins: bool,
+    /// Let a flush beat a full house
+    #[arg(long)]
+    pub flush_beats_full_house: bool,
+    /// Require the 3♦ to be played on its own when it opens the game
+    #[arg(long)]
+    pub three_of_diamonds_opens_alone: bool,
+    /// Require the first play to be the first player's lowest card on its own
+    #[arg(long)]
+    pub must_play_lowest_single_first: bool,
+    /// Make anyone who passes when they could have led reveal their lowest card, or draw
+    /// a penalty marker
+    #[arg(long)]
+    pub pass_card_penalty: bool,
+    /// The order suits break ties in: standard, vietnamese, or diamonds-high
+    #[arg(long, value_name = "ORDER")]
+    pub suit_precedence: Option<SuitPrecedence>,
+    /// Play a rated game, with every assist turned off
+    #[arg(long)]
+    pub rated: bool,
+}
+
+impl RuleArgs {
+    /// Turn on the rules given on the command line in `profile`. Nothing's saved.
+    pub fn apply(&self, profile: &mut Profile) {
+        profile.instant_wins |= self.instant_wins;
+        profile.flush_beats_full_house |= self.flush_beats_full_house;
+        profile.three_of_diamonds_opens_alone |= self.three_of_diamonds_opens_alone;
+        profile.must_play_lowest_single_first |= self.must_play_lowest_single_first;
+        profile.pass_card_penalty |= self.pass_card_penalty;
+        profile.rated |= self.rated;
+        if let Some(suit_precedence) = self.suit_precedence {
+            profile.suit_precedence = suit_precedence;
+        }
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct PlayArgs {
+    #[command(flatten)]
+    pub table: TableArgs,
+    /// Read moves from a file, one per line, instead of the keyboard
+    #[arg(long, value_name = "FILE")]
+    pub script: Option<PathBuf>,
+    /// Have the computer play every seat while you watch, or with --connect, watch a
+    /// server's game without joining it
+    #[arg(long)]
+    pub spectate: bool,
+    /// Join the game hosted at this address
+    #[arg(long, value_name = "HOST:PORT")]
+    pub connect: Option<String>,
+    /// The name to play under, instead of the one in your profile
+    #[arg(long, env = "DAI_DI_PLAYER_NAME")]
+    pub name: Option<String>,
+    /// Play at the table saved with `dai-di preset save <name>`, instead of the one given
+    /// on the command line
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = [
+            "players",
+            "humans",
+            "difficulty",
+            "games",
+            "seed",
+            "instant_wins",
+            "flush_beats_full_house",
+            "three_of_diamonds_opens_alone",
+            "must_play_lowest_single_first",
+            "pass_card_penalty",
+            "suit_precedence",
+            "rated",
+            "spectate",
+            "connect",
+        ]
+    )]
+    pub preset: Option<String>,
+}
+
+impl PlayArgs {
+    pub fn into_args(self, no_color: bool) -> anyhow::Result<Args> {
+        let mut args = table_args(self.table, no_color)?;
+        args.script = self.script;
+        args.spectate = self.spectate;
+        args.connect = self.connect;
+        args.name = self.name;
+        if let Some(name) = &self.preset {
+            let preset = Preset::load(name)?;
+            args.players = preset.seats.len();
+            args.humans = preset.table().humans();
+            args.difficulty = preset.difficulty;
+            args.games = preset.games;
+            args.seed = preset.seed;
+            args.preset = Some(preset);
+        }
+        // Everyone at the table is played by the computer
+        if args.spectate {
+            args.humans = 0;
+        }
+        args.menu = std::io::stdin().is_terminal()
+            && args.script.is_none()
+            && args.connect.is_none()
+            && !args.spectate
+            && args.preset.is_none();
+
+        Ok(args)
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ServeArgs {
+    #[command(flatten)]
+    pub table: TableArgs,
+    /// The port to host the game on
+    #[arg(long, default_value_t = net::DEFAULT_PORT)]
+    pub port: u16,
+    /// Accept WebSocket connections, for playing in a browser
+    #[arg(long)]
+    pub websocket: bool,
+    /// Let people choose a table to play at, instead of hosting a single game
+    #[arg(long)]
+    pub lobby: bool,
+    /// Host a tournament across this many tables at once, each with --humans people
+    #[arg(long, default_value_t = 1, conflicts_with = "lobby")]
+    pub tables: usize,
+}
+
+impl ServeArgs {
+    pub fn into_args(self, no_color: bool) -> anyhow::Result<Args> {
+        let mut args = table_args(self.table, no_color)?;
+        args.server = true;
+        args.port = self.port;
+        args.websocket = self.websocket;
+        args.lobby = self.lobby;
+        if self.tables == 0 {
+            anyhow::bail!("--tables must be at least 1");
+        }
+        args.tables = self.tables;
+
+        Ok(args)
+    }
+}
+
+impl TableArgs {
+    /// Check that the table can be played at.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(2..=FOUR_PLAYERS).contains(&self.players) {
+            anyhow::bail!("--players must be from 2 to {FOUR_PLAYERS}");
+        }
+        if !(1..=self.players).contains(&self.humans) {
+            anyhow::bail!("--humans must be from 1 to the number of players");
+        }
+        if self.games == 0 {
+            anyhow::bail!("--games must be at least 1");
+        }
+
+        Ok(())
+    }
+}
+
+fn table_args(table: TableArgs, no_color: bool) -> anyhow::Result<Args> {
+    table.validate()?;
+
+    Ok(Args {
+        script: None,
+        players: table.players,
+        humans: table.humans,
+        difficulty: table.difficulty,
+        games: table.games,
+        seed: table.seed,
+        rules: table.rules,
+        no_color,
+        server: false,
+        websocket: false,
+        lobby: false,
+        tables: 1,
+        spectate: false,
+        port: net::DEFAULT_PORT,
+        connect: None,
+        name: None,
+        menu: false,
+        preset: None,
+    })
+}
+
+#[derive(Debug, clap::Args)]
+pub struct SimulateArgs {
+    /// The number of games to play
+    #[arg(long, default_value_t = 100)]
+    pub games: usize,
+    /// Deal the games from seeds counting up from this one, so the same seed deals the
+    /// same hands
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// The strategy played by each seat: random, advised, lowest-first,
+    /// hand-preserving, or weighted
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_values_t = [
+            Strategy::Random,
+            Strategy::Advised,
+            Strategy::LowestFirst,
+            Strategy::HandPreserving,
+        ]
+    )]
+    pub strategies: Vec<Strategy>,
+    /// The weights that weighted seats score plays by, from this TOML file. It's read
+    /// again between batches of games whenever it changes, so it can be tuned during a
+    /// long run without losing the standings so far.
+    #[arg(long, value_name = "FILE")]
+    pub weights: Option<PathBuf>,
+    /// Write every turn of every game to this file, as a dataset of what each seat could
+    /// see, what it played, and how its game ended, for training a model to play like
+    /// the strategies
+    #[arg(long, value_name = "FILE")]
+    pub dataset: Option<PathBuf>,
+    #[command(flatten)]
+    pub rules: RuleArgs,
+    /// Play the seats, rules, and seeds described in this TOML file instead of the ones
+    /// given on the command line
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "games",
+            "seed",
+            "strategies",
+            "weights",
+            "dataset",
+            "instant_wins",
+            "flush_beats_full_house",
+            "three_of_diamonds_opens_alone",
+            "must_play_lowest_single_first",
+            "pass_card_penalty",
+            "suit_precedence",
+            "rated",
+        ]
+    )]
+    pub scenario: Option<PathBuf>,
+    /// Where to write a scenario's results, as TOML [default: the scenario's path,
+    /// ending in .results.toml]
+    #[arg(long, value_name = "FILE", requires = "scenario")]
+    pub output: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cli, Command, PresetCommand};
+    use clap::{CommandFactory, Parser};
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_play_is_the_default() {
+        let cli = Cli::try_parse_from(["dai-di", "--players", "3", "--seed", "7"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.play.table.players, 3);
+        assert_eq!(cli.play.table.seed, Some(7));
+
+        let cli = Cli::try_parse_from(["dai-di", "server", "--humans", "2", "-vv"]).unwrap();
+        let Some(Command::Serve(serve)) = cli.command else {
+            panic!("expected the serve subcommand");
+        };
+        assert_eq!(serve.table.humans, 2);
+        assert_eq!(cli.verbose, 2);
+    }
+
+    #[test]
+    fn test_simulate_scenario() {
+        let cli = Cli::try_parse_from(["dai-di", "simulate", "--scenario", "s.toml"]).unwrap();
+        let Some(Command::Simulate(simulate)) = cli.command else {
+            panic!("expected the simulate subcommand");
+        };
+        assert_eq!(simulate.scenario.unwrap().to_str(), Some("s.toml"));
+
+        // A scenario says which games to play, so they can't be given as well
+        let args = ["dai-di", "simulate", "--scenario", "s.toml", "--games", "5"];
+        assert!(Cli::try_parse_from(args).is_err());
+        let args = [
+            "dai-di",
+            "simulate",
+            "--scenario",
+            "s.toml",
+            "--weights",
+            "w.toml",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+        let args = [
+            "dai-di",
+            "simulate",
+            "--scenario",
+            "s.toml",
+            "--dataset",
+            "turns.bin",
+        ];
+        assert!(Cli::try_parse_from(args).is_err());
+        // and nor can the rules, which would otherwise be silently ignored
+        for rule in [
+            &["--instant-wins"][..],
+            &["--flush-beats-full-house"],
+            &["--three-of-diamonds-opens-alone"],
+            &["--must-play-lowest-single-first"],
+            &["--pass-card-penalty"],
+            &["--suit-precedence", "vietnamese"],
+            &["--rated"],
+        ] {
+            let args = ["dai-di", "simulate", "--scenario", "s.toml"];
+            let args = args.iter().chain(rule);
+            assert!(Cli::try_parse_from(args).is_err(), "{rule:?}");
+        }
+        assert!(Cli::try_parse_from(["dai-di", "simulate", "--output", "r.toml"]).is_err());
+    }
+
+    #[test]
+    fn test_preset() {
+        let cli = Cli::try_parse_from(["dai-di", "preset", "save", "friday", "--games", "5"]);
+        let Some(Command::Preset {
+            command: PresetCommand::Save { name, table },
+        }) = cli.unwrap().command
+        else {
+            panic!("expected the preset save subcommand");
+        };
+        assert_eq!(name, "friday");
+        assert_eq!(table.games, 5);
+
+        let cli = Cli::try_parse_from(["dai-di", "--preset", "friday"]).unwrap();
+        assert_eq!(cli.play.preset.as_deref(), Some("friday"));
+        // A preset says who's at the table, so the table can't be given as well
+        let args = ["dai-di", "play", "--preset", "friday", "--players", "3"];
+        assert!(Cli::try_parse_from(args).is_err());
+        let args = ["dai-di", "--preset", "friday", "--instant-wins"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+}
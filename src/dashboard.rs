@@ -0,0 +1,130 @@
+//! Charts of how everyone's been doing, drawn in the terminal from the saved stats, viewed
+//! with `dai-di dashboard`.
+
+use card_games::stats::{PlayerStats, Stats};
+
+/// The characters a sparkline is drawn with, from lowest to highest
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// The widest a bar is drawn
+const BAR_WIDTH: usize = 40;
+/// The number of ranges that scores are split into
+const SCORE_BINS: usize = 8;
+
+pub fn run(player: Option<&str>, games_per_point: usize) -> anyhow::Result<()> {
+    let games_per_point = games_per_point.max(1);
+    let stats = crate::stats::load()?;
+    match player {
+        Some(name) => {
+            let Some(stats) = stats.player(name) else {
+                anyhow::bail!("{name} hasn't finished a game yet");
+            };
+            print_player(name, stats, games_per_point);
+        }
+        None => print_dashboard(&stats, games_per_point),
+    }
+
+    Ok(())
+}
+
+/// Chart everyone who's played, from the highest rated to the lowest.
+fn print_dashboard(stats: &Stats, games_per_point: usize) {
+    let leaderboard = stats.leaderboard();
+    if leaderboard.is_empty() {
+        println!("No games have been finished yet.");
+        return;
+    }
+
+    let n = leaderboard
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let most = leaderboard
+        .iter()
+        .map(|(_, player)| player.average_cards_left())
+        .fold(0.0, f64::max);
+    println!("Average cards left");
+    for (name, player) in &leaderboard {
+        let average = player.average_cards_left();
+        println!(
+            "  {name:<n$}  {:<BAR_WIDTH$}  {average:.1}",
+            bar(average, most)
+        );
+    }
+
+    for (name, player) in leaderboard {
+        println!();
+        print_player(name, player, games_per_point);
+    }
+}
+
+/// Chart one player's games.
+fn print_player(name: &str, player: &PlayerStats, games_per_point: usize) {
+    println!(
+        "{name}: {} wins, {} losses, rated {:.0}",
+        player.wins, player.losses, player.rating
+    );
+    if player.history.is_empty() {
+        println!("  No games to chart; games are only charted from this version on.");
+        return;
+    }
+
+    let win_rates = player.win_rates(games_per_point);
+    println!(
+        "  Win rate, {games_per_point} games at a time:  {}  {:.0}% lately",
+        sparkline(win_rates.iter().map(|rate| Some(*rate)), 1.0),
+        win_rates.last().unwrap_or(&0.0) * 100.0
+    );
+    let cards_left = player.cards_left_over_time(games_per_point);
+    let most = cards_left.iter().flatten().copied().fold(0.0, f64::max);
+    println!(
+        "  Cards left, {games_per_point} games at a time:  {}",
+        sparkline(cards_left.into_iter(), most)
+    );
+
+    println!("  Scores:");
+    let distribution = player.score_distribution(SCORE_BINS);
+    let most = distribution
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0);
+    let width = distribution
+        .iter()
+        .map(|(low, _)| low.to_string().len())
+        .max()
+        .unwrap_or(0);
+    for (low, count) in distribution {
+        println!(
+            "    {low:>width$} and up  {:<BAR_WIDTH$}  {count}",
+            bar(count as f64, most as f64)
+        );
+    }
+}
+
+/// A line of characters that rise and fall with `values`, where `max` is drawn full
+/// height. Values that aren't known are left blank.
+fn sparkline(values: impl Iterator<Item = Option<f64>>, max: f64) -> String {
+    values
+        .map(|value| match value {
+            Some(value) => {
+                let height = match max > 0.0 {
+                    true => value / max,
+                    false => 0.0,
+                };
+                let top = SPARKS.len() - 1;
+                SPARKS[((height * top as f64).round() as usize).min(top)]
+            }
+            None => ' ',
+        })
+        .collect()
+}
+
+/// A bar as long as `value` is compared to `max`, which is drawn [`BAR_WIDTH`] long.
+fn bar(value: f64, max: f64) -> String {
+    let length = match max > 0.0 {
+        true => (value / max * BAR_WIDTH as f64).round() as usize,
+        false => 0,
+    };
+    "█".repeat(length)
+}
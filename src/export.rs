@@ -0,0 +1,56 @@
+//! Writing saved replays out as CSV for spreadsheets, run with `dai-di export`.
+
+use anyhow::Context;
+use card_games::{
+    cho_dai_di::FOUR_PLAYERS,
+    export::{self, PlayRow},
+    replay::{self, Replay},
+};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Write the turns of every replay in `files` as one CSV, to `output` or to stdout. A
+/// file is only replaced once every replay has been read.
+pub fn run(files: &[PathBuf], output: Option<&Path>) -> anyhow::Result<()> {
+    let Some(path) = output else {
+        return write_all(files, io::stdout().lock());
+    };
+    let mut csv = Vec::new();
+    write_all(files, &mut csv)?;
+    let csv = String::from_utf8(csv).context("the CSV isn't valid UTF-8")?;
+    crate::persist::write_atomically(path, &csv)
+        .with_context(|| format!("saving the CSV to {}", path.display()))
+}
+
+fn write_all(files: &[PathBuf], mut writer: impl Write) -> anyhow::Result<()> {
+    // Every game goes in the one table, under a single header
+    export::write_header(&mut writer)?;
+    for file in files {
+        let contents = fs::read_to_string(file)
+            .with_context(|| format!("reading replay {}", file.display()))?;
+        let rows = rows(&contents, &game_id(file))
+            .with_context(|| format!("exporting replay {}", file.display()))?;
+        export::write_csv(&rows, &mut writer)?;
+    }
+    writer.flush().context("writing the CSV")?;
+
+    Ok(())
+}
+
+fn rows(contents: &str, game_id: &str) -> anyhow::Result<Vec<PlayRow>> {
+    match replay::number_of_players(contents) {
+        2 => export::rows(&contents.parse::<Replay<2>>()?, game_id),
+        3 => export::rows(&contents.parse::<Replay<3>>()?, game_id),
+        FOUR_PLAYERS => export::rows(&contents.parse::<Replay<FOUR_PLAYERS>>()?, game_id),
+        n => anyhow::bail!("the replay has {n} players, but games have from 2 to {FOUR_PLAYERS}"),
+    }
+}
+
+/// Replays are saved as `replay-<game ID>.txt`, so the ID is taken from the file name.
+fn game_id(file: &Path) -> String {
+    let stem = file.file_stem().unwrap_or_default().to_string_lossy();
+    stem.strip_prefix("replay-").unwrap_or(&stem).to_owned()
+}
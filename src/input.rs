@@ -0,0 +1,129 @@
+//! Where a human seat's input comes from.
+//!
+//! Each human seat reads its moves from an [`InputSource`], so a seat can be played
+//! from this terminal's stdin or from a separate terminal device, e.g. one keyboard per
+//! seat at a kiosk.
+
+use anyhow::Context;
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufRead, BufReader, IsTerminal},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+pub trait InputSource {
+    /// Read the next line of input, without the trailing newline. Returns `None` once
+    /// there's no more input, e.g. when moves piped into stdin have run out.
+    fn read_line(&mut self) -> anyhow::Result<Option<String>>;
+
+    /// Returns true if a person is typing this input as it's read, rather than it
+    /// having been written beforehand, e.g. in a script or piped into stdin.
+    fn is_interactive(&self) -> bool {
+        true
+    }
+}
+
+/// Share one input source between seats, e.g. people taking turns at the same keyboard.
+impl<T: InputSource + ?Sized> InputSource for Rc<RefCell<T>> {
+    fn read_line(&mut self) -> anyhow::Result<Option<String>> {
+        self.borrow_mut().read_line()
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.borrow().is_interactive()
+    }
+}
+
+/// Read input from this process's stdin.
+pub struct Stdin;
+
+impl InputSource for Stdin {
+    fn read_line(&mut self) -> anyhow::Result<Option<String>> {
+        let mut buf = String::new();
+        let read = std::io::stdin()
+            .read_line(&mut buf)
+            .context("reading from stdin")?;
+
+        Ok((read > 0).then(|| buf.trim_end_matches(['\r', '\n']).to_owned()))
+    }
+
+    fn is_interactive(&self) -> bool {
+        std::io::stdin().is_terminal()
+    }
+}
+
+/// Read input from a terminal device, such as `/dev/tty2`.
+pub struct Terminal {
+    path: PathBuf,
+    reader: BufReader<File>,
+}
+
+impl Terminal {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("opening terminal {}", path.display()))?;
+
+        Ok(Terminal {
+            path: path.to_owned(),
+            reader: BufReader::new(file),
+        })
+    }
+}
+
+impl InputSource for Terminal {
+    fn read_line(&mut self) -> anyhow::Result<Option<String>> {
+        let mut buf = String::new();
+        let read = self
+            .reader
+            .read_line(&mut buf)
+            .with_context(|| format!("reading from terminal {}", self.path.display()))?;
+
+        Ok((read > 0).then(|| buf.trim_end_matches(['\r', '\n']).to_owned()))
+    }
+}
+
+/// Read moves from a script file, e.g. to replay a sequence of moves in a test.
+///
+/// Scripts have one move per line, written just as it would be typed at the prompt.
+/// Anything after a `#` is a comment, and blank lines are skipped. Each move is echoed
+/// as it's read so that the output shows what was played.
+pub struct Script {
+    path: PathBuf,
+    lines: std::vec::IntoIter<String>,
+}
+
+impl Script {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading script {}", path.display()))?;
+        let lines: Vec<String> = contents
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+
+        Ok(Script {
+            path: path.to_owned(),
+            lines: lines.into_iter(),
+        })
+    }
+}
+
+impl InputSource for Script {
+    fn read_line(&mut self) -> anyhow::Result<Option<String>> {
+        let line = self.lines.next();
+        match &line {
+            Some(line) => println!("{line}"),
+            None => tracing::debug!("reached the end of script {}", self.path.display()),
+        }
+
+        Ok(line)
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
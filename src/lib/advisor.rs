@@ -0,0 +1,316 @@
+//! Advice for choosing a play, shared by every feature that suggests moves to a player.
+
+use crate::{
+    cho_dai_di::{CardOrdering, ChoDaiDi, GameView},
+    collections::Cards,
+    partition::{breaks_up, partition},
+    player::ai::Weights,
+};
+use std::fmt;
+
+/// The reasons behind a candidate play's score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Explanation {
+    /// The number of cards this play gets rid of
+    pub cards_shed: usize,
+    /// True if this play uses the highest card in the hand
+    pub uses_highest_card: bool,
+    /// True if this play empties the hand, winning the game
+    pub wins_game: bool,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.wins_game {
+            return write!(f, "plays your last cards and wins the game");
+        }
+
+        match self.cards_shed {
+            1 => write!(f, "sheds 1 card")?,
+            n => write!(f, "sheds {n} cards")?,
+        }
+        if self.uses_highest_card {
+            write!(f, " but spends your highest card")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Rank every legal play for `hand`, best first.
+///
+/// Plays that shed more cards are preferred, and weaker plays are preferred over stronger
+/// ones of the same size so that high cards are held for later.
+pub fn rank_plays<const PLAYERS: usize>(
+    view: &GameView<'_, PLAYERS>,
+    hand: &Cards<ChoDaiDi<PLAYERS>>,
+) -> Vec<(Cards<ChoDaiDi<PLAYERS>>, f32, Explanation)> {
+    let ordering = view.ordering();
+    let highest_card = ordering.highest_card(hand).copied();
+    let mut ranked = Vec::new();
+
+    for play in view.possible_plays(hand) {
+        let explanation = Explanation {
+            cards_shed: play.len(),
+            uses_highest_card: highest_card.is_some_and(|card| play.contains(&card)),
+            wins_game: play.len() == hand.len(),
+        };
+        let score = score(&play, &explanation, &ordering);
+        ranked.push((play, score, explanation));
+    }
+
+    ranked.sort_by(|(_, a, _), (_, b, _)| b.total_cmp(a));
+    ranked
+}
+
+/// Find the weakest legal play for `hand`, holding stronger cards for later.
+///
+/// Plays are compared by the kind of five-card hand they form, if any, and then by their
+/// key card in the game's current order of cards, so any single, pair, or triplet is
+/// weaker than a five-card hand. Between equally weak plays, the one that sheds more
+/// cards is chosen.
+pub fn weakest_play<const PLAYERS: usize>(
+    view: &GameView<'_, PLAYERS>,
+    hand: &Cards<ChoDaiDi<PLAYERS>>,
+) -> Option<Cards<ChoDaiDi<PLAYERS>>> {
+    weakest(view, view.possible_plays(hand))
+}
+
+/// Find the weakest legal play for `hand` that doesn't break up a stronger combination
+/// the hand is planned to be played as, as planned by [`partition`].
+///
+/// If no play keeps the plan intact, this returns `None` so the player can pass, unless
+/// they're leading and must play something. Then the weakest play is returned instead.
+pub fn preserving_play<const PLAYERS: usize>(
+    view: &GameView<'_, PLAYERS>,
+    hand: &Cards<ChoDaiDi<PLAYERS>>,
+) -> Option<Cards<ChoDaiDi<PLAYERS>>> {
+    let planned = partition(hand, &view.ordering());
+    let possible_plays = view.possible_plays(hand);
+    let preserving = possible_plays
+        .iter()
+        .filter(|play| !breaks_up(&planned, play))
+        .cloned();
+
+    match weakest(view, preserving) {
+        None if view.last_play().is_none() => weakest(view, possible_plays),
+        play => play,
+    }
+}
+
+/// Find the legal play for `hand` that scores highest by `weights`. A play that empties
+/// the hand always scores highest.
+pub fn weighted_play<const PLAYERS: usize>(
+    view: &GameView<'_, PLAYERS>,
+    hand: &Cards<ChoDaiDi<PLAYERS>>,
+    weights: &Weights,
+) -> Option<Cards<ChoDaiDi<PLAYERS>>> {
+    let ordering = view.ordering();
+    let highest_card = ordering.highest_card(hand).copied();
+    // Planning how to play the hand takes a while, so it's only done if it counts
+    let planned = (weights.breaking_up != 0.0).then(|| partition(hand, &ordering));
+    let score = |play: &Cards<ChoDaiDi<PLAYERS>>| {
+        if play.len() == hand.len() {
+            return f32::MAX;
+        }
+        let strength = ordering
+            .highest_card(play)
+            .map(|card| ordering.card_strength(card))
+            .unwrap_or_default();
+        let mut score = weights.cards_shed * play.len() as f32 - weights.strength * strength;
+        if highest_card.is_some_and(|card| play.contains(&card)) {
+            score -= weights.highest_card;
+        }
+        if planned
+            .as_ref()
+            .is_some_and(|planned| breaks_up(planned, play))
+        {
+            score -= weights.breaking_up;
+        }
+        score
+    };
+
+    // Of equally good plays, the last is picked, so they're gone through backwards to
+    // pick the first, as `rank_plays` does
+    view.possible_plays(hand)
+        .into_iter()
+        .rev()
+        .map(|play| (score(&play), play))
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, play)| play)
+}
+
+fn weakest<const PLAYERS: usize>(
+    view: &GameView<'_, PLAYERS>,
+    plays: impl IntoIterator<Item = Cards<ChoDaiDi<PLAYERS>>>,
+) -> Option<Cards<ChoDaiDi<PLAYERS>>> {
+    let ordering = view.ordering();
+    plays
+        .into_iter()
+        .filter_map(|play| {
+            let (combination, key_card) = play.classify_under(&ordering)?;
+            Some((combination, key_card, play))
+        })
+        .min_by(|(a_combination, a_key, a), (b_combination, b_key, b)| {
+            // Only five-card hands are ranked by their combination
+            let combinations =
+                if a_combination.is_five_card_hand() && b_combination.is_five_card_hand() {
+                    ordering.cmp_five_card_hand(a_combination, b_combination)
+                } else {
+                    a_combination
+                        .is_five_card_hand()
+                        .cmp(&b_combination.is_five_card_hand())
+                };
+            combinations
+                .then_with(|| ordering.cmp_card(a_key, b_key))
+                .then_with(|| b.len().cmp(&a.len()))
+        })
+        .map(|(_, _, play)| play)
+}
+
+fn score<const PLAYERS: usize>(
+    play: &Cards<ChoDaiDi<PLAYERS>>,
+    explanation: &Explanation,
+    ordering: &CardOrdering,
+) -> f32 {
+    if explanation.wins_game {
+        return f32::MAX;
+    }
+
+    let strength = ordering
+        .highest_card(play)
+        .map(|card| ordering.card_strength(card))
+        .unwrap_or_default();
+
+    explanation.cards_shed as f32 - strength
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card::Card, cho_dai_di::new_4p_game};
+
+    #[test]
+    fn test_rank_plays_prefers_weaker_singles() {
+        let mut game = new_4p_game();
+        let opener = game.get_current_players_hand();
+        let three_of_diamonds = Cards::from(Card::THREE_OF_DIAMONDS);
+        assert!(opener.contains(&Card::THREE_OF_DIAMONDS));
+        game.play_cards(three_of_diamonds).unwrap();
+
+        let hand = Cards::<ChoDaiDi>::try_from(vec!["4D", "9H", "2S"]).unwrap();
+        let ranked = rank_plays(&game.view(), &hand);
+        let plays: Vec<_> = ranked.iter().map(|(play, _, _)| play.clone()).collect();
+
+        assert_eq!(
+            plays,
+            vec![
+                Cards::from(Card::FOUR_OF_DIAMONDS),
+                Cards::from(Card::NINE_OF_HEARTS),
+                Cards::from(Card::TWO_OF_SPADES),
+            ]
+        );
+        assert!(ranked[2].2.uses_highest_card);
+    }
+
+    #[test]
+    fn test_rank_plays_prefers_winning() {
+        let mut game = new_4p_game();
+        game.play_cards(Cards::from(Card::THREE_OF_DIAMONDS))
+            .unwrap();
+
+        let hand = Cards::<ChoDaiDi>::try_from(vec!["2S"]).unwrap();
+        let ranked = rank_plays(&game.view(), &hand);
+
+        assert_eq!(ranked.len(), 1);
+        assert!(ranked[0].2.wins_game);
+    }
+
+    #[test]
+    fn test_weakest_play() {
+        let mut game = new_4p_game();
+        game.play_cards(Cards::from(Card::THREE_OF_DIAMONDS))
+            .unwrap();
+
+        let hand = Cards::<ChoDaiDi>::try_from(vec!["9H", "4S", "4D", "2S"]).unwrap();
+        assert_eq!(
+            weakest_play(&game.view(), &hand),
+            Some(Cards::from(Card::FOUR_OF_DIAMONDS))
+        );
+
+        // When leading, a low single is weaker than a five-card hand
+        game.unset_last_play();
+        let hand = Cards::<ChoDaiDi>::try_from(vec!["5C", "6D", "7H", "8S", "9D"]).unwrap();
+        assert_eq!(
+            weakest_play(&game.view(), &hand),
+            Some(Cards::try_from(vec!["5C"]).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_preserving_play() {
+        let mut game = new_4p_game();
+        game.play_cards(Cards::from(Card::THREE_OF_DIAMONDS))
+            .unwrap();
+
+        // Playing the 4♦ would break up the straight, so the lone 2♠ is played instead
+        let hand = Cards::<ChoDaiDi>::try_from(vec!["4D", "5H", "6C", "7D", "8S", "2S"]).unwrap();
+        assert_eq!(
+            preserving_play(&game.view(), &hand),
+            Some(Cards::from(Card::TWO_OF_SPADES))
+        );
+
+        // With nothing to spare, it passes
+        let hand = Cards::<ChoDaiDi>::try_from(vec!["4D", "5H", "6C", "7D", "8S"]).unwrap();
+        assert_eq!(preserving_play(&game.view(), &hand), None);
+
+        // Unless it's leading
+        game.unset_last_play();
+        assert_eq!(
+            preserving_play(&game.view(), &hand).map(|play| play.len()),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn test_weighted_play() {
+        let mut game = new_4p_game();
+        game.play_cards(Cards::from(Card::THREE_OF_DIAMONDS))
+            .unwrap();
+        let hand = Cards::<ChoDaiDi>::try_from(vec!["4D", "5H", "6C", "7D", "8S", "2S"]).unwrap();
+
+        // By default, plays are scored the way they're ranked
+        let weights = Weights::default();
+        assert_eq!(
+            weighted_play(&game.view(), &hand, &weights),
+            Some(rank_plays(&game.view(), &hand)[0].0.clone())
+        );
+
+        // Caring about keeping the straight together spends the 2♠ instead
+        let weights = Weights {
+            breaking_up: 2.0,
+            ..Weights::default()
+        };
+        assert_eq!(
+            weighted_play(&game.view(), &hand, &weights),
+            Some(Cards::from(Card::TWO_OF_SPADES))
+        );
+
+        // Unless the 2♠ is worth even more
+        let weights = Weights {
+            highest_card: 5.0,
+            ..weights
+        };
+        assert_eq!(
+            weighted_play(&game.view(), &hand, &weights),
+            Some(Cards::from(Card::FOUR_OF_DIAMONDS))
+        );
+
+        // The last cards are always played
+        let hand = Cards::<ChoDaiDi>::try_from(vec!["2S"]).unwrap();
+        assert_eq!(
+            weighted_play(&game.view(), &hand, &weights),
+            Some(Cards::from(Card::TWO_OF_SPADES))
+        );
+    }
+}
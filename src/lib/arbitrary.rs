@@ -0,0 +1,294 @@
+//! [proptest](mod@proptest) strategies for generating cards, plays, and games in property tests.
+//!
+//! Only built with the `test-util` feature.
+
+use crate::{
+    card::{Card, STANDARD_DECK},
+    cho_dai_di::{CardOrdering, ChoDaiDi, SuitPrecedence, TurnAction},
+    collections::Cards,
+    r#match::Rules,
+    rank::Rank,
+    suit::Suit,
+};
+use proptest::{
+    collection::{vec, SizeRange},
+    prelude::*,
+    sample::{select, subsequence, Index},
+};
+
+/// The most turns taken in a [`RandomGame`].
+const MAX_TURNS: usize = 60;
+
+fn ranks() -> Vec<Rank> {
+    Cards::<ChoDaiDi>::RANK_PRECEDENCE.to_vec()
+}
+
+fn suits() -> Vec<Suit> {
+    SuitPrecedence::Standard.suits().to_vec()
+}
+
+/// The ranks of every straight, from 3 4 5 6 7 up to J Q K A 2, then A 2 3 4 5 and
+/// 2 3 4 5 6.
+fn straights() -> Vec<Vec<Rank>> {
+    let mut straights: Vec<_> = ranks().windows(5).map(<[Rank]>::to_vec).collect();
+    straights.push(vec![
+        Rank::Ace,
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+    ]);
+    straights.push(vec![
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+    ]);
+    straights
+}
+
+pub fn rank() -> impl Strategy<Value = Rank> {
+    select(ranks())
+}
+
+pub fn suit() -> impl Strategy<Value = Suit> {
+    select(suits())
+}
+
+pub fn card() -> impl Strategy<Value = Card> {
+    select(STANDARD_DECK.to_vec())
+}
+
+/// Any suit precedence.
+pub fn suit_precedence() -> impl Strategy<Value = SuitPrecedence> {
+    prop_oneof![
+        Just(SuitPrecedence::Standard),
+        Just(SuitPrecedence::Vietnamese),
+        Just(SuitPrecedence::DiamondsHigh),
+    ]
+}
+
+/// Any order of cards, with or without a revolution in effect.
+pub fn card_ordering() -> impl Strategy<Value = CardOrdering> {
+    (any::<bool>(), any::<bool>(), suit_precedence()).prop_map(
+        |(ranks_inverted, flush_beats_full_house, suit_precedence)| {
+            let mut ordering = CardOrdering::STANDARD;
+            if ranks_inverted {
+                ordering.invert_ranks();
+            }
+            ordering.set_flush_beats_full_house(flush_beats_full_house);
+            ordering.set_suit_precedence(suit_precedence);
+            ordering
+        },
+    )
+}
+
+/// Any combination of house rules.
+pub fn rules() -> impl Strategy<Value = Rules> {
+    (
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        suit_precedence(),
+    )
+        .prop_map(
+            |(
+                revolutions,
+                instant_wins,
+                flush_beats_full_house,
+                opens_alone,
+                lowest_single_first,
+                pass_card_penalty,
+                precedence,
+            )| {
+                Rules::default()
+                    .revolutions_enabled(revolutions)
+                    .instant_wins_enabled(instant_wins)
+                    .flush_beats_full_house(flush_beats_full_house)
+                    .three_of_diamonds_opens_alone(opens_alone)
+                    .must_play_lowest_single_first(lowest_single_first)
+                    .pass_card_penalty(pass_card_penalty)
+                    .suit_precedence(precedence)
+            },
+        )
+}
+
+/// A hand of different cards, in no particular order.
+pub fn hand<const PLAYERS: usize>(
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = Cards<ChoDaiDi<PLAYERS>>> {
+    subsequence(STANDARD_DECK.to_vec(), size)
+        .prop_shuffle()
+        .prop_map(Cards::from)
+}
+
+/// `count` cards of the same rank.
+fn of_a_kind<const PLAYERS: usize>(
+    count: usize,
+) -> impl Strategy<Value = Cards<ChoDaiDi<PLAYERS>>> {
+    (rank(), subsequence(suits(), count)).prop_map(|(rank, suits)| {
+        suits
+            .into_iter()
+            .map(|suit| Card::new(rank, suit))
+            .collect::<Vec<_>>()
+            .into()
+    })
+}
+
+pub fn single<const PLAYERS: usize>() -> impl Strategy<Value = Cards<ChoDaiDi<PLAYERS>>> {
+    card().prop_map(Cards::from)
+}
+
+pub fn pair<const PLAYERS: usize>() -> impl Strategy<Value = Cards<ChoDaiDi<PLAYERS>>> {
+    of_a_kind(2)
+}
+
+pub fn triplet<const PLAYERS: usize>() -> impl Strategy<Value = Cards<ChoDaiDi<PLAYERS>>> {
+    of_a_kind(3)
+}
+
+/// A straight, flush, full house, four of a kind plus one, or straight flush.
+pub fn five_card_hand<const PLAYERS: usize>() -> impl Strategy<Value = Cards<ChoDaiDi<PLAYERS>>> {
+    let straight = (
+        select(straights()),
+        [suit(), suit(), suit(), suit(), suit()],
+    )
+        .prop_map(|(ranks, suits)| {
+            ranks
+                .into_iter()
+                .zip(suits)
+                .map(|(rank, suit)| Card::new(rank, suit))
+                .collect::<Vec<_>>()
+        });
+    let flush = (suit(), subsequence(ranks(), 5)).prop_map(|(suit, ranks)| {
+        ranks
+            .into_iter()
+            .map(|rank| Card::new(rank, suit))
+            .collect::<Vec<_>>()
+    });
+    let full_house = (
+        subsequence(ranks(), 2).prop_shuffle(),
+        subsequence(suits(), 3),
+        subsequence(suits(), 2),
+    )
+        .prop_map(|(ranks, triplet_suits, pair_suits)| {
+            let triplet = triplet_suits
+                .into_iter()
+                .map(|suit| Card::new(ranks[0], suit));
+            let pair = pair_suits.into_iter().map(|suit| Card::new(ranks[1], suit));
+            triplet.chain(pair).collect::<Vec<_>>()
+        });
+    let four_of_a_kind_plus_one =
+        (subsequence(ranks(), 2).prop_shuffle(), suit()).prop_map(|(ranks, kicker_suit)| {
+            let four = suits().into_iter().map(|suit| Card::new(ranks[0], suit));
+            four.chain([Card::new(ranks[1], kicker_suit)])
+                .collect::<Vec<_>>()
+        });
+
+    prop_oneof![straight, flush, full_house, four_of_a_kind_plus_one]
+        .prop_shuffle()
+        .prop_map(Cards::from)
+}
+
+/// Any valid play, whether or not it could follow another.
+pub fn play<const PLAYERS: usize>() -> impl Strategy<Value = Cards<ChoDaiDi<PLAYERS>>> {
+    prop_oneof![single(), pair(), triplet(), five_card_hand(),]
+}
+
+/// A game dealt from a seed and played for a number of legal turns.
+///
+/// Games can't be printed or cloned, so this holds what's needed to play the game again
+/// instead. When a test fails, proptest shrinks it towards fewer turns.
+#[derive(Debug, Clone)]
+pub struct RandomGame {
+    pub seed: u64,
+    pub rules: Rules,
+    /// For each turn, which of the possible plays to make, or `None` to pass where
+    /// passing is allowed
+    turns: Vec<Option<Index>>,
+}
+
+impl RandomGame {
+    /// Deal the game and take its turns, stopping early if someone wins.
+    pub fn play<const PLAYERS: usize>(&self) -> ChoDaiDi<PLAYERS> {
+        let mut game = ChoDaiDi::new_game_with_seed(self.seed);
+        self.rules.apply(&mut game);
+
+        for turn in &self.turns {
+            if game.is_game_ended() {
+                break;
+            }
+            let hand = game.get_current_players_hand();
+            let possible_plays = game.possible_plays(&hand);
+            // Whoever leads has to play something
+            let leading = game.last_play().is_none();
+
+            let action = match turn {
+                _ if possible_plays.is_empty() => TurnAction::Pass,
+                None if !leading => TurnAction::Pass,
+                turn => {
+                    let index = turn.map_or(0, |index| index.index(possible_plays.len()));
+                    TurnAction::Play(possible_plays[index].clone())
+                }
+            };
+            game.advance(action).expect("possible plays are legal");
+        }
+
+        game
+    }
+}
+
+/// Take up to `turns` turns of `game`, stopping early if someone wins. Each player makes
+/// the first of their possible plays, which is their weakest, or passes if they can't
+/// play.
+pub fn play_weakest<const PLAYERS: usize>(game: &mut ChoDaiDi<PLAYERS>, turns: usize) {
+    for _ in 0..turns {
+        if game.is_game_ended() {
+            break;
+        }
+        let hand = game.get_current_players_hand();
+        let action = match game.possible_plays(&hand).into_iter().next() {
+            Some(play) => TurnAction::Play(play),
+            None => TurnAction::Pass,
+        };
+        game.advance(action).expect("possible plays are legal");
+    }
+}
+
+/// A game some way through, under any combination of house rules.
+pub fn random_game() -> impl Strategy<Value = RandomGame> {
+    (
+        any::<u64>(),
+        rules(),
+        vec(any::<Option<Index>>(), 0..=MAX_TURNS),
+    )
+        .prop_map(|(seed, rules, turns)| RandomGame { seed, rules, turns })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{five_card_hand, hand, play};
+    use crate::{cho_dai_di::FOUR_PLAYERS, collections::CardSet};
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn test_plays_are_valid_hands(play in play::<FOUR_PLAYERS>()) {
+            prop_assert!(play.is_valid_hand().is_ok(), "{play}");
+        }
+
+        #[test]
+        fn test_five_card_hands_are_classified(cards in five_card_hand::<FOUR_PLAYERS>()) {
+            prop_assert!(cards.evaluate_five().is_some(), "{cards}");
+        }
+
+        #[test]
+        fn test_hands_hold_different_cards(cards in hand::<FOUR_PLAYERS>(0..=13)) {
+            prop_assert_eq!(CardSet::from(&cards).len(), cards.len());
+        }
+    }
+}
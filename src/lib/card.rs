@@ -2,6 +2,7 @@ mod constants;
 
 use crate::{rank::Rank, suit::Suit};
 pub use constants::STANDARD_DECK;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{fmt, str::FromStr};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,6 +12,10 @@ pub struct Card {
 }
 
 impl Card {
+    pub(crate) const fn new(rank: Rank, suit: Suit) -> Self {
+        Card { rank, suit }
+    }
+
     pub fn rank(&self) -> Rank {
         self.rank
     }
@@ -29,16 +34,89 @@ impl fmt::Display for Card {
 impl FromStr for Card {
     type Err = anyhow::Error;
 
+    /// Parse a card written as its rank and then its suit, like "10h" or "K♠". The suit
+    /// may also come first, and either may be written by its localized name, as in
+    /// "紅心K" or "黑桃十".
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
-        if s.len() < 2 {
-            return Err(anyhow::anyhow!("`{s}` is not a valid card"));
+        // Suits may be written with more than one character, so try every split
+        let splits = s.char_indices().map(|(i, _)| i).skip(1);
+        for i in splits {
+            let (left, right) = s.split_at(i);
+            if let (Ok(rank), Ok(suit)) = (left.parse(), right.parse()) {
+                return Ok(Card::new(rank, suit));
+            }
+            if let (Ok(suit), Ok(rank)) = (left.parse(), right.parse()) {
+                return Ok(Card::new(rank, suit));
+            }
         }
 
-        let (rank, suit) = s.split_at(s.len() - 1);
-        let rank = rank.parse()?;
-        let suit = suit.parse()?;
+        Err(anyhow::anyhow!("`{s}` is not a valid card"))
+    }
+}
+
+/// Cards are serialized the way they're displayed, e.g. `"K♥"`.
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Card;
+    use crate::{rank::Rank, suit::Suit};
+
+    #[test]
+    fn test_from_str() {
+        let king_of_hearts = Card {
+            rank: Rank::King,
+            suit: Suit::Hearts,
+        };
+        assert_eq!("kh".parse::<Card>().unwrap(), king_of_hearts);
+        assert_eq!("K♥".parse::<Card>().unwrap(), king_of_hearts);
+        assert_eq!("紅心K".parse::<Card>().unwrap(), king_of_hearts);
+        assert_eq!("红心K".parse::<Card>().unwrap(), king_of_hearts);
+        assert_eq!("K紅心".parse::<Card>().unwrap(), king_of_hearts);
+        assert_eq!(
+            "10黑桃".parse::<Card>().unwrap(),
+            Card {
+                rank: Rank::Ten,
+                suit: Suit::Spades
+            }
+        );
+
+        assert_eq!(
+            "黑桃十".parse::<Card>().unwrap(),
+            Card {
+                rank: Rank::Ten,
+                suit: Suit::Spades
+            }
+        );
+        assert_eq!(
+            "方塊二".parse::<Card>().unwrap(),
+            Card {
+                rank: Rank::Two,
+                suit: Suit::Diamonds
+            }
+        );
+        assert_eq!(
+            "尖梅花".parse::<Card>().unwrap(),
+            Card {
+                rank: Rank::Ace,
+                suit: Suit::Clubs
+            }
+        );
 
-        Ok(Card { rank, suit })
+        assert!("k".parse::<Card>().is_err());
+        assert!("紅心".parse::<Card>().is_err());
+        assert!("1x".parse::<Card>().is_err());
     }
 }
@@ -0,0 +1,45 @@
+//! The kinds of hands that may be played.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The combinations of cards that may be played, from lowest to highest.
+///
+/// A five-card hand beats any five-card hand of a lower combination, unless the game's
+/// [`CardOrdering`](super::CardOrdering) says otherwise. Hands of the same combination
+/// are compared by their key cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Combination {
+    Single,
+    Pair,
+    Triplet,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKindPlusOne,
+    StraightFlush,
+}
+
+impl Combination {
+    /// Whether this is one of the five-card hands.
+    pub fn is_five_card_hand(&self) -> bool {
+        *self >= Combination::Straight
+    }
+}
+
+impl fmt::Display for Combination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let combination = match self {
+            Combination::Single => "card",
+            Combination::Pair => "pair",
+            Combination::Triplet => "triplet",
+            Combination::Straight => "straight",
+            Combination::Flush => "flush",
+            Combination::FullHouse => "full house",
+            Combination::FourOfAKindPlusOne => "four of a kind plus one",
+            Combination::StraightFlush => "straight flush",
+        };
+        write!(f, "{combination}")
+    }
+}
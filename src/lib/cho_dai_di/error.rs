@@ -0,0 +1,86 @@
+use super::Combination;
+use crate::card::Card;
+use std::fmt;
+
+/// The reason a play isn't allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayError {
+    /// No cards were played
+    Empty,
+    /// No combination has this many cards
+    InvalidSize { got: usize },
+    /// The play has a different number of cards than the last play
+    WrongSize { expected: usize, got: usize },
+    /// A single, pair, or triplet has cards of more than one rank
+    NotSameRank,
+    /// Five cards that don't form a five-card hand. `closest_category` is the hand the
+    /// cards came nearest to forming.
+    NotAFiveCardHand { closest_category: Combination },
+    /// The play doesn't beat the last play. `required` is the lowest key card that
+    /// would, or `None` if the last play can't be beaten. `got` is the play's key card.
+    TooLow {
+        category: Combination,
+        required: Option<Card>,
+        got: Card,
+    },
+    /// The play is a lower kind of five-card hand than the last play
+    MismatchedCombination {
+        expected: Combination,
+        got: Combination,
+    },
+    /// The first play of the game doesn't include the three of diamonds
+    MustIncludeThreeOfDiamonds,
+    /// The first play of the game must be the three of diamonds on its own
+    MustBeThreeOfDiamondsAlone,
+    /// The first play of the game must be the first player's lowest card on its own
+    MustBeLowestSingle { lowest: Card },
+    /// The player doesn't hold every card in the play
+    NotYourCards,
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayError::Empty => write!(f, "a hand must contain at least one card"),
+            PlayError::InvalidSize { .. } => write!(
+                f,
+                "plays must be either a single card, a pair, a triplet, or a quintuple"
+            ),
+            PlayError::WrongSize { expected, got } => write!(
+                f,
+                "during a trick, all hands must contain the same number of cards \
+                 (expected {expected}, got {got})"
+            ),
+            PlayError::NotSameRank => {
+                write!(f, "1-3 card plays may only contain cards of the same rank")
+            }
+            PlayError::NotAFiveCardHand { .. } => write!(
+                f,
+                "5 card plays must be a straight, a flush, a full house, \
+                 a four of a kind plus one, or a straight flush"
+            ),
+            PlayError::TooLow { category, .. } => write!(
+                f,
+                "the played {category} must be higher than the previous {category}"
+            ),
+            PlayError::MismatchedCombination { expected, got } => {
+                write!(f, "a {got} can't be played on a {expected}")
+            }
+            PlayError::MustIncludeThreeOfDiamonds => {
+                write!(f, "the first play must contain the three of diamonds")
+            }
+            PlayError::MustBeThreeOfDiamondsAlone => {
+                write!(f, "the first play must be the three of diamonds on its own")
+            }
+            PlayError::MustBeLowestSingle { lowest } => {
+                write!(
+                    f,
+                    "the first play must be your lowest card, {lowest}, on its own"
+                )
+            }
+            PlayError::NotYourCards => write!(f, "you don't have all of those cards"),
+        }
+    }
+}
+
+impl std::error::Error for PlayError {}
@@ -0,0 +1,173 @@
+//! Classifying five-card hands from bit masks of their ranks and suits, in a single pass
+//! over the cards.
+
+use super::{rank_index, ChoDaiDi, Combination};
+use crate::{collections::Cards, rank::Rank};
+
+/// For every mask of ranks, with the 3 in bit 0 up to the 2 in bit 12, whether those
+/// ranks form a straight.
+static STRAIGHTS: [bool; 1 << 13] = straights();
+
+const fn straights() -> [bool; 1 << 13] {
+    let mut straights = [false; 1 << 13];
+    // From 3 4 5 6 7 up to 10 J Q K A, then J Q K A 2
+    let mut lowest = 0;
+    while lowest <= 8 {
+        straights[0b11111 << lowest] = true;
+        lowest += 1;
+    }
+    // A 2 3 4 5 and 2 3 4 5 6
+    straights[0b1_1000_0000_0111] = true;
+    straights[0b1_0000_0000_1111] = true;
+
+    straights
+}
+
+/// The combination of a five-card hand, and what decides it against another hand of its
+/// combination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FiveCardEvaluation {
+    pub combination: Combination,
+    /// The rank of the triplet in a full house, or of the four of a kind in a four of a
+    /// kind plus one. Hands of the other combinations are compared by their highest card.
+    pub group_rank: Option<Rank>,
+}
+
+impl<const PLAYERS: usize> Cards<ChoDaiDi<PLAYERS>> {
+    /// Work out which five-card hand these cards form, or `None` if they aren't five cards
+    /// that form one.
+    pub fn evaluate_five(&self) -> Option<FiveCardEvaluation> {
+        if self.len() != 5 {
+            return None;
+        }
+
+        let mut ranks = 0u16;
+        let mut suits = 0u8;
+        let mut rank_counts = [0u8; 13];
+        for card in self.iter() {
+            let rank = rank_index(card.rank()) as usize;
+            ranks |= 1 << rank;
+            suits |= 1 << card.suit() as u8;
+            rank_counts[rank] += 1;
+        }
+        let is_flush = suits.count_ones() == 1;
+        let is_straight = ranks.count_ones() == 5 && STRAIGHTS[ranks as usize];
+
+        let evaluation = |combination, group_rank| {
+            Some(FiveCardEvaluation {
+                combination,
+                group_rank,
+            })
+        };
+        match ranks.count_ones() {
+            _ if is_straight && is_flush => evaluation(Combination::StraightFlush, None),
+            // Two ranks make either a four of a kind and one more, or a triplet and a pair
+            2 => {
+                let group = rank_counts.iter().position(|&count| count >= 3)?;
+                let group_rank = Some(Self::RANK_PRECEDENCE[group]);
+                match rank_counts[group] {
+                    4 => evaluation(Combination::FourOfAKindPlusOne, group_rank),
+                    _ => evaluation(Combination::FullHouse, group_rank),
+                }
+            }
+            _ if is_flush => evaluation(Combination::Flush, None),
+            _ if is_straight => evaluation(Combination::Straight, None),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FiveCardEvaluation;
+    use crate::{
+        card::STANDARD_DECK,
+        cho_dai_di::{ChoDaiDi, Combination},
+        collections::Cards,
+        rank::Rank,
+    };
+    use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+
+    fn evaluate(cards: &[&str]) -> Option<FiveCardEvaluation> {
+        Cards::<ChoDaiDi>::try_from(cards.to_vec())
+            .unwrap()
+            .evaluate_five()
+    }
+
+    #[test]
+    fn test_evaluate_five() {
+        let combination = |cards| evaluate(cards).map(|it| it.combination);
+        assert_eq!(
+            combination(&["3D", "4C", "5H", "6S", "7D"]),
+            Some(Combination::Straight)
+        );
+        assert_eq!(
+            combination(&["JD", "QC", "KH", "AS", "2D"]),
+            Some(Combination::Straight)
+        );
+        assert_eq!(
+            combination(&["AD", "2C", "3H", "4S", "5D"]),
+            Some(Combination::Straight)
+        );
+        assert_eq!(
+            combination(&["2C", "3H", "4S", "5D", "6D"]),
+            Some(Combination::Straight)
+        );
+        assert_eq!(
+            combination(&["3H", "8H", "9H", "JH", "AH"]),
+            Some(Combination::Flush)
+        );
+        assert_eq!(
+            combination(&["10S", "JS", "QS", "KS", "AS"]),
+            Some(Combination::StraightFlush)
+        );
+        // Wrapping around from the 2 to the 3 is as far as straights go
+        assert_eq!(combination(&["QD", "KC", "AH", "2S", "3D"]), None);
+        assert_eq!(combination(&["3D", "3C", "4H", "4S", "5D"]), None);
+        assert_eq!(combination(&["3D", "4C", "5H", "6S"]), None);
+
+        assert_eq!(
+            evaluate(&["9D", "9C", "9H", "4S", "4D"]),
+            Some(FiveCardEvaluation {
+                combination: Combination::FullHouse,
+                group_rank: Some(Rank::Nine),
+            })
+        );
+        assert_eq!(
+            evaluate(&["4S", "2D", "2C", "2H", "2S"]),
+            Some(FiveCardEvaluation {
+                combination: Combination::FourOfAKindPlusOne,
+                group_rank: Some(Rank::Two),
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_five_agrees_with_scanning() {
+        let mut rng = SmallRng::seed_from_u64(5);
+        let mut deck = STANDARD_DECK;
+        for _ in 0..20_000 {
+            deck.shuffle(&mut rng);
+            let cards = Cards::<ChoDaiDi>::from(deck[..5].to_vec());
+            let scanned = if cards.is_a_straight_flush() {
+                Some(Combination::StraightFlush)
+            } else if cards.is_four_of_a_kind_plus_one() {
+                Some(Combination::FourOfAKindPlusOne)
+            } else if cards.is_a_full_house() {
+                Some(Combination::FullHouse)
+            } else if cards.is_a_flush() {
+                Some(Combination::Flush)
+            } else if cards.is_a_straight() {
+                Some(Combination::Straight)
+            } else {
+                None
+            };
+
+            assert_eq!(
+                cards.evaluate_five().map(|it| it.combination),
+                scanned,
+                "{cards}"
+            );
+        }
+    }
+}
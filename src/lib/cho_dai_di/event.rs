@@ -0,0 +1,30 @@
+use super::{ChoDaiDi, FOUR_PLAYERS};
+use crate::{card::Card, collections::Cards};
+use serde::{Deserialize, Serialize};
+
+/// Something that happened during a game, as passed to callbacks registered with
+/// [`ChoDaiDi::on_event`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum GameEvent<const PLAYERS: usize = FOUR_PLAYERS> {
+    /// A player played some cards
+    CardsPlayed {
+        player: usize,
+        cards: Cards<ChoDaiDi<PLAYERS>>,
+    },
+    /// A player passed their turn
+    PlayerPassed { player: usize },
+    /// A player passed when they could have led, and had to reveal `card` from their
+    /// hand, see [`ChoDaiDi::set_pass_card_penalty`]
+    CardRevealed { player: usize, card: Card },
+    /// A player passed when they could have led with every card in their hand already
+    /// revealed, and drew a penalty marker. `markers` is how many they now hold.
+    PenaltyMarkerDrawn { player: usize, markers: usize },
+    /// The round ended and a new one started, led by `leader`
+    RoundEnded { leader: usize },
+    /// A player won the game
+    GameEnded { winner: usize },
+}
+
+/// A callback registered with [`ChoDaiDi::on_event`].
+pub(crate) type Observer<const PLAYERS: usize> = Box<dyn FnMut(GameEvent<PLAYERS>) + Send>;
@@ -0,0 +1,32 @@
+use super::{ChoDaiDi, FOUR_PLAYERS};
+use crate::collections::Cards;
+
+/// What a player does on their turn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TurnAction<const PLAYERS: usize = FOUR_PLAYERS> {
+    Play(Cards<ChoDaiDi<PLAYERS>>),
+    Pass,
+}
+
+/// What happened as a result of a turn, as returned by [`ChoDaiDi::advance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnOutcome {
+    /// The turn passed to the next player
+    TurnTaken,
+    /// The round ended and a new one started. Whoever made the last play leads it.
+    RoundEnded,
+    /// The player emptied their hand, ending the game
+    GameEnded,
+}
+
+/// A turn taken during a game, as returned by [`ChoDaiDi::history`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnRecord<const PLAYERS: usize = FOUR_PLAYERS> {
+    /// The seat of the player who took the turn
+    pub player: usize,
+    pub action: TurnAction<PLAYERS>,
+    /// The number of turns taken before this one in the game, starting from 0
+    pub turn_number: usize,
+    /// The number of rounds that ended before this turn, starting from 0
+    pub round_number: usize,
+}
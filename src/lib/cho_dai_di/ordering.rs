@@ -0,0 +1,384 @@
+use super::Combination;
+use crate::{
+    card::{Card, STANDARD_DECK},
+    collections::Cards,
+    rank::Rank,
+    suit::Suit,
+};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// The precedence of ranks in Cho Dai Di, from lowest to highest.
+pub(crate) const RANK_PRECEDENCE: [Rank; 13] = [
+    Rank::Three,
+    Rank::Four,
+    Rank::Five,
+    Rank::Six,
+    Rank::Seven,
+    Rank::Eight,
+    Rank::Nine,
+    Rank::Ten,
+    Rank::Jack,
+    Rank::Queen,
+    Rank::King,
+    Rank::Ace,
+    Rank::Two,
+];
+
+/// The position of every rank in [`RANK_PRECEDENCE`], looked up by the rank's discriminant.
+const RANK_INDICES: [u8; 13] = {
+    let mut indices = [0; 13];
+    let mut i = 0;
+    while i < RANK_PRECEDENCE.len() {
+        indices[RANK_PRECEDENCE[i] as usize] = i as u8;
+        i += 1;
+    }
+    indices
+};
+
+/// The position of a rank in Cho Dai Di precedence, from 0 for the 3 up to 12 for the 2.
+pub fn rank_index(rank: Rank) -> u8 {
+    RANK_INDICES[rank as usize]
+}
+
+/// The order that suits break ties in. Regional variants of the game disagree on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SuitPrecedence {
+    /// ♦ ♣ ♥ ♠, from lowest to highest
+    #[default]
+    Standard,
+    /// ♠ ♣ ♦ ♥, as in Tiến lên
+    Vietnamese,
+    /// ♣ ♥ ♠ ♦, with diamonds promoted from lowest to highest
+    DiamondsHigh,
+}
+
+impl SuitPrecedence {
+    /// Get the suits from lowest to highest.
+    pub const fn suits(&self) -> [Suit; 4] {
+        match self {
+            SuitPrecedence::Standard => [Suit::Diamonds, Suit::Clubs, Suit::Hearts, Suit::Spades],
+            SuitPrecedence::Vietnamese => [Suit::Spades, Suit::Clubs, Suit::Diamonds, Suit::Hearts],
+            SuitPrecedence::DiamondsHigh => {
+                [Suit::Clubs, Suit::Hearts, Suit::Spades, Suit::Diamonds]
+            }
+        }
+    }
+}
+
+/// The position of every suit in each [`SuitPrecedence`]'s order, looked up by the
+/// precedence's and then the suit's discriminant.
+const SUIT_INDICES: [[u8; 4]; 3] = {
+    const fn indices(precedence: SuitPrecedence) -> [u8; 4] {
+        let suits = precedence.suits();
+        let mut indices = [0; 4];
+        let mut i = 0;
+        while i < suits.len() {
+            indices[suits[i] as usize] = i as u8;
+            i += 1;
+        }
+        indices
+    }
+
+    [
+        indices(SuitPrecedence::Standard),
+        indices(SuitPrecedence::Vietnamese),
+        indices(SuitPrecedence::DiamondsHigh),
+    ]
+};
+
+impl SuitPrecedence {
+    /// The position of a suit in this order, from 0 for the lowest up to 3 for the highest.
+    pub fn suit_index(&self, suit: Suit) -> u8 {
+        SUIT_INDICES[*self as usize][suit as usize]
+    }
+}
+
+impl fmt::Display for SuitPrecedence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuitPrecedence::Standard => write!(f, "standard"),
+            SuitPrecedence::Vietnamese => write!(f, "vietnamese"),
+            SuitPrecedence::DiamondsHigh => write!(f, "diamonds-high"),
+        }
+    }
+}
+
+impl FromStr for SuitPrecedence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(SuitPrecedence::Standard),
+            "vietnamese" => Ok(SuitPrecedence::Vietnamese),
+            "diamonds-high" => Ok(SuitPrecedence::DiamondsHigh),
+            _ => anyhow::bail!(
+                "unknown suit precedence '{s}', try 'standard', 'vietnamese', or 'diamonds-high'"
+            ),
+        }
+    }
+}
+
+/// The order that cards are compared in during a game.
+///
+/// Normally this is the standard Cho Dai Di order, but some variants temporarily
+/// invert the order of ranks (a "revolution") after certain plays, or rank the kinds
+/// of five-card hands differently.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CardOrdering {
+    ranks_inverted: bool,
+    flush_beats_full_house: bool,
+    suit_precedence: SuitPrecedence,
+}
+
+impl CardOrdering {
+    /// The standard Cho Dai Di order, from the three of diamonds up to the two of spades.
+    pub const STANDARD: CardOrdering = CardOrdering {
+        ranks_inverted: false,
+        flush_beats_full_house: false,
+        suit_precedence: SuitPrecedence::Standard,
+    };
+
+    /// Returns true if the order of ranks is currently inverted.
+    pub fn ranks_inverted(&self) -> bool {
+        self.ranks_inverted
+    }
+
+    /// Flip the order of ranks. Calling this twice restores the original order.
+    pub fn invert_ranks(&mut self) {
+        self.ranks_inverted = !self.ranks_inverted;
+    }
+
+    /// Returns true if a flush beats a full house, instead of the other way around.
+    pub fn flush_beats_full_house(&self) -> bool {
+        self.flush_beats_full_house
+    }
+
+    /// Choose whether a flush beats a full house, as it does in some regional variants.
+    pub fn set_flush_beats_full_house(&mut self, enabled: bool) {
+        self.flush_beats_full_house = enabled;
+    }
+
+    /// Get the order that suits break ties in.
+    pub fn suit_precedence(&self) -> SuitPrecedence {
+        self.suit_precedence
+    }
+
+    /// Change the order that suits break ties in.
+    pub fn set_suit_precedence(&mut self, precedence: SuitPrecedence) {
+        self.suit_precedence = precedence;
+    }
+
+    /// Compare two combinations of five-card hand.
+    pub fn cmp_five_card_hand(&self, a: &Combination, b: &Combination) -> Ordering {
+        use Combination::{Flush, FullHouse};
+
+        match (a, b) {
+            (Flush, FullHouse) | (FullHouse, Flush) if self.flush_beats_full_house => {
+                a.cmp(b).reverse()
+            }
+            _ => a.cmp(b),
+        }
+    }
+
+    /// A key that sorts ranks in this order, from 0 for the lowest up to 12 for the highest.
+    pub fn rank_key(&self, rank: Rank) -> u8 {
+        let index = rank_index(rank);
+        if self.ranks_inverted {
+            12 - index
+        } else {
+            index
+        }
+    }
+
+    /// A key that sorts cards in this order, from 0 for the lowest up to 51 for the highest.
+    pub fn card_key(&self, card: &Card) -> u8 {
+        self.rank_key(card.rank()) * 4 + self.suit_precedence.suit_index(card.suit())
+    }
+
+    /// Compare two suits by their precedence.
+    ///
+    /// Suits are only ever used to break ties, so they're unaffected by revolutions.
+    pub fn cmp_suit(&self, a: &Suit, b: &Suit) -> Ordering {
+        let precedence = self.suit_precedence;
+        precedence.suit_index(*a).cmp(&precedence.suit_index(*b))
+    }
+
+    /// Compare two ranks by their precedence.
+    pub fn cmp_rank(&self, a: &Rank, b: &Rank) -> Ordering {
+        self.rank_key(*a).cmp(&self.rank_key(*b))
+    }
+
+    /// Compare two cards by rank, using suit as the tie-breaker.
+    pub fn cmp_card(&self, a: &Card, b: &Card) -> Ordering {
+        self.card_key(a).cmp(&self.card_key(b))
+    }
+
+    /// Get the lowest card that's higher than `card`, or `None` if `card` is the highest.
+    pub fn next_card_above(&self, card: &Card) -> Option<Card> {
+        STANDARD_DECK
+            .into_iter()
+            .filter(|c| self.cmp_card(c, card) == Ordering::Greater)
+            .min_by(|a, b| self.cmp_card(a, b))
+    }
+
+    /// Get the lowest card of any rank higher than `rank`, or `None` if `rank` is the highest.
+    pub fn next_card_of_higher_rank(&self, rank: &Rank) -> Option<Card> {
+        STANDARD_DECK
+            .into_iter()
+            .filter(|c| self.cmp_rank(&c.rank(), rank) == Ordering::Greater)
+            .min_by(|a, b| self.cmp_card(a, b))
+    }
+
+    /// Get the highest card in a collection of cards.
+    pub fn highest_card<'a, G>(&self, cards: &'a Cards<G>) -> Option<&'a Card> {
+        cards.iter().max_by_key(|card| self.card_key(card))
+    }
+
+    /// Get the lowest card in a collection of cards.
+    pub fn lowest_card<'a, G>(&self, cards: &'a Cards<G>) -> Option<&'a Card> {
+        cards.iter().min_by_key(|card| self.card_key(card))
+    }
+
+    /// The strength of a card relative to the rest of the deck in this order, from `0.0`
+    /// for the lowest card to `1.0` for the highest.
+    pub fn card_strength(&self, card: &Card) -> f32 {
+        self.card_key(card) as f32 / 51.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invert_ranks() {
+        let mut ordering = CardOrdering::STANDARD;
+        assert_eq!(
+            ordering.cmp_card(&Card::THREE_OF_DIAMONDS, &Card::TWO_OF_SPADES),
+            Ordering::Less
+        );
+
+        ordering.invert_ranks();
+        assert!(ordering.ranks_inverted());
+        assert_eq!(
+            ordering.cmp_card(&Card::THREE_OF_DIAMONDS, &Card::TWO_OF_SPADES),
+            Ordering::Greater
+        );
+        // Suits still break ties in the usual order
+        assert_eq!(
+            ordering.cmp_card(&Card::THREE_OF_DIAMONDS, &Card::THREE_OF_SPADES),
+            Ordering::Less
+        );
+
+        ordering.invert_ranks();
+        assert_eq!(ordering, CardOrdering::STANDARD);
+    }
+
+    #[test]
+    fn test_flush_beats_full_house() {
+        let mut ordering = CardOrdering::STANDARD;
+        let cmp = |ordering: &CardOrdering, a, b| ordering.cmp_five_card_hand(&a, &b);
+        assert_eq!(
+            cmp(&ordering, Combination::Flush, Combination::FullHouse),
+            Ordering::Less
+        );
+
+        ordering.set_flush_beats_full_house(true);
+        assert_eq!(
+            cmp(&ordering, Combination::Flush, Combination::FullHouse),
+            Ordering::Greater
+        );
+        // The other kinds of hand keep their usual order
+        assert_eq!(
+            cmp(&ordering, Combination::Straight, Combination::Flush),
+            Ordering::Less
+        );
+        assert_eq!(
+            cmp(
+                &ordering,
+                Combination::Flush,
+                Combination::FourOfAKindPlusOne
+            ),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_suit_precedence() {
+        let mut ordering = CardOrdering::STANDARD;
+        assert_eq!(
+            ordering.cmp_card(&Card::THREE_OF_DIAMONDS, &Card::THREE_OF_SPADES),
+            Ordering::Less
+        );
+
+        ordering.set_suit_precedence(SuitPrecedence::Vietnamese);
+        assert_eq!(
+            ordering.cmp_card(&Card::THREE_OF_DIAMONDS, &Card::THREE_OF_SPADES),
+            Ordering::Greater
+        );
+        assert_eq!(
+            ordering.next_card_above(&Card::KING_OF_HEARTS),
+            Some(Card::ACE_OF_SPADES)
+        );
+        // Ranks still come first
+        assert_eq!(
+            ordering.cmp_card(&Card::THREE_OF_HEARTS, &Card::FOUR_OF_SPADES),
+            Ordering::Less
+        );
+
+        for precedence in [
+            SuitPrecedence::Standard,
+            SuitPrecedence::Vietnamese,
+            SuitPrecedence::DiamondsHigh,
+        ] {
+            assert_eq!(
+                precedence.to_string().parse::<SuitPrecedence>().unwrap(),
+                precedence
+            );
+        }
+    }
+
+    #[test]
+    fn test_keys_agree_with_precedence() {
+        for (i, rank) in RANK_PRECEDENCE.into_iter().enumerate() {
+            assert_eq!(rank_index(rank) as usize, i);
+        }
+        for precedence in [
+            SuitPrecedence::Standard,
+            SuitPrecedence::Vietnamese,
+            SuitPrecedence::DiamondsHigh,
+        ] {
+            for (i, suit) in precedence.suits().into_iter().enumerate() {
+                assert_eq!(precedence.suit_index(suit) as usize, i);
+            }
+        }
+
+        let mut ordering = CardOrdering::STANDARD;
+        assert_eq!(ordering.card_key(&Card::THREE_OF_DIAMONDS), 0);
+        assert_eq!(ordering.card_key(&Card::TWO_OF_SPADES), 51);
+        ordering.invert_ranks();
+        assert_eq!(ordering.card_key(&Card::TWO_OF_SPADES), 3);
+    }
+
+    #[test]
+    fn test_next_card_above() {
+        let ordering = CardOrdering::STANDARD;
+        assert_eq!(
+            ordering.next_card_above(&Card::THREE_OF_DIAMONDS),
+            Some(Card::THREE_OF_CLUBS)
+        );
+        assert_eq!(
+            ordering.next_card_above(&Card::KING_OF_SPADES),
+            Some(Card::ACE_OF_DIAMONDS)
+        );
+        assert_eq!(ordering.next_card_above(&Card::TWO_OF_SPADES), None);
+
+        assert_eq!(
+            ordering.next_card_of_higher_rank(&Rank::Nine),
+            Some(Card::TEN_OF_DIAMONDS)
+        );
+        assert_eq!(ordering.next_card_of_higher_rank(&Rank::Two), None);
+    }
+}
@@ -0,0 +1,247 @@
+use super::{ChoDaiDi, Combination, TurnAction};
+use crate::{collections::Cards, rank::Rank};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// How the cards left in each losing player's hand are counted against them.
+///
+/// Every card left in hand costs a point, or two points when 11 or more cards are left.
+/// The options below add the penalties of common Cantonese house rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringRules {
+    /// If true, the penalty doubles for each 2 left in a hand
+    pub twos_double_penalty: bool,
+    /// If true, a player left holding 13 or more cards loses three points per card
+    pub full_hand_triples_penalty: bool,
+    /// If true, the winner collects a bonus for each [`Slam`] they pull off
+    pub slam_bonuses: bool,
+}
+
+impl Default for ScoringRules {
+    fn default() -> Self {
+        ScoringRules {
+            twos_double_penalty: false,
+            full_hand_triples_penalty: true,
+            slam_bonuses: false,
+        }
+    }
+}
+
+impl ScoringRules {
+    /// The Cantonese house rules, with every penalty turned on.
+    pub const CANTONESE: ScoringRules = ScoringRules {
+        twos_double_penalty: true,
+        full_hand_triples_penalty: true,
+        slam_bonuses: false,
+    };
+
+    /// Given a hand size, calculate a score
+    ///
+    /// The score is calculated as follows:
+    /// - 10 or less cards in hand, -1 point per card
+    /// - 11-12 cards in hand, -2 points per card
+    /// - 13 or more cards in hand, -3 points per card if `full_hand_triples_penalty` is
+    ///   set, otherwise -2. Hands only start with more than 13 cards in games with fewer
+    ///   than four players.
+    pub fn hand_size_to_score(&self, hand_size: usize) -> isize {
+        let score = match hand_size {
+            0..=10 => hand_size as isize,
+            11..=12 => (hand_size as isize) * 2,
+            _ if self.full_hand_triples_penalty => (hand_size as isize) * 3,
+            _ => (hand_size as isize) * 2,
+        };
+
+        -score
+    }
+
+    /// The lowest score that a hand of `hand_size` cards can get, when it holds as many
+    /// 2s as it can.
+    pub fn worst_score(&self, hand_size: usize) -> isize {
+        let score = self.hand_size_to_score(hand_size);
+        if !self.twos_double_penalty {
+            return score;
+        }
+
+        score * 2isize.pow(hand_size.min(4) as u32)
+    }
+
+    /// Calculate the score for the cards left in a hand, including the penalty for any 2s.
+    pub fn score_hand<const PLAYERS: usize>(&self, hand: &Cards<ChoDaiDi<PLAYERS>>) -> isize {
+        let score = self.hand_size_to_score(hand.len());
+        if !self.twos_double_penalty {
+            return score;
+        }
+
+        let twos = hand.iter().filter(|card| card.rank() == Rank::Two).count();
+        score * 2isize.pow(twos as u32)
+    }
+
+    /// The slams that the winner can collect a bonus for, which are none unless
+    /// `slam_bonuses` is set.
+    pub fn slams(&self) -> &'static [Slam] {
+        match self.slam_bonuses {
+            true => &Slam::ALL,
+            false => &[],
+        }
+    }
+
+    /// The most that each losing player can pay the winner in bonuses, if the winner
+    /// pulls off every slam at once.
+    pub fn largest_bonus(&self) -> isize {
+        self.slams().iter().map(Slam::points).sum()
+    }
+}
+
+/// Something that earns the winner a bonus on top of everyone else's penalties, worked
+/// out from the game once it's over. The built-in [`Slam`]s are turned on with
+/// [`ScoringRules::slam_bonuses`], and house rules of your own can be added to a game
+/// with [`ChoDaiDi::add_score_modifier`].
+///
+/// ```
+/// use card_games::prelude::*;
+///
+/// /// A point from everyone for winning without passing once
+/// struct NeverPassed;
+///
+/// impl<const PLAYERS: usize> ScoreModifier<PLAYERS> for NeverPassed {
+///     fn name(&self) -> String {
+///         "winning without passing".to_owned()
+///     }
+///
+///     fn bonus(&self, game: &ChoDaiDi<PLAYERS>, winner: usize) -> Option<isize> {
+///         let passed = game.history().iter().any(|turn| {
+///             turn.player == winner && turn.action == TurnAction::Pass
+///         });
+///         (!passed).then_some(1)
+///     }
+/// }
+///
+/// let mut game = new_4p_game();
+/// game.add_score_modifier(NeverPassed);
+/// ```
+pub trait ScoreModifier<const PLAYERS: usize>: Send {
+    /// What the bonus is for, as shown in the breakdown of the scores
+    fn name(&self) -> String;
+
+    /// The points that each other player pays `winner` if they earned the bonus in
+    /// `game`, which has ended.
+    fn bonus(&self, game: &ChoDaiDi<PLAYERS>, winner: usize) -> Option<isize>;
+}
+
+/// A bonus that the winner of a game collected, as returned by [`ChoDaiDi::bonuses`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bonus {
+    /// What the bonus was for
+    pub name: String,
+    /// The seat of the player who collected it
+    pub player: usize,
+    /// The points that each other player paid for it
+    pub points: isize,
+}
+
+/// The house rules' slams: ways of winning that are worth a bonus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Slam {
+    /// Winning by playing a straight flush as the last cards in hand
+    StraightFlushFinish,
+    /// Winning every round of the game, so that no one else ever led
+    EveryTrick,
+}
+
+impl Slam {
+    pub const ALL: [Slam; 2] = [Slam::StraightFlushFinish, Slam::EveryTrick];
+
+    /// The points that each other player pays the winner for the slam
+    pub fn points(&self) -> isize {
+        match self {
+            Slam::StraightFlushFinish => 5,
+            Slam::EveryTrick => 10,
+        }
+    }
+}
+
+impl fmt::Display for Slam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slam = match self {
+            Slam::StraightFlushFinish => "finishing with a straight flush",
+            Slam::EveryTrick => "taking every trick",
+        };
+        write!(f, "{slam}")
+    }
+}
+
+impl<const PLAYERS: usize> ScoreModifier<PLAYERS> for Slam {
+    fn name(&self) -> String {
+        self.to_string()
+    }
+
+    fn bonus(&self, game: &ChoDaiDi<PLAYERS>, winner: usize) -> Option<isize> {
+        let earned = match self {
+            Slam::StraightFlushFinish => game.history().last().is_some_and(|turn| {
+                let TurnAction::Play(cards) = &turn.action else {
+                    return false;
+                };
+                let combination = cards.classify_under(&game.ordering());
+                turn.player == winner
+                    && combination.is_some_and(|(it, _)| it == Combination::StraightFlush)
+            }),
+            Slam::EveryTrick => {
+                // Whoever played last in a round took it
+                let taken_by: BTreeMap<usize, usize> = game
+                    .history()
+                    .iter()
+                    .filter(|turn| matches!(turn.action, TurnAction::Play(_)))
+                    .map(|turn| (turn.round_number, turn.player))
+                    .collect();
+                !taken_by.is_empty() && taken_by.values().all(|player| *player == winner)
+            }
+        };
+
+        earned.then_some(self.points())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScoringRules;
+    use crate::{cho_dai_di::ChoDaiDi, collections::Cards};
+
+    #[test]
+    fn test_hand_size_to_score() {
+        let rules = ScoringRules::default();
+        assert_eq!(rules.hand_size_to_score(0), 0);
+        assert_eq!(rules.hand_size_to_score(10), -10);
+        assert_eq!(rules.hand_size_to_score(12), -24);
+        assert_eq!(rules.hand_size_to_score(13), -39);
+        // Three- and two-player games deal 17 and 26 cards
+        assert_eq!(rules.hand_size_to_score(17), -51);
+        assert_eq!(rules.hand_size_to_score(26), -78);
+
+        let rules = ScoringRules {
+            full_hand_triples_penalty: false,
+            ..ScoringRules::default()
+        };
+        assert_eq!(rules.hand_size_to_score(13), -26);
+    }
+
+    #[test]
+    fn test_score_hand() {
+        let hand: Cards<ChoDaiDi> = Cards::try_from(vec!["3D", "2S", "2H"]).unwrap();
+        assert_eq!(ScoringRules::default().score_hand(&hand), -3);
+        assert_eq!(ScoringRules::CANTONESE.score_hand(&hand), -12);
+        assert_eq!(
+            ScoringRules::CANTONESE.score_hand(&Cards::<ChoDaiDi>::from(Vec::new())),
+            0
+        );
+    }
+
+    #[test]
+    fn test_worst_score() {
+        assert_eq!(ScoringRules::default().worst_score(13), -39);
+        assert_eq!(ScoringRules::CANTONESE.worst_score(13), -39 * 16);
+        assert_eq!(ScoringRules::CANTONESE.worst_score(2), -8);
+        assert_eq!(ScoringRules::CANTONESE.worst_score(0), 0);
+    }
+}
@@ -0,0 +1,132 @@
+use super::{CardOrdering, ChoDaiDi, PlayError, TurnRecord, FOUR_PLAYERS};
+use crate::collections::{CardSet, Cards};
+use uuid::Uuid;
+
+/// What a player can see of a game: everything except the other players' hands.
+///
+/// Players are given a view rather than the whole [`ChoDaiDi`], so a computer player
+/// can't peek at anyone else's cards. Get one with [`ChoDaiDi::view`].
+#[derive(Clone, Copy)]
+pub struct GameView<'a, const PLAYERS: usize = FOUR_PLAYERS> {
+    game: &'a ChoDaiDi<PLAYERS>,
+}
+
+impl<const PLAYERS: usize> ChoDaiDi<PLAYERS> {
+    /// Get the parts of this game that every player can see.
+    pub fn view(&self) -> GameView<'_, PLAYERS> {
+        GameView { game: self }
+    }
+}
+
+impl<const PLAYERS: usize> GameView<'_, PLAYERS> {
+    /// Get the unique ID of the game
+    pub fn id(&self) -> Uuid {
+        self.game.id()
+    }
+
+    /// Returns true if the game is rated, so hints and other assists are off.
+    pub fn is_rated(&self) -> bool {
+        self.game.is_rated()
+    }
+
+    /// Get the last play
+    pub fn last_play(&self) -> Option<Cards<ChoDaiDi<PLAYERS>>> {
+        self.game.last_play()
+    }
+
+    /// Get every card played so far. The order they were played in is in the
+    /// [`history`](GameView::history).
+    pub fn card_pile(&self) -> CardSet {
+        self.game.card_pile
+    }
+
+    /// Get the number of cards in each player's hand, by seat
+    pub fn hand_sizes(&self) -> [usize; PLAYERS] {
+        self.game.hands.map(|hand| hand.len())
+    }
+
+    /// Get the cards that `player` has had to reveal for passing when they could have
+    /// led, and still holds.
+    pub fn revealed_cards(&self, player: usize) -> Cards<ChoDaiDi<PLAYERS>> {
+        self.game.revealed_cards(player)
+    }
+
+    /// Get the number of penalty markers each player has drawn, by seat.
+    pub fn penalty_markers(&self) -> [usize; PLAYERS] {
+        self.game.penalty_markers()
+    }
+
+    /// Get the current player's turn
+    pub fn whose_turn(&self) -> usize {
+        self.game.whose_turn()
+    }
+
+    /// Get every turn taken so far, oldest first.
+    pub fn history(&self) -> &[TurnRecord<PLAYERS>] {
+        self.game.history()
+    }
+
+    /// Get the number of passes since the last play.
+    pub fn pass_counter(&self) -> usize {
+        self.game.pass_counter()
+    }
+
+    /// Get the number of players
+    pub fn number_of_players(&self) -> usize {
+        PLAYERS
+    }
+
+    /// Get the order that cards are currently compared in.
+    pub fn ordering(&self) -> CardOrdering {
+        self.game.ordering()
+    }
+
+    /// Get the next `count` seats to act after the current player, in order.
+    pub fn upcoming_turn_order(&self, count: usize) -> Vec<usize> {
+        self.game.upcoming_turn_order(count)
+    }
+
+    /// Calculate what each player would score if the game ended right now.
+    pub fn provisional_scores(&self) -> [isize; PLAYERS] {
+        self.game.provisional_scores()
+    }
+
+    /// Check if a play is valid.
+    pub fn is_valid_play(&self, cards: &Cards<ChoDaiDi<PLAYERS>>) -> Result<(), PlayError> {
+        self.game.is_valid_play(cards)
+    }
+
+    /// Calculate the possible plays from a given hand. See [`ChoDaiDi::possible_plays`].
+    pub fn possible_plays(&self, hand: &Cards<ChoDaiDi<PLAYERS>>) -> Vec<Cards<ChoDaiDi<PLAYERS>>> {
+        self.game.possible_plays(hand)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        card::Card,
+        cho_dai_di::{new_4p_game, TurnAction},
+        collections::Cards,
+    };
+
+    #[test]
+    fn test_view() {
+        let mut game = new_4p_game();
+        let opener = game.whose_turn();
+        game.advance(TurnAction::Play(Cards::from(Card::THREE_OF_DIAMONDS)))
+            .unwrap();
+
+        let view = game.view();
+        let mut hand_sizes = [13; 4];
+        hand_sizes[opener] = 12;
+        assert_eq!(view.hand_sizes(), hand_sizes);
+        assert_eq!(
+            view.card_pile().iter().collect::<Vec<_>>(),
+            [Card::THREE_OF_DIAMONDS]
+        );
+        assert_eq!(view.whose_turn(), (opener + 1) % 4);
+        assert_eq!(view.history().len(), 1);
+        assert_eq!(view.last_play(), Some(Cards::from(Card::THREE_OF_DIAMONDS)));
+    }
+}
@@ -1,6 +1,10 @@
+mod card_set;
+
 use crate::card::Card;
 use anyhow::bail;
+pub use card_set::CardSet;
 use itertools::Itertools;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{cmp::Ordering, fmt, marker::PhantomData, str::FromStr};
 
 /// A collection of cards.
@@ -71,6 +75,10 @@ impl<G> Cards<G> {
         }
     }
 
+    pub fn push(&mut self, card: Card) {
+        self.inner.push(card);
+    }
+
     pub fn retain(&mut self, f: impl FnMut(&Card) -> bool) {
         self.inner.retain(f);
     }
@@ -119,23 +127,35 @@ impl<G> Cards<G> {
         }
     }
 
-    pub(crate) fn permutations(&self, k: usize) -> impl Iterator<Item = Cards<G>> + '_ {
-        self.inner.iter().permutations(k).map(|it| Cards {
-            inner: it.into_iter().cloned().collect(),
-            _game: PhantomData,
-        })
+    /// Every way of choosing `k` of these cards, in the order they're held. Each choice is
+    /// only made once, even if a card is held more than once.
+    pub(crate) fn combinations(&self, k: usize) -> impl Iterator<Item = Cards<G>> + '_ {
+        self.inner
+            .iter()
+            .enumerate()
+            .filter(|(i, card)| !self.inner[..*i].contains(card))
+            .map(|(_, card)| card)
+            .combinations(k)
+            .map(|it| Cards {
+                inner: it.into_iter().cloned().collect(),
+                _game: PhantomData,
+            })
     }
 }
 
+#[cfg(not(feature = "strict-api"))]
+#[deprecated(note = "use `Cards::is_a_pair` instead")]
 pub fn is_a_pair(cards: [&Card; 2]) -> bool {
     cards[0].rank() == cards[1].rank()
 }
 
+#[cfg(not(feature = "strict-api"))]
+#[deprecated(note = "use `Cards::is_a_triplet` instead")]
 pub fn is_a_triplet(cards: [&Card; 3]) -> bool {
     cards[0].rank() == cards[1].rank() && cards[1].rank() == cards[2].rank()
 }
 
-pub fn is_four_of_a_kind_plus_one(cards: [&Card; 5]) -> bool {
+pub(crate) fn is_four_of_a_kind_plus_one(cards: [&Card; 5]) -> bool {
     let mut a = (None, 0);
     let mut b = (None, 0);
 
@@ -152,7 +172,7 @@ pub fn is_four_of_a_kind_plus_one(cards: [&Card; 5]) -> bool {
     (a.1 == 4 && b.1 == 1) || (a.1 == 1 && b.1 == 4)
 }
 
-pub fn is_a_full_house(cards: [&Card; 5]) -> bool {
+pub(crate) fn is_a_full_house(cards: [&Card; 5]) -> bool {
     let mut a = (None, 0);
     let mut b = (None, 0);
 
@@ -169,12 +189,13 @@ pub fn is_a_full_house(cards: [&Card; 5]) -> bool {
     (a.1 == 3 && b.1 == 2) || (a.1 == 2 && b.1 == 3)
 }
 
-pub fn is_flush(cards: [&Card; 5]) -> bool {
+pub(crate) fn is_flush(cards: [&Card; 5]) -> bool {
     let suit = cards[0].suit();
     cards.iter().all(|it| it.suit() == suit)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SortCardsBy {
     Rank,
     Suit,
@@ -211,6 +232,18 @@ impl<G> fmt::Display for Cards<G> {
     }
 }
 
+impl<G> Serialize for Cards<G> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.inner.serialize(serializer)
+    }
+}
+
+impl<'de, G> Deserialize<'de> for Cards<G> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Cards::from)
+    }
+}
+
 impl<G> From<Vec<Card>> for Cards<G> {
     fn from(inner: Vec<Card>) -> Self {
         Cards {
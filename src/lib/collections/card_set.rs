@@ -0,0 +1,211 @@
+use super::Cards;
+use crate::card::{Card, STANDARD_DECK};
+use std::{fmt, marker::PhantomData};
+
+/// A set of cards, one bit per card of the standard deck.
+///
+/// Checking for a card, and taking the union or difference of two sets, take the same
+/// time however many cards are held. Cards are iterated in the order of
+/// [`STANDARD_DECK`].
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CardSet {
+    bits: u64,
+}
+
+/// The bit that stands for `card`, its position in [`STANDARD_DECK`].
+fn bit(card: &Card) -> u64 {
+    1 << (card.suit() as u32 * 13 + card.rank() as u32)
+}
+
+impl CardSet {
+    pub const EMPTY: CardSet = CardSet { bits: 0 };
+
+    pub fn new() -> Self {
+        Self::EMPTY
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn contains(&self, card: &Card) -> bool {
+        self.bits & bit(card) != 0
+    }
+
+    /// Add a card to the set, returning false if it was already there.
+    pub fn insert(&mut self, card: Card) -> bool {
+        let had_card = self.contains(&card);
+        self.bits |= bit(&card);
+        !had_card
+    }
+
+    /// Take a card out of the set, returning false if it wasn't there.
+    pub fn remove(&mut self, card: &Card) -> bool {
+        let had_card = self.contains(card);
+        self.bits &= !bit(card);
+        had_card
+    }
+
+    /// Get the cards in either set.
+    pub fn union(&self, other: &CardSet) -> CardSet {
+        CardSet {
+            bits: self.bits | other.bits,
+        }
+    }
+
+    /// Get the cards in this set that aren't in `other`.
+    pub fn difference(&self, other: &CardSet) -> CardSet {
+        CardSet {
+            bits: self.bits & !other.bits,
+        }
+    }
+
+    /// Get the cards in both sets.
+    pub fn intersection(&self, other: &CardSet) -> CardSet {
+        CardSet {
+            bits: self.bits & other.bits,
+        }
+    }
+
+    /// Returns true if every card in this set is also in `other`.
+    pub fn is_subset(&self, other: &CardSet) -> bool {
+        self.bits & !other.bits == 0
+    }
+
+    pub fn iter(&self) -> Iter {
+        Iter { bits: self.bits }
+    }
+}
+
+/// The cards in a [`CardSet`], in the order of [`STANDARD_DECK`].
+#[derive(Debug, Clone)]
+pub struct Iter {
+    bits: u64,
+}
+
+impl Iterator for Iter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.bits == 0 {
+            return None;
+        }
+        let card = STANDARD_DECK[self.bits.trailing_zeros() as usize];
+        // Clear the lowest bit
+        self.bits &= self.bits - 1;
+        Some(card)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bits.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Iter {}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> Self {
+        let mut set = CardSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Card> for CardSet {
+    fn extend<I: IntoIterator<Item = Card>>(&mut self, iter: I) {
+        for card in iter {
+            self.insert(card);
+        }
+    }
+}
+
+impl<G> From<&Cards<G>> for CardSet {
+    fn from(cards: &Cards<G>) -> Self {
+        cards.iter().copied().collect()
+    }
+}
+
+impl<G> From<Cards<G>> for CardSet {
+    fn from(cards: Cards<G>) -> Self {
+        CardSet::from(&cards)
+    }
+}
+
+impl<G> From<CardSet> for Cards<G> {
+    fn from(set: CardSet) -> Self {
+        Cards {
+            inner: set.iter().collect(),
+            _game: PhantomData,
+        }
+    }
+}
+
+impl fmt::Debug for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CardSet;
+    use crate::{
+        card::{Card, STANDARD_DECK},
+        cho_dai_di::ChoDaiDi,
+        collections::Cards,
+    };
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut set = CardSet::new();
+        assert!(set.insert(Card::KING_OF_HEARTS));
+        assert!(!set.insert(Card::KING_OF_HEARTS));
+        assert!(set.insert(Card::TWO_OF_DIAMONDS));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&Card::KING_OF_HEARTS));
+        assert!(!set.contains(&Card::KING_OF_SPADES));
+
+        assert!(set.remove(&Card::KING_OF_HEARTS));
+        assert!(!set.remove(&Card::KING_OF_HEARTS));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [Card::TWO_OF_DIAMONDS]);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let cards = |cards: Vec<&str>| CardSet::from(Cards::<ChoDaiDi>::try_from(cards).unwrap());
+        let a = cards(vec!["3D", "4C", "5H"]);
+        let b = cards(vec!["5H", "6S"]);
+
+        assert_eq!(a.union(&b), cards(vec!["3D", "4C", "5H", "6S"]));
+        assert_eq!(a.difference(&b), cards(vec!["3D", "4C"]));
+        assert_eq!(a.intersection(&b), cards(vec!["5H"]));
+        assert!(cards(vec!["4C", "3D"]).is_subset(&a));
+        assert!(!b.is_subset(&a));
+        assert!(CardSet::EMPTY.is_subset(&b));
+    }
+
+    #[test]
+    fn test_every_card() {
+        let deck: CardSet = STANDARD_DECK.into_iter().collect();
+        assert_eq!(deck.len(), 52);
+        assert_eq!(deck.iter().collect::<Vec<_>>(), STANDARD_DECK);
+        assert_eq!(
+            Cards::<ChoDaiDi>::from(deck),
+            Cards::from(STANDARD_DECK.to_vec())
+        );
+    }
+}
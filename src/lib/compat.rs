@@ -0,0 +1,61 @@
+//! Deprecated shims for items that have been renamed, moved, or made private.
+//!
+//! Each shim is kept for one release after the change so that code using the old API
+//! keeps compiling, with a deprecation warning pointing at the replacement. Build with
+//! the `strict-api` feature to leave this module out and check that you've upgraded.
+
+use crate::card::Card;
+
+#[allow(deprecated)]
+pub use crate::collections::{is_a_pair, is_a_triplet};
+
+#[deprecated(note = "use `Cards::is_four_of_a_kind_plus_one` instead")]
+pub fn is_four_of_a_kind_plus_one(cards: [&Card; 5]) -> bool {
+    crate::collections::is_four_of_a_kind_plus_one(cards)
+}
+
+#[deprecated(note = "use `Cards::is_a_full_house` instead")]
+pub fn is_a_full_house(cards: [&Card; 5]) -> bool {
+    crate::collections::is_a_full_house(cards)
+}
+
+#[deprecated(note = "use `Cards::is_a_flush` instead")]
+pub fn is_flush(cards: [&Card; 5]) -> bool {
+    crate::collections::is_flush(cards)
+}
+
+#[deprecated(note = "use `ScoringRules::hand_size_to_score` instead")]
+pub fn hand_size_to_score(hand_size: usize) -> isize {
+    crate::cho_dai_di::ScoringRules::default().hand_size_to_score(hand_size)
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use crate::{
+        cho_dai_di::{ChoDaiDi, ScoringRules, SuitPrecedence},
+        collections::Cards,
+    };
+
+    // The shims have to keep compiling, and keep meaning what they used to
+    #[test]
+    fn test_shims() {
+        assert_eq!(
+            Cards::<ChoDaiDi>::SUIT_PRECEDENCE,
+            SuitPrecedence::Standard.suits()
+        );
+        assert_eq!(
+            super::hand_size_to_score(5),
+            ScoringRules::default().hand_size_to_score(5)
+        );
+
+        let hand: Cards<ChoDaiDi> = "3♦, 3♣, 3♥, 3♠, 4♦".parse().unwrap();
+        let cards: Vec<_> = hand.iter().collect();
+        let five = [cards[0], cards[1], cards[2], cards[3], cards[4]];
+        assert!(super::is_four_of_a_kind_plus_one(five));
+        assert!(!super::is_a_full_house(five));
+        assert!(!super::is_flush(five));
+        assert!(super::is_a_triplet([cards[0], cards[1], cards[2]]));
+        assert!(super::is_a_pair([cards[0], cards[1]]));
+    }
+}
@@ -0,0 +1,248 @@
+//! Datasets of the plays computer players chose, for training a model to play like them.
+//! Written by `dai-di simulate --dataset`.
+//!
+//! A dataset starts with the 8 bytes of [`MAGIC`], followed by a [`Record`] for every
+//! turn taken, each [`RECORD_LEN`] bytes long. Numbers are little-endian:
+//!
+//! | Bytes  | Field        | Type      |                                                   |
+//! |--------|--------------|-----------|---------------------------------------------------|
+//! | 0..8   | `hand`       | `u64`     | The cards the seat held                           |
+//! | 8..16  | `to_beat`    | `u64`     | The play to beat, or 0 when leading               |
+//! | 16..24 | `played`     | `u64`     | Every card played so far in the game              |
+//! | 24..27 | `hand_sizes` | `[u8; 3]` | Cards held by the next seat, the one after, and the one after that, or 0 past the last seat |
+//! | 27     | `passes`     | `u8`      | Passes since the last play                        |
+//! | 28     | `seat`       | `u8`      | The seat, counting from 0                         |
+//! | 29     | `players`    | `u8`      | The number of seats at the table                  |
+//! | 30..32 | `score`      | `i16`     | The seat's score once the game ended              |
+//! | 32..40 | `play`       | `u64`     | The cards the seat chose to play, or 0 to pass    |
+//!
+//! Sets of cards are bitmasks where bit `n` is the `n`th lowest card of the deck, counting
+//! from 0, in the order cards ranked in when the turn was taken. That order depends on
+//! the rules and changes after a revolution, so the encoding describes how strong each
+//! card is rather than which card it is. [`decode_cards`] turns a bitmask back into
+//! cards, e.g. to make a play a model chose.
+//!
+//! With NumPy, a dataset can be read with:
+//!
+//! ```python
+//! record = np.dtype([
+//!     ("hand", "<u8"), ("to_beat", "<u8"), ("played", "<u8"), ("hand_sizes", "u1", 3),
+//!     ("passes", "u1"), ("seat", "u1"), ("players", "u1"), ("score", "<i2"), ("play", "<u8"),
+//! ])
+//! records = np.fromfile("dataset.bin", dtype=record, offset=8)
+//! ```
+
+use crate::{
+    card::{Card, STANDARD_DECK},
+    cho_dai_di::{CardOrdering, ChoDaiDi, GameView},
+    collections::Cards,
+};
+use anyhow::Context;
+use std::io::{self, Read, Write};
+
+/// The bytes every dataset starts with, which end in the version of the format.
+pub const MAGIC: [u8; 8] = *b"DAIDI-D1";
+
+/// The length of each record in bytes.
+pub const RECORD_LEN: usize = 40;
+
+/// What a seat could see when it chose its play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Perspective {
+    pub hand: u64,
+    /// The play to beat, or 0 when leading
+    pub to_beat: u64,
+    /// Every card played so far in the game
+    pub played: u64,
+    /// The number of cards held by each seat after this one, in turn order. Seats past
+    /// the last one hold 0.
+    pub hand_sizes: [u8; 3],
+    /// The number of passes since the last play
+    pub passes: u8,
+    pub seat: u8,
+    pub players: u8,
+}
+
+impl Perspective {
+    /// What the seat whose turn it is can see, holding `hand`.
+    pub fn of<const PLAYERS: usize>(
+        view: &GameView<'_, PLAYERS>,
+        hand: &Cards<ChoDaiDi<PLAYERS>>,
+    ) -> Self {
+        let ordering = view.ordering();
+        let seat = view.whose_turn();
+        let sizes = view.hand_sizes();
+        let mut hand_sizes = [0; 3];
+        for (i, size) in hand_sizes.iter_mut().enumerate().take(PLAYERS - 1) {
+            *size = sizes[(seat + i + 1) % PLAYERS] as u8;
+        }
+
+        Perspective {
+            hand: encode_cards(hand.iter().copied(), &ordering),
+            to_beat: view
+                .last_play()
+                .map_or(0, |play| encode_cards(play.iter().copied(), &ordering)),
+            played: encode_cards(view.card_pile().iter(), &ordering),
+            hand_sizes,
+            passes: view.pass_counter() as u8,
+            seat: seat as u8,
+            players: PLAYERS as u8,
+        }
+    }
+}
+
+/// One turn: what the seat could see, what it played, and how its game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    pub perspective: Perspective,
+    /// The cards played, or 0 for a pass
+    pub play: u64,
+    pub score: i16,
+}
+
+impl Record {
+    pub fn to_bytes(&self) -> [u8; RECORD_LEN] {
+        let perspective = &self.perspective;
+        let mut bytes = [0; RECORD_LEN];
+        bytes[0..8].copy_from_slice(&perspective.hand.to_le_bytes());
+        bytes[8..16].copy_from_slice(&perspective.to_beat.to_le_bytes());
+        bytes[16..24].copy_from_slice(&perspective.played.to_le_bytes());
+        bytes[24..27].copy_from_slice(&perspective.hand_sizes);
+        bytes[27] = perspective.passes;
+        bytes[28] = perspective.seat;
+        bytes[29] = perspective.players;
+        bytes[30..32].copy_from_slice(&self.score.to_le_bytes());
+        bytes[32..40].copy_from_slice(&self.play.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; RECORD_LEN]) -> Self {
+        let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        Record {
+            perspective: Perspective {
+                hand: u64_at(0),
+                to_beat: u64_at(8),
+                played: u64_at(16),
+                hand_sizes: [bytes[24], bytes[25], bytes[26]],
+                passes: bytes[27],
+                seat: bytes[28],
+                players: bytes[29],
+            },
+            score: i16::from_le_bytes([bytes[30], bytes[31]]),
+            play: u64_at(32),
+        }
+    }
+}
+
+/// A bitmask of `cards`, where bit `n` is the `n`th lowest card in `ordering`.
+pub fn encode_cards(cards: impl IntoIterator<Item = Card>, ordering: &CardOrdering) -> u64 {
+    cards
+        .into_iter()
+        .fold(0, |bits, card| bits | 1 << ordering.card_key(&card))
+}
+
+/// The cards in a bitmask made by [`encode_cards`] with the same `ordering`, from lowest
+/// to highest.
+pub fn decode_cards<G>(bits: u64, ordering: &CardOrdering) -> Cards<G> {
+    let mut cards: Vec<_> = STANDARD_DECK
+        .iter()
+        .copied()
+        .filter(|card| bits & 1 << ordering.card_key(card) != 0)
+        .collect();
+    cards.sort_by_key(|card| ordering.card_key(card));
+    Cards::from(cards)
+}
+
+/// Start a dataset by writing [`MAGIC`]. Records are written after it with
+/// [`write_records`].
+pub fn write_header(writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(&MAGIC)
+}
+
+pub fn write_records(writer: &mut impl Write, records: &[Record]) -> io::Result<()> {
+    records
+        .iter()
+        .try_for_each(|record| writer.write_all(&record.to_bytes()))
+}
+
+/// Read a whole dataset back.
+pub fn read(reader: &mut impl Read) -> anyhow::Result<Vec<Record>> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .context("reading a dataset")?;
+    let Some(records) = bytes.strip_prefix(&MAGIC) else {
+        anyhow::bail!("this isn't a dataset, or it was written by another version");
+    };
+    if records.len() % RECORD_LEN != 0 {
+        anyhow::bail!("the dataset's last record was cut short");
+    }
+
+    Ok(records
+        .chunks_exact(RECORD_LEN)
+        .map(|chunk| Record::from_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cho_dai_di::new_4p_game;
+
+    #[test]
+    fn test_cards_round_trip() {
+        let cards = Cards::<ChoDaiDi>::try_from(vec!["3D", "9H", "2S"]).unwrap();
+        let ordering = CardOrdering::STANDARD;
+        let bits = encode_cards(cards.iter().copied(), &ordering);
+        // The 3♦ is the lowest card, and the 2♠ the highest
+        assert_eq!(bits & 1, 1);
+        assert_eq!(bits >> 51, 1);
+        assert_eq!(bits.count_ones(), 3);
+        assert_eq!(decode_cards::<ChoDaiDi>(bits, &ordering), cards);
+    }
+
+    #[test]
+    fn test_perspective() {
+        let mut game = new_4p_game();
+        let opener = game.whose_turn();
+        game.play_cards(Cards::from(Card::THREE_OF_DIAMONDS))
+            .unwrap();
+        game.increment_turn_counter();
+
+        let hand = game.get_current_players_hand();
+        let perspective = Perspective::of(&game.view(), &hand);
+        assert_eq!(perspective.seat as usize, game.whose_turn());
+        assert_eq!(perspective.players, 4);
+        assert_eq!(perspective.hand.count_ones(), 13);
+        assert_eq!(perspective.to_beat, 1);
+        assert_eq!(perspective.played, 1);
+        // The opener is the last to play after this seat
+        assert_eq!(perspective.hand_sizes, [13, 13, 12]);
+        assert_eq!((opener + 1) % 4, game.whose_turn());
+    }
+
+    #[test]
+    fn test_records_round_trip() {
+        let record = Record {
+            perspective: Perspective {
+                hand: 0b1011,
+                to_beat: 1 << 40,
+                played: u64::MAX >> 12,
+                hand_sizes: [13, 12, 0],
+                passes: 2,
+                seat: 1,
+                players: 3,
+            },
+            play: 0b1000,
+            score: -17,
+        };
+        let mut bytes = Vec::new();
+        write_header(&mut bytes).unwrap();
+        write_records(&mut bytes, &[record, record]).unwrap();
+        assert_eq!(bytes.len(), MAGIC.len() + 2 * RECORD_LEN);
+        assert_eq!(read(&mut &bytes[..]).unwrap(), [record, record]);
+
+        assert!(read(&mut &bytes[..bytes.len() - 1]).is_err());
+        assert!(read(&mut &bytes[1..]).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! Working out how a hand could be played out if no one else played.
+
+use crate::{
+    card::Card,
+    cho_dai_di::{CardOrdering, ChoDaiDi},
+    collections::Cards,
+    partition::partition,
+};
+use itertools::Itertools;
+use std::cmp::Ordering;
+
+/// The most cards that every way of splitting a hand is tried for. It's the size of a
+/// hand in a four player game.
+const MAX_EXACT_CARDS: usize = 13;
+
+/// Find the fewest plays that would empty `hand` if every other player passed, comparing
+/// cards under `ordering`.
+///
+/// `last_play` is the play that must be beaten first, or `None` when leading a new
+/// round. Returns `None` if nothing in the hand beats `last_play`, since the hand can't
+/// be played out without someone else getting a turn.
+///
+/// Every way of splitting a hand of up to 13 cards is tried. Larger hands are split with
+/// [`partition`] instead, so their finish may take more plays than it needs to.
+pub fn fastest_finish<const PLAYERS: usize>(
+    hand: &Cards<ChoDaiDi<PLAYERS>>,
+    last_play: Option<&Cards<ChoDaiDi<PLAYERS>>>,
+    ordering: &CardOrdering,
+) -> Option<Vec<Cards<ChoDaiDi<PLAYERS>>>> {
+    if hand.len() > MAX_EXACT_CARDS {
+        return finish_by_partition(hand, last_play, ordering);
+    }
+
+    let cards: Vec<Card> = hand.iter().copied().collect();
+    let all = (1usize << cards.len()) - 1;
+    let plays = Plays::new(&cards);
+
+    let (first, rest) = match last_play {
+        None => (0, all),
+        Some(last_play) => {
+            // Try every play that beats the last play, then finish as fast as possible
+            let first = (1..=all)
+                .filter(|&play| play.count_ones() as usize == last_play.len())
+                .filter(|&play| {
+                    last_play
+                        .may_be_followed_by_under(&plays.cards(play), ordering)
+                        .is_ok()
+                })
+                .min_by_key(|&play| plays.fewest[all ^ play].0)?;
+            (first, all ^ first)
+        }
+    };
+
+    let mut finish = Vec::new();
+    if first != 0 {
+        finish.push(plays.cards(first));
+    }
+    let mut remaining = rest;
+    while remaining != 0 {
+        let play = plays.fewest[remaining].1;
+        finish.push(plays.cards(play));
+        remaining ^= play;
+    }
+
+    Some(finish)
+}
+
+/// Play out the combinations that `hand` is partitioned into, starting with the weakest
+/// one that beats `last_play`, or with the weakest play that does if none of them do.
+fn finish_by_partition<const PLAYERS: usize>(
+    hand: &Cards<ChoDaiDi<PLAYERS>>,
+    last_play: Option<&Cards<ChoDaiDi<PLAYERS>>>,
+    ordering: &CardOrdering,
+) -> Option<Vec<Cards<ChoDaiDi<PLAYERS>>>> {
+    let Some(last_play) = last_play else {
+        return Some(partition(hand, ordering));
+    };
+    let beats = |play: &Cards<ChoDaiDi<PLAYERS>>| {
+        last_play.may_be_followed_by_under(play, ordering).is_ok()
+    };
+
+    let mut finish = partition(hand, ordering);
+    // The partition lists combinations of a size strongest first
+    if let Some(first) = finish.iter().rposition(beats) {
+        let first = finish.remove(first);
+        finish.insert(0, first);
+        return Some(finish);
+    }
+
+    let first = hand
+        .iter()
+        .copied()
+        .combinations(last_play.len())
+        .map(Cards::<ChoDaiDi<PLAYERS>>::from)
+        .filter(beats)
+        .min_by(|a, b| match a.may_be_followed_by_under(b, ordering) {
+            Ok(()) => Ordering::Less,
+            Err(_) => Ordering::Greater,
+        })?;
+    let rest: Cards<ChoDaiDi<PLAYERS>> = hand
+        .iter()
+        .filter(|card| !first.contains(card))
+        .copied()
+        .collect::<Vec<_>>()
+        .into();
+    let mut finish = vec![first];
+    finish.extend(partition(&rest, ordering));
+
+    Some(finish)
+}
+
+/// Every subset of a hand, stored as bitmasks over the hand's cards.
+struct Plays<'a, const PLAYERS: usize> {
+    cards: &'a [Card],
+    /// For each subset, the fewest plays that empty it and the first of those plays
+    fewest: Vec<(usize, usize)>,
+}
+
+impl<'a, const PLAYERS: usize> Plays<'a, PLAYERS> {
+    fn new(cards: &'a [Card]) -> Self {
+        let subsets = 1usize << cards.len();
+        let mut plays = Plays {
+            cards,
+            fewest: Vec::new(),
+        };
+        let is_valid: Vec<bool> = (0..subsets)
+            .map(|subset| {
+                matches!(subset.count_ones(), 1..=3 | 5)
+                    && plays.cards(subset).is_valid_hand().is_ok()
+            })
+            .collect();
+
+        plays.fewest = vec![(0, 0); subsets];
+        for subset in 1..subsets {
+            // Every card has to be played eventually, so only plays that include the
+            // lowest card in the subset need to be tried.
+            let lowest = subset & subset.wrapping_neg();
+            let others = subset ^ lowest;
+            let mut best = (usize::MAX, 0);
+            let mut rest = others;
+            loop {
+                let play = rest | lowest;
+                if is_valid[play] {
+                    let count = plays.fewest[subset ^ play].0 + 1;
+                    if count < best.0 {
+                        best = (count, play);
+                    }
+                }
+                if rest == 0 {
+                    break;
+                }
+                rest = (rest - 1) & others;
+            }
+            plays.fewest[subset] = best;
+        }
+
+        plays
+    }
+
+    fn cards(&self, subset: usize) -> Cards<ChoDaiDi<PLAYERS>> {
+        self.cards
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| subset & (1 << i) != 0)
+            .map(|(_, card)| *card)
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fastest_finish;
+    use crate::{
+        card::STANDARD_DECK,
+        cho_dai_di::{CardOrdering, ChoDaiDi},
+        collections::Cards,
+    };
+
+    const STANDARD: &CardOrdering = &CardOrdering::STANDARD;
+
+    fn cards(cards: Vec<&str>) -> Cards<ChoDaiDi> {
+        Cards::try_from(cards).unwrap()
+    }
+
+    #[test]
+    fn test_fastest_finish_when_leading() {
+        let hand = cards(vec!["3D", "4S", "5H", "6C", "7D", "9S", "9H", "KD"]);
+        let finish = fastest_finish(&hand, None, STANDARD).unwrap();
+        assert_eq!(finish.len(), 3);
+        assert_eq!(finish.iter().map(|play| play.len()).sum::<usize>(), 8);
+
+        assert!(fastest_finish(&cards(vec![]), None, STANDARD)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_fastest_finish_must_beat_last_play() {
+        let hand = cards(vec!["4D", "4C", "8S", "8H", "8D"]);
+        // A full house finishes in one play
+        assert_eq!(fastest_finish(&hand, None, STANDARD).unwrap().len(), 1);
+
+        // Beating a pair of threes first takes one of the pairs apart
+        let finish = fastest_finish(&hand, Some(&cards(vec!["3D", "3S"])), STANDARD).unwrap();
+        assert_eq!(finish.len(), 2);
+        assert_eq!(finish[0].len(), 2);
+
+        assert!(fastest_finish(&hand, Some(&cards(vec!["2S"])), STANDARD).is_none());
+    }
+
+    #[test]
+    fn test_fastest_finish_under_ordering() {
+        let hand = cards(vec!["3D", "4C"]);
+        let mut inverted = CardOrdering::STANDARD;
+        inverted.invert_ranks();
+
+        assert!(fastest_finish(&hand, Some(&cards(vec!["5S"])), STANDARD).is_none());
+        let finish = fastest_finish(&hand, Some(&cards(vec!["5S"])), &inverted).unwrap();
+        assert_eq!(finish.len(), 2);
+    }
+
+    #[test]
+    fn test_fastest_finish_of_a_large_hand() {
+        // Half of the deck, as in a two player game
+        let hand = Cards::<ChoDaiDi>::from(STANDARD_DECK[..26].to_vec());
+        let finish = fastest_finish(&hand, None, STANDARD).unwrap();
+        assert_eq!(finish.iter().map(|play| play.len()).sum::<usize>(), 26);
+        assert!(finish.iter().all(|play| play.is_valid_hand().is_ok()));
+
+        let last_play = cards(vec!["3H"]);
+        let finish = fastest_finish(&hand, Some(&last_play), STANDARD).unwrap();
+        assert!(last_play.may_be_followed_by(&finish[0]).is_ok());
+    }
+}
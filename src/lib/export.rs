@@ -0,0 +1,178 @@
+//! Flattening saved games into CSV, one row per turn, for looking at in a spreadsheet.
+//!
+//! ```csv
+//! game_id,trick,seat,action,cards,category,hand_size_after
+//! 6f1c…,1,2,play,3♦,single,12
+//! 6f1c…,1,3,pass,,,13
+//! ```
+//!
+//! Tricks and seats are counted from 1. A trick ends when everyone else passes or the
+//! highest card still in play is played.
+
+use crate::{
+    cho_dai_di::{Combination, TurnAction},
+    replay::Replay,
+};
+use anyhow::Context;
+use serde::Serialize;
+use std::io;
+
+/// One turn of a game, as a row of the CSV.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlayRow {
+    pub game_id: String,
+    pub trick: usize,
+    pub seat: usize,
+    /// `play` or `pass`
+    pub action: &'static str,
+    /// The cards played, empty for a pass
+    pub cards: String,
+    /// The kind of play, empty for a pass
+    pub category: Option<Combination>,
+    /// The number of cards the player held after their turn
+    pub hand_size_after: usize,
+}
+
+/// Play back a replay, returning a row for each turn. `game_id` fills the first column,
+/// since replays don't keep the ID of the game they were recorded from.
+pub fn rows<const PLAYERS: usize>(
+    replay: &Replay<PLAYERS>,
+    game_id: &str,
+) -> anyhow::Result<Vec<PlayRow>> {
+    let mut game = replay.new_game()?;
+    let mut rows = Vec::with_capacity(replay.actions.len());
+    for (turn, action) in replay.actions.iter().enumerate() {
+        let (kind, cards, category) = match action {
+            TurnAction::Play(cards) => {
+                let category = cards
+                    .classify_under(&game.ordering())
+                    .map(|(category, _)| category);
+                ("play", cards.to_string(), category)
+            }
+            TurnAction::Pass => ("pass", String::new(), None),
+        };
+        game.advance(action.clone())
+            .with_context(|| format!("turn {} of the replay", turn + 1))?;
+        let record = game.history().last().context("the turn wasn't recorded")?;
+        rows.push(PlayRow {
+            game_id: game_id.to_owned(),
+            trick: record.round_number + 1,
+            seat: record.player + 1,
+            action: kind,
+            cards,
+            category,
+            hand_size_after: game.hands()[record.player].len(),
+        });
+    }
+
+    Ok(rows)
+}
+
+/// The names of the columns, in the order [`PlayRow`]'s fields are written.
+const COLUMNS: [&str; 7] = [
+    "game_id",
+    "trick",
+    "seat",
+    "action",
+    "cards",
+    "category",
+    "hand_size_after",
+];
+
+/// Write the CSV's header line. It's written on its own, so that a table of games that
+/// took no turns still has one.
+pub fn write_header(writer: impl io::Write) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer
+        .write_record(COLUMNS)
+        .context("writing the header of the CSV")?;
+    writer.flush().context("writing the CSV")?;
+
+    Ok(())
+}
+
+/// Write `rows` as CSV, without a header line, so that several games can be written one
+/// after another under the one [header](write_header).
+pub fn write_csv(rows: &[PlayRow], writer: impl io::Write) -> anyhow::Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(writer);
+    for row in rows {
+        writer.serialize(row).context("writing a row of the CSV")?;
+    }
+    writer.flush().context("writing the CSV")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rows, write_csv, write_header, PlayRow};
+    use crate::{
+        arbitrary::play_weakest,
+        cho_dai_di::{ChoDaiDi, Combination, TurnAction},
+        replay::Replay,
+    };
+
+    fn replay() -> Replay<3> {
+        let mut game = ChoDaiDi::<3>::new_game_with_seed(498);
+        play_weakest(&mut game, usize::MAX);
+        let names = ["A", "B", "C"].map(ToOwned::to_owned).to_vec();
+        Replay::record(&game, 498, None, names)
+    }
+
+    #[test]
+    fn test_rows() {
+        let replay = replay();
+        let rows = rows(&replay, "g1").unwrap();
+        assert_eq!(rows.len(), replay.actions.len());
+
+        let first = &rows[0];
+        assert_eq!((first.trick, first.action), (1, "play"));
+        let TurnAction::Play(opening) = &replay.actions[0] else {
+            panic!("the game opened with a pass");
+        };
+        assert_eq!(first.hand_size_after, 17 - opening.len());
+        assert!(first.category.is_some());
+        let pass = rows.iter().find(|row| row.action == "pass").unwrap();
+        assert_eq!((pass.cards.as_str(), pass.category), ("", None));
+        // Tricks only ever go up, one at a time, and the game ends with an empty hand
+        assert!(rows.windows(2).all(|w| w[1].trick - w[0].trick <= 1));
+        assert_eq!(rows.last().unwrap().hand_size_after, 0);
+        assert!(rows.iter().all(|row| (1..=3).contains(&row.seat)));
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let rows = rows(&replay(), "g1").unwrap();
+        let mut csv = Vec::new();
+        write_header(&mut csv).unwrap();
+        write_csv(&rows[..2], &mut csv).unwrap();
+        write_csv(&[], &mut csv).unwrap();
+        write_csv(&rows[2..3], &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "game_id,trick,seat,action,cards,category,hand_size_after\n\
+             g1,1,1,play,3♦,single,16\n\
+             g1,1,2,play,3♣,single,16\n\
+             g1,1,3,play,5♣,single,16\n"
+        );
+
+        // Cards are separated by commas, so plays of more than one card are quoted
+        let pair = PlayRow {
+            game_id: "g2".to_owned(),
+            trick: 3,
+            seat: 2,
+            action: "play",
+            cards: "4♦, 4♠".to_owned(),
+            category: Some(Combination::Pair),
+            hand_size_after: 7,
+        };
+        let mut csv = Vec::new();
+        write_csv(&[pair], &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "g2,3,2,play,\"4♦, 4♠\",pair,7\n"
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Listing the plays available to a player, grouped by the kind of hand.
+
+use crate::{
+    cho_dai_di::{ChoDaiDi, Combination, GameView},
+    collections::Cards,
+    render::CardStyle,
+};
+use itertools::Itertools;
+use std::cmp::Ordering;
+
+/// The order that kinds of hands are listed in.
+const COMBINATIONS: [Combination; 8] = [
+    Combination::Single,
+    Combination::Pair,
+    Combination::Triplet,
+    Combination::Straight,
+    Combination::Flush,
+    Combination::FullHouse,
+    Combination::FourOfAKindPlusOne,
+    Combination::StraightFlush,
+];
+
+/// Options for listing the plays available to a player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintFormatter {
+    /// The most plays listed for each kind of hand. Any more are counted instead, so
+    /// that large hands don't fill the screen.
+    pub max_plays_per_kind: usize,
+}
+
+impl Default for HintFormatter {
+    fn default() -> Self {
+        HintFormatter {
+            max_plays_per_kind: 6,
+        }
+    }
+}
+
+impl HintFormatter {
+    /// List the plays that may be made from `hand`, with one line for each kind of hand
+    /// that could be played, e.g. `Singles that beat 9♥: 10♣ J♦ Q♠`. Cards are shown in
+    /// `style`.
+    ///
+    /// When following a play, only hands of the same size are listed. Each kind's plays
+    /// are listed from weakest to strongest.
+    pub fn format<const PLAYERS: usize>(
+        &self,
+        view: &GameView<'_, PLAYERS>,
+        hand: &Cards<ChoDaiDi<PLAYERS>>,
+        style: &CardStyle,
+    ) -> Vec<String> {
+        let ordering = view.ordering();
+        let last_play = view.last_play();
+        let plays: Vec<_> = view
+            .possible_plays(hand)
+            .into_iter()
+            .filter_map(|mut play| {
+                let (combination, key_card) = play.classify_under(&ordering)?;
+                play.sort_by_precedence_under(&ordering);
+                Some((combination, key_card, play))
+            })
+            // Weakest first, by the card that decides what each play beats
+            .sorted_by(|(_, a_key, a), (_, b_key, b)| {
+                ordering
+                    .cmp_card(a_key, b_key)
+                    .then_with(|| a.len().cmp(&b.len()))
+                    .then_with(|| {
+                        a.iter()
+                            .zip(b.iter())
+                            .map(|(a, b)| ordering.cmp_card(a, b))
+                            .find(|it| it.is_ne())
+                            .unwrap_or(Ordering::Equal)
+                    })
+            })
+            .collect();
+
+        COMBINATIONS
+            .into_iter()
+            .filter(|combination| {
+                last_play
+                    .as_ref()
+                    .is_none_or(|last_play| size(*combination) == last_play.len())
+            })
+            .map(|combination| {
+                let of_kind: Vec<_> = plays
+                    .iter()
+                    .filter(|(of, _, _)| *of == combination)
+                    .map(|(_, _, play)| play.iter().map(|card| style.card(card)).join(" "))
+                    .collect();
+                let heading = match &last_play {
+                    Some(last_play) => format!(
+                        "{} that beat {}",
+                        plural(combination),
+                        last_play.iter().map(|card| style.card(card)).join(" ")
+                    ),
+                    None => plural(combination).to_owned(),
+                };
+
+                format!("{heading}: {}", self.list(&of_kind))
+            })
+            .collect()
+    }
+
+    fn list(&self, plays: &[String]) -> String {
+        if plays.is_empty() {
+            return "none".to_owned();
+        }
+
+        let separator = if plays.iter().any(|play| play.contains(' ')) {
+            ", "
+        } else {
+            " "
+        };
+        let mut list = plays.iter().take(self.max_plays_per_kind).join(separator);
+        if plays.len() > self.max_plays_per_kind {
+            list.push_str(&format!(
+                " … and {} more",
+                plays.len() - self.max_plays_per_kind
+            ));
+        }
+
+        list
+    }
+}
+
+fn size(combination: Combination) -> usize {
+    match combination {
+        Combination::Single => 1,
+        Combination::Pair => 2,
+        Combination::Triplet => 3,
+        _ => 5,
+    }
+}
+
+fn plural(combination: Combination) -> &'static str {
+    match combination {
+        Combination::Single => "Singles",
+        Combination::Pair => "Pairs",
+        Combination::Triplet => "Triplets",
+        Combination::Straight => "Straights",
+        Combination::Flush => "Flushes",
+        Combination::FullHouse => "Full houses",
+        Combination::FourOfAKindPlusOne => "Fours of a kind plus one",
+        Combination::StraightFlush => "Straight flushes",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HintFormatter;
+    use crate::{
+        card::Card,
+        cho_dai_di::{new_4p_game, TurnAction},
+        collections::Cards,
+        render::CardStyle,
+    };
+
+    #[test]
+    fn test_format_following() {
+        let mut game = new_4p_game();
+        game.advance(TurnAction::Play(Cards::from(Card::THREE_OF_DIAMONDS)))
+            .unwrap();
+        let hand = Cards::try_from(vec!["3S", "4D", "4S", "5H", "6C", "7S", "8D", "9H"]).unwrap();
+
+        let formatter = HintFormatter {
+            max_plays_per_kind: 3,
+        };
+        assert_eq!(
+            formatter.format(&game.view(), &hand, &CardStyle::default()),
+            ["Singles that beat 3♦: 3♠ 4♦ 4♠ … and 5 more"]
+        );
+    }
+
+    #[test]
+    fn test_format_leading() {
+        let mut game = new_4p_game();
+        game.advance(TurnAction::Play(Cards::from(Card::THREE_OF_DIAMONDS)))
+            .unwrap();
+        for _ in 0..3 {
+            game.advance(TurnAction::Pass).unwrap();
+        }
+        let hand = Cards::try_from(vec!["4D", "4S", "9H"]).unwrap();
+
+        let lines = HintFormatter::default().format(&game.view(), &hand, &CardStyle::default());
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], "Singles: 4♦ 4♠ 9♥");
+        assert_eq!(lines[1], "Pairs: 4♦ 4♠");
+        assert_eq!(lines[2], "Triplets: none");
+    }
+
+    #[test]
+    fn test_format_in_color() {
+        let mut game = new_4p_game();
+        game.advance(TurnAction::Play(Cards::from(Card::THREE_OF_DIAMONDS)))
+            .unwrap();
+        let hand = Cards::try_from(vec!["4D", "4S"]).unwrap();
+        let style = CardStyle {
+            color: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            HintFormatter::default().format(&game.view(), &hand, &style),
+            [format!(
+                "Singles that beat {}: {} 4♠",
+                style.card(&Card::THREE_OF_DIAMONDS),
+                style.card(&"4D".parse().unwrap())
+            )]
+        );
+    }
+}
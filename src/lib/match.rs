@@ -0,0 +1,705 @@
+//! A match of several games of Cho Dai Di, with a running total of each player's score.
+
+use crate::{
+    card::STANDARD_DECK,
+    cho_dai_di::{ChoDaiDi, ScoringRules, SuitPrecedence},
+    new_game_id,
+};
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// The keys of each rule in [`Rules::to_map`]. They're part of the replay format and the
+/// network protocol, so they stay the same when the fields of [`Rules`] are renamed.
+pub mod keys {
+    pub const RATED: &str = "rated";
+    pub const REVOLUTIONS: &str = "revolutions";
+    pub const INSTANT_WINS: &str = "instant-wins";
+    pub const FLUSH_BEATS_FULL_HOUSE: &str = "flush-beats-full-house";
+    pub const THREE_OF_DIAMONDS_OPENS_ALONE: &str = "three-of-diamonds-opens-alone";
+    pub const LOWEST_SINGLE_FIRST: &str = "lowest-single-first";
+    pub const SUITS: &str = "suits";
+    pub const MAX_CANDIDATE_PLAYS: &str = "max-candidate-plays";
+    pub const TWOS_DOUBLE_PENALTY: &str = "twos-double-penalty";
+    pub const FULL_HAND_TRIPLES_PENALTY: &str = "full-hand-triples-penalty";
+    pub const SLAM_BONUSES: &str = "slam-bonuses";
+    pub const PASS_CARD_PENALTY: &str = "pass-card-penalty";
+}
+
+/// The options that every game in a match is played with.
+///
+/// Regional variants of the game can be put together with the builder methods, e.g.
+/// `Rules::default().flush_beats_full_house(true)`, and used for a single game with
+/// [`ChoDaiDi::new_game_with_rules`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    /// If true, playing a four of a kind inverts the order of ranks
+    pub revolutions_enabled: bool,
+    /// If true, a player dealt a dragon wins the game before anyone plays
+    pub instant_wins_enabled: bool,
+    /// If true, a player who passes when they could have led must reveal a card, or
+    /// draw a penalty marker
+    pub pass_card_penalty: bool,
+    /// If true, a flush beats a full house instead of the other way around. It's applied
+    /// through the game's [`CardOrdering`](crate::cho_dai_di::CardOrdering), since it
+    /// changes how plays are compared.
+    pub flush_beats_full_house: bool,
+    /// If true, the 3♦ must be played on its own when it opens the game
+    pub three_of_diamonds_opens_alone: bool,
+    /// If true, the first play of the game must be the first player's lowest card on
+    /// its own
+    pub must_play_lowest_single_first: bool,
+    /// The order that suits break ties in
+    pub suit_precedence: SuitPrecedence,
+    /// The most candidate plays generated when looking for possible plays
+    pub max_candidate_plays: Option<usize>,
+    /// How the cards left in each hand are scored
+    pub scoring: ScoringRules,
+    /// If true, games count towards the players' records, so assists are off
+    pub rated: bool,
+}
+
+impl Rules {
+    /// Turn revolutions on or off, see [`ChoDaiDi::set_revolutions_enabled`].
+    pub fn revolutions_enabled(mut self, enabled: bool) -> Self {
+        self.revolutions_enabled = enabled;
+        self
+    }
+
+    /// Turn instant wins on or off, see [`ChoDaiDi::set_instant_wins_enabled`].
+    pub fn instant_wins_enabled(mut self, enabled: bool) -> Self {
+        self.instant_wins_enabled = enabled;
+        self
+    }
+
+    /// Turn the pass card penalty on or off, see [`ChoDaiDi::set_pass_card_penalty`].
+    pub fn pass_card_penalty(mut self, enabled: bool) -> Self {
+        self.pass_card_penalty = enabled;
+        self
+    }
+
+    /// Choose whether a flush beats a full house.
+    pub fn flush_beats_full_house(mut self, enabled: bool) -> Self {
+        self.flush_beats_full_house = enabled;
+        self
+    }
+
+    /// Choose whether the 3♦ must be played on its own when it opens the game.
+    pub fn three_of_diamonds_opens_alone(mut self, enabled: bool) -> Self {
+        self.three_of_diamonds_opens_alone = enabled;
+        self
+    }
+
+    /// Choose whether the first play must be the lowest single, see
+    /// [`ChoDaiDi::set_must_play_lowest_single_first`].
+    pub fn must_play_lowest_single_first(mut self, enabled: bool) -> Self {
+        self.must_play_lowest_single_first = enabled;
+        self
+    }
+
+    /// Choose the order that suits break ties in.
+    pub fn suit_precedence(mut self, precedence: SuitPrecedence) -> Self {
+        self.suit_precedence = precedence;
+        self
+    }
+
+    /// Limit the plays the computer weighs up, see [`ChoDaiDi::set_max_candidate_plays`].
+    pub fn max_candidate_plays(mut self, limit: Option<usize>) -> Self {
+        self.max_candidate_plays = limit;
+        self
+    }
+
+    /// Choose how the cards left in each hand are scored.
+    pub fn scoring(mut self, scoring: ScoringRules) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Choose whether games are rated, see [`ChoDaiDi::set_rated`].
+    pub fn rated(mut self, rated: bool) -> Self {
+        self.rated = rated;
+        self
+    }
+
+    /// Every rule, by its [stable key](keys), with its value written out, e.g.
+    /// `suits = vietnamese`. A limit on candidate plays that isn't set is written `none`.
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        let max_candidate_plays = match self.max_candidate_plays {
+            Some(limit) => limit.to_string(),
+            None => "none".to_owned(),
+        };
+        [
+            (keys::RATED, self.rated.to_string()),
+            (keys::REVOLUTIONS, self.revolutions_enabled.to_string()),
+            (keys::INSTANT_WINS, self.instant_wins_enabled.to_string()),
+            (
+                keys::FLUSH_BEATS_FULL_HOUSE,
+                self.flush_beats_full_house.to_string(),
+            ),
+            (
+                keys::THREE_OF_DIAMONDS_OPENS_ALONE,
+                self.three_of_diamonds_opens_alone.to_string(),
+            ),
+            (
+                keys::LOWEST_SINGLE_FIRST,
+                self.must_play_lowest_single_first.to_string(),
+            ),
+            (keys::SUITS, self.suit_precedence.to_string()),
+            (keys::MAX_CANDIDATE_PLAYS, max_candidate_plays),
+            (
+                keys::TWOS_DOUBLE_PENALTY,
+                self.scoring.twos_double_penalty.to_string(),
+            ),
+            (
+                keys::FULL_HAND_TRIPLES_PENALTY,
+                self.scoring.full_hand_triples_penalty.to_string(),
+            ),
+            (keys::SLAM_BONUSES, self.scoring.slam_bonuses.to_string()),
+            (keys::PASS_CARD_PENALTY, self.pass_card_penalty.to_string()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value))
+        .collect()
+    }
+
+    /// Read rules written with [`Rules::to_map`]. Rules that are left out keep their
+    /// default, and a key that isn't known is an error.
+    pub fn from_map(map: &BTreeMap<String, String>) -> anyhow::Result<Self> {
+        let mut rules = Rules::default();
+        for (key, value) in map {
+            let context = || format!("reading the rule '{key} = {value}'");
+            let flag = || value.parse::<bool>().with_context(context);
+            match key.as_str() {
+                keys::RATED => rules.rated = flag()?,
+                keys::REVOLUTIONS => rules.revolutions_enabled = flag()?,
+                keys::INSTANT_WINS => rules.instant_wins_enabled = flag()?,
+                keys::FLUSH_BEATS_FULL_HOUSE => rules.flush_beats_full_house = flag()?,
+                keys::THREE_OF_DIAMONDS_OPENS_ALONE => {
+                    rules.three_of_diamonds_opens_alone = flag()?
+                }
+                keys::LOWEST_SINGLE_FIRST => rules.must_play_lowest_single_first = flag()?,
+                keys::SUITS => rules.suit_precedence = value.parse().with_context(context)?,
+                keys::MAX_CANDIDATE_PLAYS => {
+                    rules.max_candidate_plays = match value.as_str() {
+                        "none" => None,
+                        limit => Some(limit.parse().with_context(context)?),
+                    }
+                }
+                keys::TWOS_DOUBLE_PENALTY => rules.scoring.twos_double_penalty = flag()?,
+                keys::FULL_HAND_TRIPLES_PENALTY => {
+                    rules.scoring.full_hand_triples_penalty = flag()?
+                }
+                keys::SLAM_BONUSES => rules.scoring.slam_bonuses = flag()?,
+                keys::PASS_CARD_PENALTY => rules.pass_card_penalty = flag()?,
+                _ => bail!("unknown rule '{key}'"),
+            }
+        }
+
+        Ok(rules)
+    }
+
+    /// A short hash of the rules that differ from the defaults, recorded with saves,
+    /// replays, and games played over the network to check that whoever reads them back
+    /// plays by the same rules. Rules left at their defaults aren't hashed, so adding a
+    /// rule doesn't change the fingerprint of anything recorded before it.
+    pub fn fingerprint(&self) -> String {
+        // FNV-1a, which unlike the standard library's hasher hashes the same on every
+        // platform and version
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for (key, value) in self.changes() {
+            for byte in format!("{key}={value}\n").bytes() {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+
+        format!("{hash:016x}")
+    }
+
+    /// Check that these rules, as read back by this version of the game, are the ones
+    /// that `version` recorded with `fingerprint`.
+    pub fn check_fingerprint(&self, fingerprint: &str, version: &str) -> anyhow::Result<()> {
+        if self.fingerprint() == fingerprint {
+            return Ok(());
+        }
+        let changes: Vec<_> = self
+            .changes()
+            .map(|(key, value)| format!("{key} = {value}"))
+            .collect();
+        let read_as = match changes.is_empty() {
+            true => "the standard rules".to_owned(),
+            false => changes.join(", "),
+        };
+        bail!(
+            "version {version} of the game recorded this under rules that version {} can't \
+             read the same way (it reads {read_as}); open it with version {version} instead",
+            crate::VERSION
+        )
+    }
+
+    /// The rules that differ from the defaults, by their stable keys.
+    pub(crate) fn changes(&self) -> impl Iterator<Item = (String, String)> {
+        let defaults = Rules::default().to_map();
+        self.to_map()
+            .into_iter()
+            .filter(move |(key, value)| defaults.get(key) != Some(value))
+    }
+
+    pub(crate) fn apply<const PLAYERS: usize>(&self, game: &mut ChoDaiDi<PLAYERS>) {
+        game.set_revolutions_enabled(self.revolutions_enabled);
+        game.set_instant_wins_enabled(self.instant_wins_enabled);
+        game.set_pass_card_penalty(self.pass_card_penalty);
+        game.set_flush_beats_full_house(self.flush_beats_full_house);
+        game.set_three_of_diamonds_opens_alone(self.three_of_diamonds_opens_alone);
+        game.set_must_play_lowest_single_first(self.must_play_lowest_single_first);
+        game.set_suit_precedence(self.suit_precedence);
+        game.set_max_candidate_plays(self.max_candidate_plays);
+        game.set_scoring_rules(self.scoring);
+        game.set_rated(self.rated);
+    }
+}
+
+/// When a match ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Termination {
+    /// The match ends after this many games
+    Games(usize),
+    /// The match ends once any player's total score falls to this many points below zero
+    ScoreThreshold(isize),
+}
+
+/// The outcome of one game in a match.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub game_id: Uuid,
+    /// Each player's score for this game, by seat
+    pub scores: Vec<isize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Match {
+    id: Uuid,
+    /// The name of the player in each seat
+    players: Vec<String>,
+    rules: Rules,
+    termination: Termination,
+    results: Vec<GameResult>,
+}
+
+impl Match {
+    pub fn new(players: Vec<String>, rules: Rules, termination: Termination) -> Self {
+        Match {
+            id: new_game_id(),
+            players,
+            rules,
+            termination,
+            results: Vec::new(),
+        }
+    }
+
+    /// Get the unique ID of this match
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Get the name of the player in each seat
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    pub fn termination(&self) -> Termination {
+        self.termination
+    }
+
+    /// Get the results of every game recorded so far, in the order they were played
+    pub fn results(&self) -> &[GameResult] {
+        &self.results
+    }
+
+    /// Get the seat dealing the next game. The deal passes one seat to the left after
+    /// every game.
+    ///
+    /// This is only for show: the deck is shuffled before every deal, so who deals
+    /// doesn't change anyone's hand, and the holder of the 3♦ still goes first.
+    pub fn dealer(&self) -> usize {
+        self.results.len() % self.players.len()
+    }
+
+    /// Deal the next game of the match, set up with the match's rules.
+    pub fn new_game<const PLAYERS: usize>(&self) -> anyhow::Result<ChoDaiDi<PLAYERS>> {
+        self.set_up(ChoDaiDi::new_game())
+    }
+
+    /// Like [`Match::new_game`], but the deck is shuffled with `seed` so that the deal
+    /// can be repeated.
+    pub fn new_game_with_seed<const PLAYERS: usize>(
+        &self,
+        seed: u64,
+    ) -> anyhow::Result<ChoDaiDi<PLAYERS>> {
+        self.set_up(ChoDaiDi::new_game_with_seed(seed))
+    }
+
+    fn set_up<const PLAYERS: usize>(
+        &self,
+        mut game: ChoDaiDi<PLAYERS>,
+    ) -> anyhow::Result<ChoDaiDi<PLAYERS>> {
+        if PLAYERS != self.players.len() {
+            bail!(
+                "this match has {} players but the game is for {PLAYERS}",
+                self.players.len()
+            );
+        }
+        if self.is_over() {
+            bail!("the match is over");
+        }
+
+        self.rules.apply(&mut game);
+        game.check_instant_wins();
+
+        Ok(game)
+    }
+
+    /// Record the scores of a finished game.
+    pub fn record_game<const PLAYERS: usize>(
+        &mut self,
+        game: &ChoDaiDi<PLAYERS>,
+    ) -> anyhow::Result<()> {
+        if PLAYERS != self.players.len() {
+            bail!(
+                "this match has {} players but the game had {PLAYERS}",
+                self.players.len()
+            );
+        }
+        if !game.is_game_ended() {
+            bail!("only finished games can be recorded");
+        }
+        if self
+            .results
+            .iter()
+            .any(|result| result.game_id == game.id())
+        {
+            bail!("game {} has already been recorded", game.id());
+        }
+
+        self.results.push(GameResult {
+            game_id: game.id(),
+            scores: game.provisional_scores().to_vec(),
+        });
+
+        Ok(())
+    }
+
+    /// Get each player's total score across every recorded game, by seat.
+    pub fn cumulative_scores(&self) -> Vec<isize> {
+        let mut totals = vec![0; self.players.len()];
+        for result in &self.results {
+            for (total, score) in totals.iter_mut().zip(&result.scores) {
+                *total += score;
+            }
+        }
+
+        totals
+    }
+
+    /// Returns true once the match's termination condition has been met.
+    pub fn is_over(&self) -> bool {
+        match self.termination {
+            Termination::Games(games) => self.results.len() >= games,
+            Termination::ScoreThreshold(threshold) => self
+                .cumulative_scores()
+                .iter()
+                .any(|total| *total <= -threshold),
+        }
+    }
+
+    /// Returns true if the leader can't be caught, however the rest of the match goes.
+    ///
+    /// This is the case once the match is over, or when the leader is further ahead of
+    /// every other player than the games left could make up. A match that ends at a
+    /// score threshold can go on for any number of games, so it's only decided early
+    /// when the next game is sure to be its last: when two players are each a point
+    /// away from the threshold, at least one of them loses a point and crosses it.
+    pub fn is_decided(&self) -> bool {
+        if self.is_over() {
+            return true;
+        }
+        let games_left = match self.termination {
+            Termination::Games(games) => games - self.results.len(),
+            Termination::ScoreThreshold(threshold) => {
+                let totals = self.cumulative_scores();
+                let near_threshold = totals.iter().filter(|total| **total <= 1 - threshold);
+                match near_threshold.count() {
+                    0 | 1 => return false,
+                    _ => 1,
+                }
+            }
+        };
+
+        let totals = self.cumulative_scores();
+        let Some((leader, lead)) = totals.iter().enumerate().max_by_key(|(_, total)| **total)
+        else {
+            return false;
+        };
+        let Some(swing) = self.largest_swing() else {
+            return false;
+        };
+        let reach = games_left as isize * swing;
+        totals
+            .iter()
+            .enumerate()
+            .all(|(seat, total)| seat == leader || lead - total > reach)
+    }
+
+    /// The most that one game can close the gap between two players by: the winner
+    /// takes the penalties of everyone else, who might not have played a card, and every
+    /// slam bonus from each of them. There's no limit to the penalty markers a player
+    /// can draw under the pass card penalty, so there's no limit to the swing either.
+    fn largest_swing(&self) -> Option<isize> {
+        if self.rules.pass_card_penalty {
+            return None;
+        }
+        let players = self.players.len();
+        let hand_size = STANDARD_DECK.len() / players.max(1);
+        let penalty = -self.rules.scoring.worst_score(hand_size);
+        Some((penalty + self.rules.scoring.largest_bonus()) * players as isize)
+    }
+
+    /// Play one more game after this one, however the match was meant to end. Scores
+    /// carry over into it.
+    pub fn play_another_game(&mut self) {
+        self.termination = Termination::Games(self.results.len() + 1);
+    }
+
+    /// End the match after the games played so far, e.g. once it [is
+    /// decided](Match::is_decided).
+    pub fn end_early(&mut self) {
+        self.termination = Termination::Games(self.results.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cho_dai_di::FOUR_PLAYERS;
+
+    fn players() -> Vec<String> {
+        ["A", "B", "C", "D"].map(ToOwned::to_owned).to_vec()
+    }
+
+    fn result(scores: [isize; FOUR_PLAYERS]) -> GameResult {
+        GameResult {
+            game_id: new_game_id(),
+            scores: scores.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_cumulative_scores_and_termination() {
+        let mut m = Match::new(players(), Rules::default(), Termination::Games(2));
+        m.results.push(result([10, -2, -3, -5]));
+        assert!(!m.is_over());
+        m.results.push(result([-1, 7, -4, -2]));
+        assert_eq!(m.cumulative_scores(), vec![9, 5, -7, -7]);
+        assert!(m.is_over());
+        assert!(m.new_game::<FOUR_PLAYERS>().is_err());
+        assert!(m.new_game_with_seed::<FOUR_PLAYERS>(1).is_err());
+
+        let mut m = Match::new(players(), Rules::default(), Termination::ScoreThreshold(10));
+        m.results.push(result([15, -9, -3, -3]));
+        assert!(!m.is_over());
+        m.results.push(result([-1, -1, 4, -2]));
+        assert!(m.is_over());
+    }
+
+    #[test]
+    fn test_record_game() {
+        let rules = Rules::default()
+            .revolutions_enabled(true)
+            .scoring(ScoringRules::CANTONESE);
+        let mut m = Match::new(players(), rules, Termination::Games(3));
+        assert!(m.new_game::<3>().is_err());
+
+        let game = m.new_game::<FOUR_PLAYERS>().unwrap();
+        assert_eq!(game.rules(), rules);
+        // The game hasn't finished yet
+        assert!(m.record_game(&game).is_err());
+        assert!(m.results().is_empty());
+    }
+
+    #[test]
+    fn test_play_another_game() {
+        let mut m = Match::new(players(), Rules::default(), Termination::ScoreThreshold(10));
+        m.results.push(result([15, -11, -3, -1]));
+        assert!(m.is_over());
+
+        m.play_another_game();
+        assert!(!m.is_over());
+        assert_eq!(m.termination(), Termination::Games(2));
+        m.results.push(result([-2, 6, -3, -1]));
+        assert!(m.is_over());
+        assert_eq!(m.cumulative_scores(), vec![13, -5, -6, -2]);
+    }
+
+    #[test]
+    fn test_rules_map() {
+        let rules = Rules::default()
+            .revolutions_enabled(true)
+            .suit_precedence(SuitPrecedence::Vietnamese)
+            .max_candidate_plays(Some(100))
+            .scoring(ScoringRules::CANTONESE)
+            .rated(true);
+        let map = rules.to_map();
+        assert_eq!(map["revolutions"], "true");
+        assert_eq!(map["instant-wins"], "false");
+        assert_eq!(map["suits"], "vietnamese");
+        assert_eq!(map["max-candidate-plays"], "100");
+        assert_eq!(map["twos-double-penalty"], "true");
+        assert_eq!(Rules::from_map(&map).unwrap(), rules);
+        assert_eq!(
+            Rules::from_map(&Rules::default().to_map()).unwrap(),
+            Rules::default()
+        );
+
+        // Rules that are left out keep their defaults
+        let map = BTreeMap::from([("flush-beats-full-house".to_owned(), "true".to_owned())]);
+        assert_eq!(
+            Rules::from_map(&map).unwrap(),
+            Rules::default().flush_beats_full_house(true)
+        );
+
+        let unknown = BTreeMap::from([("jokers".to_owned(), "true".to_owned())]);
+        assert!(Rules::from_map(&unknown).is_err());
+        let invalid = BTreeMap::from([("suits".to_owned(), "sideways".to_owned())]);
+        assert!(Rules::from_map(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let rules = Rules::default().revolutions_enabled(true);
+        assert_eq!(rules.fingerprint(), rules.fingerprint());
+        assert_ne!(rules.fingerprint(), Rules::default().fingerprint());
+        assert_ne!(
+            rules.fingerprint(),
+            rules
+                .suit_precedence(SuitPrecedence::Vietnamese)
+                .fingerprint()
+        );
+        // The hash is part of saved files, so it mustn't change from one build to the next
+        assert_eq!(Rules::default().fingerprint(), "cbf29ce484222325");
+
+        assert!(rules
+            .check_fingerprint(&rules.fingerprint(), "0.1.0")
+            .is_ok());
+        let e = Rules::default()
+            .check_fingerprint(&rules.fingerprint(), "9.9.9")
+            .unwrap_err();
+        assert!(e.to_string().contains("version 9.9.9"), "{e}");
+        assert!(e.to_string().contains("the standard rules"), "{e}");
+    }
+
+    #[test]
+    fn test_is_decided_after_a_number_of_games() {
+        let mut m = Match::new(players(), Rules::default(), Termination::Games(3));
+        assert!(!m.is_decided());
+        // Each game can close a gap by 4 × 39 points
+        let swing = 156;
+        m.results
+            .push(result([3 * swing / 2, -swing / 2, -swing / 2, -swing / 2]));
+        assert!(!m.is_decided());
+
+        // With one game left, a lead of exactly one swing can still be tied
+        let mut m = Match::new(players(), Rules::default(), Termination::Games(2));
+        m.results.push(result([swing, 0, -swing / 2, -swing / 2]));
+        assert!(!m.is_decided());
+        let mut m = Match::new(players(), Rules::default(), Termination::Games(2));
+        m.results
+            .push(result([swing + 1, -1, -swing / 2, -swing / 2]));
+        assert!(m.is_decided());
+
+        // Doubled penalties for 2s make for much bigger swings
+        let rules = Rules::default().scoring(ScoringRules::CANTONESE);
+        let mut m = Match::new(players(), rules, Termination::Games(2));
+        m.results
+            .push(result([swing + 1, -1, -swing / 2, -swing / 2]));
+        assert!(!m.is_decided());
+        // and so do slam bonuses
+        let scoring = ScoringRules {
+            slam_bonuses: true,
+            ..ScoringRules::default()
+        };
+        let mut m = Match::new(players(), rules.scoring(scoring), Termination::Games(2));
+        m.results
+            .push(result([swing + 1, -1, -swing / 2, -swing / 2]));
+        assert!(!m.is_decided());
+
+        m.end_early();
+        assert!(m.is_over());
+        assert!(m.is_decided());
+    }
+
+    #[test]
+    fn test_is_decided_at_a_score_threshold() {
+        let mut m = Match::new(
+            players(),
+            Rules::default(),
+            Termination::ScoreThreshold(100),
+        );
+        // Only one player is close to the threshold, and might win the next game
+        m.results.push(result([250, -99, -98, -53]));
+        assert!(!m.is_decided());
+
+        // B or C loses at least a point next game and ends the match, but D could
+        // still catch up in it
+        let mut m = Match::new(players(), Rules::default(), Termination::ScoreThreshold(10));
+        m.results.push(result([18, -9, -9, 0]));
+        assert!(!m.is_decided());
+
+        let mut m = Match::new(
+            players(),
+            Rules::default(),
+            Termination::ScoreThreshold(200),
+        );
+        m.results.push(result([277, -199, -199, 121]));
+        assert!(!m.is_decided());
+        let mut m = Match::new(
+            players(),
+            Rules::default(),
+            Termination::ScoreThreshold(200),
+        );
+        m.results.push(result([278, -199, -199, 120]));
+        assert!(m.is_decided());
+
+        // Penalty markers have no limit, so no lead is safe until the match is over
+        let rules = Rules::default().pass_card_penalty(true);
+        let mut m = Match::new(players(), rules, Termination::Games(2));
+        m.results.push(result([1000, -334, -333, -333]));
+        assert!(!m.is_decided());
+    }
+
+    #[test]
+    fn test_dealer() {
+        let mut m = Match::new(players(), Rules::default(), Termination::Games(6));
+        let mut dealers = vec![m.dealer()];
+        for _ in 0..5 {
+            m.results.push(result([4, -1, -1, -2]));
+            dealers.push(m.dealer());
+        }
+        assert_eq!(dealers, [0, 1, 2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut m = Match::new(players(), Rules::default(), Termination::Games(3));
+        m.results.push(result([10, -2, -3, -5]));
+
+        let serialized = toml::to_string(&m).unwrap();
+        assert_eq!(toml::from_str::<Match>(&serialized).unwrap(), m);
+    }
+}
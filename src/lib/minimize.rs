@@ -0,0 +1,314 @@
+//! Shrinking a replay that makes the engine fail down to the smallest game that still
+//! fails the same way, to attach to a bug report.
+//!
+//! The replay is cut off after the turn that fails, then every card that isn't played
+//! before it is taken out of the hands, one at a time, as long as the game still fails
+//! at the same turn with the same error. What's left is written out as a test.
+
+use crate::{
+    cho_dai_di::{ChoDaiDi, ScoringRules, SuitPrecedence, TurnAction},
+    collections::{CardSet, Cards},
+    r#match::Rules,
+    replay::Replay,
+};
+use anyhow::bail;
+use std::{
+    any::Any,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// How a game failed while its turns were played back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// The number of turns taken before the one that failed
+    pub turn: usize,
+    /// The engine's error, or the message it panicked with
+    pub message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "turn {} failed: {}", self.turn + 1, self.message)
+    }
+}
+
+/// A game set up from chosen hands, and the turns that make it fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase<const PLAYERS: usize> {
+    pub rules: Rules,
+    /// The player chosen to go first, if any
+    pub first_player: Option<usize>,
+    /// The cards each player starts with, by seat
+    pub hands: [Cards<ChoDaiDi<PLAYERS>>; PLAYERS],
+    /// Every turn taken, up to and including the one that fails
+    pub actions: Vec<TurnAction<PLAYERS>>,
+    pub failure: Failure,
+}
+
+impl<const PLAYERS: usize> TestCase<PLAYERS> {
+    /// Play the turns from the start, returning the first failure, if there is one. A
+    /// game that can't be set up from the hands doesn't count as failing.
+    ///
+    /// Panics are caught, but the panic hook still runs, so the binary silences it while
+    /// it shrinks a replay.
+    pub fn run(&self) -> Option<Failure> {
+        let mut game = ChoDaiDi::new_game_with_hands(self.hands.clone()).ok()?;
+        self.rules.apply(&mut game);
+        if let Some(player) = self.first_player {
+            game.set_first_player(player).ok()?;
+        }
+
+        for (turn, action) in self.actions.iter().enumerate() {
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| game.advance(action.clone())));
+            let message = match outcome {
+                Ok(Ok(_)) => continue,
+                Ok(Err(e)) => e.to_string(),
+                Err(payload) => panic_message(payload),
+            };
+            return Some(Failure { turn, message });
+        }
+
+        None
+    }
+
+    /// The number of cards in every hand put together
+    pub fn cards(&self) -> usize {
+        self.hands.iter().map(Cards::len).sum()
+    }
+
+    fn still_fails(&self) -> bool {
+        self.run().as_ref() == Some(&self.failure)
+    }
+}
+
+/// Find the smallest game that fails the same way as `replay`: the fewest turns, and as
+/// few cards as can be taken out of the hands one by one.
+pub fn minimize<const PLAYERS: usize>(
+    replay: &Replay<PLAYERS>,
+) -> anyhow::Result<TestCase<PLAYERS>> {
+    let mut case = TestCase {
+        rules: replay.rules,
+        first_player: replay.first_player,
+        hands: replay.new_game()?.hands(),
+        actions: replay.actions.clone(),
+        failure: Failure {
+            turn: 0,
+            message: String::new(),
+        },
+    };
+    let Some(failure) = case.run() else {
+        bail!("the replay plays back without failing");
+    };
+    // The turns after the failure are never reached
+    case.actions.truncate(failure.turn + 1);
+    case.failure = failure;
+
+    // Cards played before the failure have to stay, or the game fails sooner
+    let played: CardSet = case
+        .actions
+        .iter()
+        .take(case.failure.turn)
+        .filter_map(|action| match action {
+            TurnAction::Play(cards) => Some(CardSet::from(cards)),
+            TurnAction::Pass => None,
+        })
+        .fold(CardSet::new(), |all, cards| all.union(&cards));
+    // Taking a card out can change who leads or when a round ends, which can let other
+    // cards go, so keep going until a whole pass removes nothing
+    let mut shrunk = true;
+    while shrunk {
+        shrunk = false;
+        for seat in 0..PLAYERS {
+            for card in case.hands[seat].clone().iter() {
+                if played.contains(card) {
+                    continue;
+                }
+                let mut candidate = case.clone();
+                candidate.hands[seat].retain(|it| it != card);
+                if candidate.still_fails() {
+                    case = candidate;
+                    shrunk = true;
+                }
+            }
+        }
+    }
+
+    Ok(case)
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(message) => (*message).to_owned(),
+            Err(_) => "the engine panicked".to_owned(),
+        },
+    }
+}
+
+/// Written out as a test that plays the game and fails where the replay did.
+impl<const PLAYERS: usize> fmt::Display for TestCase<PLAYERS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "// Shrunk with `dai-di minimize`: {}", self.failure)?;
+        writeln!(f, "use card_games::prelude::*;")?;
+        writeln!(f)?;
+        writeln!(f, "#[test]")?;
+        writeln!(f, "fn test_minimized_failure() {{")?;
+        writeln!(
+            f,
+            "    let hands: [Cards<ChoDaiDi<{PLAYERS}>>; {PLAYERS}] = ["
+        )?;
+        for hand in &self.hands {
+            writeln!(f, "        \"{hand}\",")?;
+        }
+        writeln!(f, "    ]")?;
+        writeln!(f, "    .map(|hand| hand.parse().unwrap());")?;
+        writeln!(
+            f,
+            "    let mut game = ChoDaiDi::new_game_with_hands(hands).unwrap();"
+        )?;
+        for setting in rule_settings(&self.rules) {
+            writeln!(f, "    game.{setting};")?;
+        }
+        if let Some(player) = self.first_player {
+            writeln!(f, "    game.set_first_player({player}).unwrap();")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "    for action in [")?;
+        for action in &self.actions {
+            match action {
+                TurnAction::Play(cards) => writeln!(f, "        \"{cards}\",")?,
+                TurnAction::Pass => writeln!(f, "        \"pass\",")?,
+            }
+        }
+        writeln!(f, "    ] {{")?;
+        writeln!(f, "        let action = match action {{")?;
+        writeln!(f, "            \"pass\" => TurnAction::Pass,")?;
+        writeln!(
+            f,
+            "            cards => TurnAction::Play(cards.parse().unwrap()),"
+        )?;
+        writeln!(f, "        }};")?;
+        writeln!(f, "        game.advance(action).unwrap();")?;
+        writeln!(f, "    }}")?;
+        writeln!(f, "}}")
+    }
+}
+
+/// The calls that set up a game with the rules that differ from the defaults.
+fn rule_settings(rules: &Rules) -> Vec<String> {
+    let defaults = Rules::default();
+    let mut settings = Vec::new();
+    let flags = [
+        (rules.revolutions_enabled, "set_revolutions_enabled"),
+        (rules.instant_wins_enabled, "set_instant_wins_enabled"),
+        (rules.pass_card_penalty, "set_pass_card_penalty"),
+        (rules.flush_beats_full_house, "set_flush_beats_full_house"),
+        (
+            rules.three_of_diamonds_opens_alone,
+            "set_three_of_diamonds_opens_alone",
+        ),
+        (
+            rules.must_play_lowest_single_first,
+            "set_must_play_lowest_single_first",
+        ),
+        (rules.rated, "set_rated"),
+    ];
+    for (enabled, setter) in flags {
+        if enabled {
+            settings.push(format!("{setter}(true)"));
+        }
+    }
+    if rules.suit_precedence != SuitPrecedence::Standard {
+        settings.push(format!(
+            "set_suit_precedence(SuitPrecedence::{:?})",
+            rules.suit_precedence
+        ));
+    }
+    if rules.max_candidate_plays != defaults.max_candidate_plays {
+        settings.push(format!(
+            "set_max_candidate_plays({:?})",
+            rules.max_candidate_plays
+        ));
+    }
+    if rules.scoring != ScoringRules::default() {
+        settings.push(format!("set_scoring_rules({:?})", rules.scoring));
+    }
+
+    settings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minimize, TestCase};
+    use crate::{
+        arbitrary::play_weakest,
+        cho_dai_di::{ChoDaiDi, TurnAction, FOUR_PLAYERS},
+        r#match::Rules,
+        replay::Replay,
+    };
+
+    /// A replay of a game's first turns, then a play of a card the player doesn't hold.
+    fn failing_replay() -> Replay<FOUR_PLAYERS> {
+        let mut game = ChoDaiDi::<FOUR_PLAYERS>::new_game_with_seed(531);
+        game.set_revolutions_enabled(true);
+        play_weakest(&mut game, 8);
+        let names = ["A", "B", "C", "D"].map(ToOwned::to_owned).to_vec();
+        let mut replay = Replay::record(&game, 531, None, names);
+
+        let someone_else = (game.whose_turn() + 1) % FOUR_PLAYERS;
+        let card = *game.hands()[someone_else].iter().next().unwrap();
+        replay.actions.push(TurnAction::Play(card.into()));
+        replay.actions.extend([TurnAction::Pass, TurnAction::Pass]);
+
+        replay
+    }
+
+    #[test]
+    fn test_minimize() {
+        let replay = failing_replay();
+        let case = minimize(&replay).unwrap();
+
+        assert_eq!(case.failure.turn, 8);
+        assert_eq!(case.failure.message, "you don't have all of those cards");
+        assert_eq!(case.actions, replay.actions[..9]);
+        assert!(case.cards() < 52, "{case:?}");
+        assert_eq!(case.run(), Some(case.failure.clone()));
+
+        // Every card that's left is needed
+        for seat in 0..FOUR_PLAYERS {
+            for card in case.hands[seat].iter() {
+                let mut smaller = case.clone();
+                smaller.hands[seat].retain(|it| it != card);
+                assert_ne!(smaller.run(), Some(case.failure.clone()), "{card}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimize_needs_a_failure() {
+        let mut replay = failing_replay();
+        replay.actions.truncate(8);
+        assert!(minimize(&replay).is_err());
+    }
+
+    #[test]
+    fn test_written_test_case() {
+        let case = minimize(&failing_replay()).unwrap();
+        let written = case.to_string();
+
+        assert!(written.contains("fn test_minimized_failure()"), "{written}");
+        assert!(
+            written.contains("game.set_revolutions_enabled(true);"),
+            "{written}"
+        );
+        assert!(written.contains("        \"3♦\",\n"), "{written}");
+
+        let plain = TestCase {
+            rules: Rules::default(),
+            ..case
+        };
+        assert!(!plain.to_string().contains("game.set_"), "{plain}");
+    }
+}
@@ -1,26 +1,64 @@
 #![allow(clippy::new_without_default)]
 
+pub mod advisor;
+#[cfg(any(test, feature = "test-util"))]
+pub mod arbitrary;
 pub mod card;
 pub mod cho_dai_di;
 pub mod collections;
+#[cfg(not(feature = "strict-api"))]
+pub mod compat;
+pub mod dataset;
+pub mod endgame;
+pub mod export;
+pub mod hint_format;
+pub mod r#match;
+pub mod minimize;
+pub mod palette;
+pub mod partition;
 pub mod player;
+pub mod position;
+pub mod prelude;
+pub mod protocol;
 pub mod rank;
+pub mod render;
+pub mod replay;
+pub mod score_format;
+pub mod simulation;
+pub mod stats;
 pub mod suit;
 
 use card::{Card, STANDARD_DECK};
-use once_cell::sync::Lazy;
 use rand::{prelude::*, rngs::SmallRng, SeedableRng};
-use std::{marker::PhantomData, sync::Mutex};
+use std::{cell::RefCell, marker::PhantomData};
+use uuid::Uuid;
 
-static RNG: Lazy<Mutex<SmallRng>> = Lazy::new(|| Mutex::new(SmallRng::from_entropy()));
+/// The version of the game, recorded in saves, replays, and the network handshake along
+/// with the [rules' fingerprint](r#match::Rules::fingerprint).
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+thread_local! {
+    // Each thread has its own, so games dealt on different threads don't wait on each other
+    static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_entropy());
+}
 
 pub fn shuffled_deck() -> Vec<Card> {
+    RNG.with(|rng| shuffled_deck_with(&mut *rng.borrow_mut()))
+}
+
+/// Shuffle a deck with the given RNG. Seeding the RNG makes the shuffle reproducible.
+pub fn shuffled_deck_with(rng: &mut impl Rng) -> Vec<Card> {
     let mut deck = STANDARD_DECK;
-    let mut rng = RNG.lock().unwrap();
-    deck.shuffle(&mut *rng);
+    deck.shuffle(rng);
     deck.into()
 }
 
+/// Generate a random ID used to correlate logs and records with a specific game.
+pub fn new_game_id() -> Uuid {
+    let bytes = RNG.with(|rng| rng.borrow_mut().gen());
+    uuid::Builder::from_random_bytes(bytes).into_uuid()
+}
+
 pub struct Deck<G> {
     cards: Vec<Card>,
     _game: PhantomData<G>,
@@ -38,7 +76,7 @@ impl<G> Deck<G> {
 
 #[cfg(test)]
 mod tests {
-    use super::shuffled_deck;
+    use super::{shuffled_deck, shuffled_deck_with};
     use crate::{
         cho_dai_di::{ChoDaiDi, FOUR_PLAYERS},
         Deck,
@@ -50,6 +88,25 @@ mod tests {
         assert_eq!(deck.len(), 52);
     }
 
+    #[test]
+    fn test_shuffled_deck_with_seed() {
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let a = shuffled_deck_with(&mut SmallRng::seed_from_u64(7));
+        let b = shuffled_deck_with(&mut SmallRng::seed_from_u64(7));
+        let c = shuffled_deck_with(&mut SmallRng::seed_from_u64(8));
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_new_game_id() {
+        let a = super::new_game_id();
+        let b = super::new_game_id();
+        assert_ne!(a, b);
+        assert_eq!(a.get_version(), Some(uuid::Version::Random));
+    }
+
     #[test]
     fn test_cho_dai_di_draw_starting_hands() {
         let mut deck = Deck::<ChoDaiDi<FOUR_PLAYERS>>::new();
@@ -0,0 +1,54 @@
+use crate::suit::Suit;
+use serde::{Deserialize, Serialize};
+
+/// Sets of terminal colors that front-ends use to highlight cards and scores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    /// Red and green, like a standard deck of cards
+    #[default]
+    Standard,
+    /// Blue and orange, which remain distinguishable with deuteranopia and protanopia
+    ColorblindSafe,
+    /// Bold, bright colors for low-contrast displays
+    HighContrast,
+}
+
+impl Palette {
+    /// The escape sequence that clears any color set by a palette.
+    pub const RESET: &'static str = "\x1b[0m";
+
+    /// The escape sequence used for positive values such as winning scores.
+    pub fn positive(&self) -> &'static str {
+        match self {
+            Palette::Standard => "\x1b[32m",
+            Palette::ColorblindSafe => "\x1b[38;5;33m",
+            Palette::HighContrast => "\x1b[1;92m",
+        }
+    }
+
+    /// The escape sequence used for negative values such as losing scores.
+    pub fn negative(&self) -> &'static str {
+        match self {
+            Palette::Standard => "\x1b[31m",
+            Palette::ColorblindSafe => "\x1b[38;5;208m",
+            Palette::HighContrast => "\x1b[1;91m",
+        }
+    }
+
+    /// The escape sequence used for cards of a suit. Returns `None` if cards of
+    /// this suit should be shown in the terminal's default color.
+    pub fn suit(&self, suit: Suit) -> Option<&'static str> {
+        match (self, suit) {
+            (Palette::Standard, Suit::Diamonds | Suit::Hearts) => Some("\x1b[31m"),
+            (Palette::Standard, Suit::Clubs | Suit::Spades) => None,
+            // A four-color deck so no suit relies on telling red from green
+            (Palette::ColorblindSafe, Suit::Diamonds) => Some("\x1b[38;5;33m"),
+            (Palette::ColorblindSafe, Suit::Hearts) => Some("\x1b[38;5;208m"),
+            (Palette::ColorblindSafe, Suit::Clubs) => Some("\x1b[38;5;250m"),
+            (Palette::ColorblindSafe, Suit::Spades) => None,
+            (Palette::HighContrast, Suit::Diamonds | Suit::Hearts) => Some("\x1b[1;91m"),
+            (Palette::HighContrast, Suit::Clubs | Suit::Spades) => Some("\x1b[1;97m"),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Planning how a hand will be played by splitting it into combinations.
+
+use crate::{
+    card::Card,
+    cho_dai_di::{CardOrdering, ChoDaiDi},
+    collections::Cards,
+};
+use itertools::Itertools;
+use std::cmp::Reverse;
+
+/// Split a hand into the combinations it could be played as under `ordering`, strongest
+/// first.
+///
+/// Five-card hands are set aside first, taking the strongest kind each time and, among
+/// hands of that kind, the one with the lowest key card. The remaining cards are
+/// grouped by rank into triplets, pairs, and singles.
+pub fn partition<const PLAYERS: usize>(
+    hand: &Cards<ChoDaiDi<PLAYERS>>,
+    ordering: &CardOrdering,
+) -> Vec<Cards<ChoDaiDi<PLAYERS>>> {
+    let mut remaining: Vec<Card> = hand.iter().copied().collect();
+    let mut combinations = Vec::new();
+
+    while let Some(five_card_hand) = strongest_five_card_hand(&remaining, ordering) {
+        remaining.retain(|card| !five_card_hand.contains(card));
+        combinations.push(five_card_hand);
+    }
+
+    let mut ranks = Cards::<ChoDaiDi<PLAYERS>>::RANK_PRECEDENCE.to_vec();
+    ranks.sort_by_key(|rank| Reverse(ordering.rank_key(*rank)));
+    let mut groups: Vec<Vec<Card>> = Vec::new();
+    for rank in &ranks {
+        let cards: Vec<Card> = remaining
+            .iter()
+            .filter(|card| card.rank() == *rank)
+            .copied()
+            .collect();
+        match cards.len() {
+            0 => {}
+            // There's no four-card play, so a lone four of a kind is two pairs
+            4 => groups.extend(cards.chunks(2).map(<[Card]>::to_vec)),
+            _ => groups.push(cards),
+        }
+    }
+    groups.sort_by_key(|group| Reverse(group.len()));
+    combinations.extend(groups.into_iter().map(Cards::from));
+
+    combinations
+}
+
+/// Returns true if `play` takes some, but not all, of the cards of a combination in
+/// `partition`, whatever the size of the play.
+pub fn breaks_up<const PLAYERS: usize>(
+    partition: &[Cards<ChoDaiDi<PLAYERS>>],
+    play: &Cards<ChoDaiDi<PLAYERS>>,
+) -> bool {
+    partition.iter().any(|combination| {
+        let used = combination
+            .iter()
+            .filter(|card| play.contains(card))
+            .count();
+        used > 0 && used < combination.len()
+    })
+}
+
+fn strongest_five_card_hand<const PLAYERS: usize>(
+    cards: &[Card],
+    ordering: &CardOrdering,
+) -> Option<Cards<ChoDaiDi<PLAYERS>>> {
+    cards
+        .iter()
+        .copied()
+        .combinations(5)
+        .map(Cards::<ChoDaiDi<PLAYERS>>::from)
+        .filter_map(|cards| {
+            let (combination, key_card) = cards.classify_under(ordering)?;
+            Some((combination, key_card, cards))
+        })
+        .max_by(|(a_combination, a_key, _), (b_combination, b_key, _)| {
+            ordering
+                .cmp_five_card_hand(a_combination, b_combination)
+                .then_with(|| ordering.cmp_card(b_key, a_key))
+        })
+        .map(|(_, _, cards)| cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{breaks_up, partition};
+    use crate::{
+        cho_dai_di::{CardOrdering, ChoDaiDi, Combination},
+        collections::Cards,
+    };
+
+    fn cards(cards: Vec<&str>) -> Cards<ChoDaiDi> {
+        Cards::try_from(cards).unwrap()
+    }
+
+    #[test]
+    fn test_partition() {
+        let hand = cards(vec![
+            "3D", "4S", "5H", "6C", "7D", "9S", "9H", "9D", "KD", "KC", "2S",
+        ]);
+        let planned = partition(&hand, &CardOrdering::STANDARD);
+
+        // The full house is stronger than the straight, so it's set aside first
+        assert_eq!(planned[0].five_card_hand(), Some(Combination::FullHouse));
+        assert_eq!(planned[1].five_card_hand(), Some(Combination::Straight));
+        assert_eq!(planned[2], cards(vec!["2S"]));
+        assert_eq!(planned.iter().map(|it| it.len()).sum::<usize>(), hand.len());
+    }
+
+    #[test]
+    fn test_breaks_up() {
+        let hand = cards(vec!["3D", "4S", "5H", "6C", "7D", "9S", "9H", "2S"]);
+        let planned = partition(&hand, &CardOrdering::STANDARD);
+
+        assert!(!breaks_up(&planned, &cards(vec!["2S"])));
+        assert!(!breaks_up(&planned, &cards(vec!["9S", "9H"])));
+        assert!(!breaks_up(
+            &planned,
+            &cards(vec!["3D", "4S", "5H", "6C", "7D"])
+        ));
+        assert!(breaks_up(&planned, &cards(vec!["3D"])));
+        assert!(breaks_up(&planned, &cards(vec!["9S"])));
+
+        // A play that's larger than the combination still breaks it up
+        let planned = [cards(vec!["9S", "9H"]), cards(vec!["8D"])];
+        assert!(breaks_up(
+            &planned,
+            &cards(vec!["5H", "6C", "7D", "8D", "9S"])
+        ));
+    }
+
+    #[test]
+    fn test_partition_under_ordering() {
+        let hand = cards(vec!["9S", "9H", "9D", "KD", "KC", "3D", "5D", "7D"]);
+        assert_eq!(
+            partition(&hand, &CardOrdering::STANDARD)[0].five_card_hand(),
+            Some(Combination::FullHouse)
+        );
+
+        let mut ordering = CardOrdering::STANDARD;
+        ordering.set_flush_beats_full_house(true);
+        let planned = partition(&hand, &ordering);
+        assert_eq!(planned[0].five_card_hand(), Some(Combination::Flush));
+        assert_eq!(planned[1], cards(vec!["9S", "9H"]));
+    }
+}
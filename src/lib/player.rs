@@ -2,11 +2,90 @@ use rand::seq::SliceRandom;
 use std::sync::Mutex;
 
 pub mod human {
-    use crate::collections::SortCardsBy;
+    use crate::collections::{Cards, SortCardsBy};
+    use anyhow::Context;
+    use serde::{Deserialize, Serialize};
+
+    /// Single-key commands available at the play prompt.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Shortcut {
+        /// Play the lowest single card that's allowed
+        LowestSingle,
+        /// Suggest a play
+        Hint,
+        /// Summarize the state of the round
+        Recap,
+        /// Toggle between sorting by rank and sorting by suit
+        Sort,
+    }
+
+    /// The keys bound to each [`Shortcut`].
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct Keybindings {
+        pub lowest_single: String,
+        pub hint: String,
+        pub recap: String,
+        pub sort: String,
+    }
+
+    impl Default for Keybindings {
+        fn default() -> Self {
+            Keybindings {
+                lowest_single: "l".to_owned(),
+                hint: "h".to_owned(),
+                recap: "r".to_owned(),
+                sort: "s".to_owned(),
+            }
+        }
+    }
+
+    impl Keybindings {
+        /// Inputs that always pass or quit, so they can't be bound to a shortcut.
+        pub const RESERVED: [&'static str; 4] = ["p", "pass", "q", "quit"];
+
+        /// Get the shortcut bound to some input, if any.
+        pub fn shortcut(&self, input: &str) -> Option<Shortcut> {
+            self.bindings()
+                .into_iter()
+                .find(|(key, _, _)| !key.is_empty() && key.eq_ignore_ascii_case(input))
+                .map(|(_, _, shortcut)| shortcut)
+        }
+
+        /// Check that no shortcut is bound to a reserved input, which it would shadow.
+        pub fn validate(&self) -> anyhow::Result<()> {
+            for (key, name, _) in self.bindings() {
+                if Self::RESERVED
+                    .iter()
+                    .any(|reserved| reserved.eq_ignore_ascii_case(key))
+                {
+                    anyhow::bail!(
+                        "`{key}` already passes or quits, so it can't be bound to `{name}`"
+                    );
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Each shortcut, with the key it's bound to and the name of its setting.
+        fn bindings(&self) -> [(&String, &'static str, Shortcut); 4] {
+            [
+                (&self.lowest_single, "lowest_single", Shortcut::LowestSingle),
+                (&self.hint, "hint", Shortcut::Hint),
+                (&self.recap, "recap", Shortcut::Recap),
+                (&self.sort, "sort", Shortcut::Sort),
+            ]
+        }
+    }
 
     pub struct Player {
         name: String,
         sort_cards_by: SortCardsBy,
+        explain_passes: bool,
+        keybindings: Keybindings,
+        rated: bool,
+        confirm_plays: bool,
     }
 
     impl Player {
@@ -14,6 +93,10 @@ pub mod human {
             Player {
                 name: name.into(),
                 sort_cards_by: SortCardsBy::Rank,
+                explain_passes: false,
+                keybindings: Keybindings::default(),
+                rated: false,
+                confirm_plays: true,
             }
         }
 
@@ -31,18 +114,256 @@ pub mod human {
         pub fn sort_cards_by(&self) -> SortCardsBy {
             self.sort_cards_by
         }
+
+        pub fn set_sort_cards_by(&mut self, sort_cards_by: SortCardsBy) {
+            self.sort_cards_by = sort_cards_by;
+        }
+
+        /// If true, the player is told what they're giving up whenever they pass. This is
+        /// always false in rated games.
+        pub fn explain_passes(&self) -> bool {
+            self.explain_passes && !self.rated
+        }
+
+        pub fn set_explain_passes(&mut self, explain_passes: bool) {
+            self.explain_passes = explain_passes;
+        }
+
+        pub fn keybindings(&self) -> &Keybindings {
+            &self.keybindings
+        }
+
+        pub fn set_keybindings(&mut self, keybindings: Keybindings) {
+            self.keybindings = keybindings;
+        }
+
+        /// If true, the player is in a rated game, so hints and other assists are off.
+        #[cfg(not(feature = "strict-api"))]
+        #[deprecated(note = "use `Rules::rated` and `GameView::is_rated` instead")]
+        pub fn rated(&self) -> bool {
+            self.rated
+        }
+
+        #[cfg(not(feature = "strict-api"))]
+        #[deprecated(note = "use `Rules::rated` and `GameView::is_rated` instead")]
+        pub fn set_rated(&mut self, rated: bool) {
+            self.rated = rated;
+        }
+
+        /// If true, the player is asked to confirm each play before it's made, so that a
+        /// mistyped play can be undone.
+        pub fn confirm_plays(&self) -> bool {
+            self.confirm_plays
+        }
+
+        pub fn set_confirm_plays(&mut self, confirm_plays: bool) {
+            self.confirm_plays = confirm_plays;
+        }
+    }
+
+    /// Parse the cards a player chose from `hand`.
+    ///
+    /// Cards may be picked by their position in the hand as it was displayed, counting
+    /// from 1 (e.g. `1 4 7`), or by their codes (e.g. `3d 5c`). The style is chosen by
+    /// whether every entry is a number.
+    pub fn parse_selection<G>(input: &str, hand: &Cards<G>) -> anyhow::Result<Cards<G>> {
+        let entries: Vec<_> = input
+            .split_whitespace()
+            .map(|it| it.trim_end_matches(','))
+            .collect();
+        if entries.is_empty() || !entries.iter().all(|it| it.parse::<usize>().is_ok()) {
+            return input.parse();
+        }
+
+        let mut cards = Vec::with_capacity(entries.len());
+        let mut picked = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let index: usize = entry.parse()?;
+            let card = index
+                .checked_sub(1)
+                .and_then(|i| hand.iter().nth(i))
+                .with_context(|| {
+                    format!("there's no card {index}, pick from 1 to {}", hand.len())
+                })?;
+            anyhow::ensure!(!picked.contains(&index), "card {index} was picked twice");
+            picked.push(index);
+            cards.push(*card);
+        }
+
+        Ok(Cards::from(cards))
     }
 }
 
 pub mod ai {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    use crate::{
+        advisor::{preserving_play, rank_plays, weakest_play, weighted_play},
+        cho_dai_di::{ChoDaiDi, GameView, TurnAction, FOUR_PLAYERS},
+        collections::Cards,
+    };
+    use rand::{seq::SliceRandom, Rng, RngCore};
+    use serde::{Deserialize, Serialize};
+    use std::{fmt, str::FromStr};
+
+    /// A way for a computer player to choose its plays.
+    ///
+    /// [`Player`] implements this for the built-in [`Strategy`] variants. Implement it
+    /// for your own type to seat a bot of your own.
+    pub trait AiStrategy<const PLAYERS: usize = FOUR_PLAYERS> {
+        /// Choose what to do with `hand` on this turn.
+        ///
+        /// Any randomness should come from `rng`, so that games played with the same
+        /// seed play out the same way.
+        fn choose_play(
+            &mut self,
+            view: &GameView<'_, PLAYERS>,
+            hand: &Cards<ChoDaiDi<PLAYERS>>,
+            rng: &mut dyn RngCore,
+        ) -> TurnAction<PLAYERS>;
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
     pub enum Strategy {
+        /// Play any legal play
         Random,
+        /// Play whichever play [`crate::advisor::rank_plays`] ranks highest
+        Advised,
+        /// Always play the weakest legal play, found with [`crate::advisor::weakest_play`]
+        LowestFirst,
+        /// Play the weakest play that doesn't break up a stronger planned combination,
+        /// found with [`crate::advisor::preserving_play`]
+        HandPreserving,
+        /// Play whichever play scores highest by the player's [`Weights`], found with
+        /// [`crate::advisor::weighted_play`]
+        Weighted,
+    }
+
+    impl fmt::Display for Strategy {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Strategy::Random => write!(f, "random"),
+                Strategy::Advised => write!(f, "advised"),
+                Strategy::LowestFirst => write!(f, "lowest-first"),
+                Strategy::HandPreserving => write!(f, "hand-preserving"),
+                Strategy::Weighted => write!(f, "weighted"),
+            }
+        }
+    }
+
+    impl FromStr for Strategy {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_lowercase().as_str() {
+                "random" => Ok(Strategy::Random),
+                "advised" => Ok(Strategy::Advised),
+                "lowest-first" => Ok(Strategy::LowestFirst),
+                "hand-preserving" => Ok(Strategy::HandPreserving),
+                "weighted" => Ok(Strategy::Weighted),
+                _ => anyhow::bail!(
+                    "'{s}' isn't a strategy, try 'random', 'advised', 'lowest-first', 'hand-preserving', or 'weighted'"
+                ),
+            }
+        }
+    }
+
+    /// How much a [`Strategy::Weighted`] player cares about each thing a play does. Each
+    /// play is scored by adding up what it does, times the weight of each, and the one
+    /// that scores highest is made. A play that wins the game is always made.
+    ///
+    /// Weights are kept in TOML files, where any that are left out keep their defaults:
+    ///
+    /// ```toml
+    /// cards_shed = 1.0
+    /// strength = 1.5
+    /// breaking_up = 0.5
+    /// ```
+    ///
+    /// The defaults score plays the way [`Strategy::Advised`] ranks them.
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct Weights {
+        /// Added for each card the play gets rid of
+        pub cards_shed: f32,
+        /// Taken off for the strength of the play's highest card, from 0 for the lowest
+        /// card to 1 for the highest, so that strong cards are held for later
+        pub strength: f32,
+        /// Taken off if the play spends the hand's highest card
+        pub highest_card: f32,
+        /// Taken off if the play breaks up a combination that the rest of the hand is
+        /// planned to be played as, as planned by [`crate::partition::partition`]
+        pub breaking_up: f32,
+    }
+
+    impl Default for Weights {
+        fn default() -> Self {
+            Weights {
+                cards_shed: 1.0,
+                strength: 1.0,
+                highest_card: 0.0,
+                breaking_up: 0.0,
+            }
+        }
+    }
+
+    /// Presets for how well computer players play.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum Difficulty {
+        Easy,
+        Medium,
+        Hard,
+    }
+
+    impl Difficulty {
+        pub fn strategy(self) -> Strategy {
+            match self {
+                Difficulty::Easy | Difficulty::Medium => Strategy::Advised,
+                Difficulty::Hard => Strategy::HandPreserving,
+            }
+        }
+
+        /// How often a player at this difficulty makes a deliberate mistake
+        pub fn error_rate(self) -> f64 {
+            match self {
+                Difficulty::Easy => 0.4,
+                Difficulty::Medium => 0.15,
+                Difficulty::Hard => 0.0,
+            }
+        }
+    }
+
+    impl fmt::Display for Difficulty {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Difficulty::Easy => write!(f, "easy"),
+                Difficulty::Medium => write!(f, "medium"),
+                Difficulty::Hard => write!(f, "hard"),
+            }
+        }
+    }
+
+    impl FromStr for Difficulty {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s.to_lowercase().as_str() {
+                "easy" => Ok(Difficulty::Easy),
+                "medium" => Ok(Difficulty::Medium),
+                "hard" => Ok(Difficulty::Hard),
+                _ => anyhow::bail!("'{s}' isn't a difficulty, try 'easy', 'medium', or 'hard'"),
+            }
+        }
     }
 
     pub struct Player {
         name: String,
         strategy: Strategy,
+        error_rate: f64,
+        /// The preset this player was set up with, if any
+        difficulty: Option<Difficulty>,
+        /// What the player cares about, if it plays [`Strategy::Weighted`]
+        weights: Weights,
     }
 
     impl Player {
@@ -50,6 +371,29 @@ pub mod ai {
             Player {
                 name: name.into(),
                 strategy: Strategy::Random,
+                error_rate: 0.0,
+                difficulty: None,
+                weights: Weights::default(),
+            }
+        }
+
+        pub fn with_strategy(name: impl Into<String>, strategy: Strategy) -> Self {
+            Player {
+                name: name.into(),
+                strategy,
+                error_rate: 0.0,
+                difficulty: None,
+                weights: Weights::default(),
+            }
+        }
+
+        pub fn with_difficulty(name: impl Into<String>, difficulty: Difficulty) -> Self {
+            Player {
+                name: name.into(),
+                strategy: difficulty.strategy(),
+                error_rate: difficulty.error_rate(),
+                difficulty: Some(difficulty),
+                weights: Weights::default(),
             }
         }
 
@@ -60,6 +404,82 @@ pub mod ai {
         pub fn strategy(&self) -> Strategy {
             self.strategy
         }
+
+        /// How often this player passes over its best play for a worse, but still legal,
+        /// one. Only [`Strategy::Advised`] players make mistakes, since random play
+        /// doesn't have a best play.
+        pub fn error_rate(&self) -> f64 {
+            self.error_rate
+        }
+
+        /// Get the difficulty preset this player is playing at, if any.
+        pub fn difficulty(&self) -> Option<Difficulty> {
+            self.difficulty
+        }
+
+        /// Play with the strategy and error rate of a difficulty preset.
+        pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+            self.strategy = difficulty.strategy();
+            self.error_rate = difficulty.error_rate();
+            self.difficulty = Some(difficulty);
+        }
+
+        /// The weights the player scores plays by, if it plays [`Strategy::Weighted`].
+        pub fn weights(&self) -> Weights {
+            self.weights
+        }
+
+        /// Score plays by `weights` from the next turn on.
+        pub fn set_weights(&mut self, weights: Weights) {
+            self.weights = weights;
+        }
+
+        /// Set the error rate, which is clamped between 0 and 1, with NaN taken as 0. The
+        /// player is no longer playing at a difficulty preset afterwards.
+        pub fn set_error_rate(&mut self, error_rate: f64) {
+            self.error_rate = match error_rate.is_nan() {
+                true => 0.0,
+                false => error_rate.clamp(0.0, 1.0),
+            };
+            self.difficulty = None;
+        }
+    }
+
+    impl<const PLAYERS: usize> AiStrategy<PLAYERS> for Player {
+        fn choose_play(
+            &mut self,
+            view: &GameView<'_, PLAYERS>,
+            hand: &Cards<ChoDaiDi<PLAYERS>>,
+            rng: &mut dyn RngCore,
+        ) -> TurnAction<PLAYERS> {
+            let mut hand = hand.clone();
+            hand.sort_by_rank_under(&view.ordering());
+
+            let play = match self.strategy {
+                Strategy::Random => view.possible_plays(&hand).choose(rng).cloned(),
+                Strategy::Advised => {
+                    let mut ranked = rank_plays(view, &hand);
+                    // Sometimes pick one of the worse plays on purpose
+                    let index = if ranked.len() > 1 && rng.gen_bool(self.error_rate) {
+                        rng.gen_range(1..ranked.len())
+                    } else {
+                        0
+                    };
+                    (!ranked.is_empty()).then(|| ranked.swap_remove(index).0)
+                }
+                Strategy::LowestFirst => weakest_play(view, &hand),
+                Strategy::HandPreserving => preserving_play(view, &hand),
+                Strategy::Weighted => weighted_play(view, &hand, &self.weights),
+            };
+
+            match play {
+                Some(play) => TurnAction::Play(play),
+                None => {
+                    tracing::debug!("{} found no play to make, so it passes", self.name);
+                    TurnAction::Pass
+                }
+            }
+        }
     }
 }
 
@@ -70,11 +490,13 @@ pub fn new_ai_player() -> ai::Player {
 
     let mut rng = rand::thread_rng();
     let name = loop {
-        let chosen_names = CHOSEN_NAMES.lock().unwrap();
-        debug_assert_ne!(chosen_names.len(), AI_NAMES.len(), "ran out of AI names");
+        let mut chosen_names = CHOSEN_NAMES.lock().unwrap();
+        // Every name has been used, so they're used again
+        if chosen_names.len() == AI_NAMES.len() {
+            chosen_names.clear();
+        }
 
         let name = AI_NAMES.choose(&mut rng).unwrap();
-        let mut chosen_names = chosen_names;
         if !chosen_names.contains(name) {
             chosen_names.push(name);
             break *name;
@@ -84,6 +506,201 @@ pub fn new_ai_player() -> ai::Player {
     ai::Player::new(name)
 }
 
+/// Create `count` AI players for one table, each with a different name that isn't
+/// one of the `taken` names of the players already sitting there.
+pub fn new_ai_players(count: usize, taken: &[&str]) -> anyhow::Result<Vec<ai::Player>> {
+    let names: Vec<_> = AI_NAMES
+        .iter()
+        .filter(|name| !taken.contains(name))
+        .collect();
+    if count > names.len() {
+        anyhow::bail!("there are only {} AI players to choose from", names.len());
+    }
+
+    Ok(names
+        .choose_multiple(&mut rand::thread_rng(), count)
+        .map(|&&name| ai::Player::new(name))
+        .collect())
+}
+
 pub fn new_human_player(name: &str) -> human::Player {
     human::Player::new(name)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ai,
+        human::{parse_selection, Keybindings, Shortcut},
+    };
+    use crate::{cho_dai_di::ChoDaiDi, collections::Cards};
+
+    #[test]
+    fn test_keybindings_shortcut() {
+        let keybindings = Keybindings::default();
+        assert_eq!(keybindings.shortcut("l"), Some(Shortcut::LowestSingle));
+        assert_eq!(keybindings.shortcut("H"), Some(Shortcut::Hint));
+        assert_eq!(keybindings.shortcut("3d"), None);
+        assert_eq!(keybindings.shortcut(""), None);
+
+        let keybindings = Keybindings {
+            hint: "?".to_owned(),
+            sort: String::new(),
+            ..Default::default()
+        };
+        assert_eq!(keybindings.shortcut("?"), Some(Shortcut::Hint));
+        assert_eq!(keybindings.shortcut("h"), None);
+        assert_eq!(keybindings.shortcut("s"), None);
+    }
+
+    #[test]
+    fn test_keybindings_validate() {
+        assert!(Keybindings::default().validate().is_ok());
+
+        for reserved in ["p", "Pass", "q", "QUIT"] {
+            let keybindings = Keybindings {
+                hint: reserved.to_owned(),
+                ..Default::default()
+            };
+            assert!(keybindings.validate().is_err(), "{reserved}");
+        }
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        let hand: Cards<ChoDaiDi> = Cards::try_from(vec!["3D", "5C", "9H", "9S"]).unwrap();
+
+        let by_index = parse_selection("1 3, 4", &hand).unwrap();
+        assert_eq!(by_index, Cards::try_from(vec!["3D", "9H", "9S"]).unwrap());
+        let by_code = parse_selection("3d 5c", &hand).unwrap();
+        assert_eq!(by_code, Cards::try_from(vec!["3D", "5C"]).unwrap());
+
+        assert!(parse_selection("0", &hand).is_err());
+        assert!(parse_selection("5", &hand).is_err());
+        assert!(parse_selection("2 2", &hand).is_err());
+        assert!(parse_selection("1 5c", &hand).is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict-api"))]
+    #[allow(deprecated)]
+    fn test_rated_disables_explain_passes() {
+        let mut player = super::human::Player::new("Rated");
+        player.set_explain_passes(true);
+        assert!(player.explain_passes());
+
+        player.set_rated(true);
+        assert!(!player.explain_passes());
+    }
+
+    #[test]
+    fn test_error_rate() {
+        let mut player = ai::Player::with_difficulty("Easy", ai::Difficulty::Easy);
+        assert_eq!(player.strategy(), ai::Strategy::Advised);
+        assert_eq!(player.error_rate(), ai::Difficulty::Easy.error_rate());
+
+        assert_eq!(player.difficulty(), Some(ai::Difficulty::Easy));
+
+        player.set_error_rate(1.5);
+        assert_eq!(player.error_rate(), 1.0);
+        assert_eq!(player.difficulty(), None);
+        player.set_error_rate(-1.0);
+        assert_eq!(player.error_rate(), 0.0);
+        player.set_error_rate(f64::INFINITY);
+        assert_eq!(player.error_rate(), 1.0);
+        player.set_error_rate(f64::NAN);
+        assert_eq!(player.error_rate(), 0.0);
+
+        let medium = ai::Player::with_difficulty("Medium", ai::Difficulty::Medium);
+        assert_eq!(medium.strategy(), ai::Strategy::Advised);
+        assert!(medium.error_rate() > 0.0);
+    }
+
+    #[test]
+    fn test_difficulty() {
+        assert_eq!(
+            "Hard".parse::<ai::Difficulty>().unwrap(),
+            ai::Difficulty::Hard
+        );
+        assert!("impossible".parse::<ai::Difficulty>().is_err());
+
+        for difficulty in [
+            ai::Difficulty::Easy,
+            ai::Difficulty::Medium,
+            ai::Difficulty::Hard,
+        ] {
+            assert_eq!(
+                difficulty.to_string().parse::<ai::Difficulty>().unwrap(),
+                difficulty
+            );
+        }
+        assert_eq!(
+            ai::Difficulty::Hard.strategy(),
+            ai::Strategy::HandPreserving
+        );
+    }
+
+    #[test]
+    fn test_strategy_names() {
+        for strategy in [
+            ai::Strategy::Random,
+            ai::Strategy::Advised,
+            ai::Strategy::LowestFirst,
+            ai::Strategy::HandPreserving,
+            ai::Strategy::Weighted,
+        ] {
+            assert_eq!(
+                strategy.to_string().parse::<ai::Strategy>().unwrap(),
+                strategy
+            );
+        }
+        assert_eq!(
+            "Lowest-First".parse::<ai::Strategy>().unwrap(),
+            ai::Strategy::LowestFirst
+        );
+        assert!("cheating".parse::<ai::Strategy>().is_err());
+    }
+
+    #[test]
+    fn test_new_ai_players() {
+        // Tables are filled over and over without running out of names
+        for _ in 0..10 {
+            let players = super::new_ai_players(3, &["AImy"]).unwrap();
+            let mut names: Vec<_> = players.iter().map(ai::Player::name).collect();
+            names.sort_unstable();
+            names.dedup();
+            assert_eq!(names.len(), 3);
+            assert!(!names.contains(&"AImy"));
+            super::new_ai_player();
+        }
+        assert!(super::new_ai_players(5, &["AImy"]).is_err());
+    }
+
+    #[test]
+    fn test_ai_strategy() {
+        use super::ai::AiStrategy;
+        use crate::{
+            card::Card,
+            cho_dai_di::{new_4p_game, TurnAction},
+            collections::Cards,
+        };
+
+        let mut game = new_4p_game();
+        game.play_cards(Cards::from(Card::THREE_OF_DIAMONDS))
+            .unwrap();
+        let hand = Cards::try_from(vec!["9H", "4D", "2S"]).unwrap();
+
+        let mut player = ai::Player::with_strategy("Low", ai::Strategy::LowestFirst);
+        let rng = &mut rand::thread_rng();
+        assert_eq!(
+            player.choose_play(&game.view(), &hand, rng),
+            TurnAction::Play(Cards::from(Card::FOUR_OF_DIAMONDS))
+        );
+
+        let hand = Cards::try_from(vec!["3D"]).unwrap();
+        assert_eq!(
+            player.choose_play(&game.view(), &hand, rng),
+            TurnAction::Pass
+        );
+    }
+}
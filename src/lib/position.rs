@@ -0,0 +1,113 @@
+//! Generating random mid-game positions, e.g. for puzzles, fuzzing, and benchmarks.
+
+use crate::{
+    cho_dai_di::{ChoDaiDi, TurnAction},
+    r#match::Rules,
+};
+use rand::{seq::SliceRandom, Rng};
+
+/// How often a player passes even though they could play.
+const PASS_CHANCE: f64 = 0.2;
+
+/// Generate a random position with at most `cards_left` cards left across every hand.
+///
+/// The position is reached by dealing a game with `rules` and taking random legal turns,
+/// so it could come up in a real game. Deals that end before reaching `cards_left` are
+/// thrown away and dealt again.
+///
+/// `cards_left` must be at least the number of players, since no one has emptied
+/// their hand in a game that's still going.
+///
+/// Every deal and turn is drawn from `rng`, so an rng seeded the same way generates the
+/// same position.
+pub fn random_position<const PLAYERS: usize>(
+    rules: &Rules,
+    cards_left: usize,
+    rng: &mut impl Rng,
+) -> anyhow::Result<ChoDaiDi<PLAYERS>> {
+    if cards_left < PLAYERS {
+        anyhow::bail!("every player must have at least one card left in a game that's going");
+    }
+
+    loop {
+        let mut game = ChoDaiDi::new_game_with_seed(rng.gen());
+        rules.apply(&mut game);
+
+        while !game.is_game_ended() {
+            if total_cards_left(&game) <= cards_left {
+                return Ok(game);
+            }
+            take_random_turn(&mut game, rng)?;
+        }
+    }
+}
+
+fn total_cards_left<const PLAYERS: usize>(game: &ChoDaiDi<PLAYERS>) -> usize {
+    game.hands().iter().map(|hand| hand.len()).sum()
+}
+
+fn take_random_turn<const PLAYERS: usize>(
+    game: &mut ChoDaiDi<PLAYERS>,
+    rng: &mut impl Rng,
+) -> anyhow::Result<()> {
+    let hand = game.get_current_players_hand();
+    let possible_plays = game.possible_plays(&hand);
+    // Whoever leads has to play something
+    let passes = game.last_play().is_some() && rng.gen_bool(PASS_CHANCE);
+
+    let action = match possible_plays.choose(rng) {
+        Some(play) if !passes => TurnAction::Play(play.clone()),
+        _ => TurnAction::Pass,
+    };
+    game.advance(action)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::random_position;
+    use crate::{
+        cho_dai_di::{ChoDaiDi, FOUR_PLAYERS},
+        r#match::Rules,
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    /// Keep the tests quick by not generating every five-card play
+    fn rules() -> Rules {
+        Rules {
+            max_candidate_plays: Some(500),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_random_position() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let rules = rules();
+        let game: ChoDaiDi<FOUR_PLAYERS> = random_position(&rules, 46, &mut rng).unwrap();
+        let cards_left: usize = game.hands().iter().map(|hand| hand.len()).sum();
+        assert!(cards_left <= 46);
+        assert!(!game.is_game_ended());
+
+        assert!(random_position::<FOUR_PLAYERS>(&rules, 3, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_same_seed_same_position() {
+        let position = |seed| {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            random_position::<FOUR_PLAYERS>(&rules(), 40, &mut rng).unwrap()
+        };
+
+        let a = position(3);
+        let b = position(3);
+        assert_eq!(a.hands(), b.hands());
+        assert_eq!(a.last_play(), b.last_play());
+        assert_eq!(a.whose_turn(), b.whose_turn());
+        assert_eq!(a.history().len(), b.history().len());
+
+        assert_ne!(a.hands(), position(4).hands());
+    }
+}
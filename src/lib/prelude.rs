@@ -0,0 +1,26 @@
+//! The commonly used types for building a front-end for one of the games in this crate.
+//!
+//! ```
+//! use card_games::prelude::*;
+//!
+//! let game = new_4p_game();
+//! assert_eq!(game.number_of_players(), FOUR_PLAYERS);
+//! ```
+
+pub use crate::{
+    card::{Card, STANDARD_DECK},
+    cho_dai_di::{
+        new_4p_game, Bonus, CardOrdering, ChoDaiDi, Combination, FiveCardEvaluation, GameEvent,
+        GameView, PlayError, ScoreModifier, ScoringRules, Slam, SuitPrecedence, TurnAction,
+        TurnOutcome, TurnRecord, FOUR_PLAYERS,
+    },
+    collections::{CardSet, Cards, SortCardsBy},
+    palette::Palette,
+    player::{ai, human, new_ai_player, new_ai_players, new_human_player},
+    r#match::{Match, Rules, Termination},
+    rank::Rank,
+    render::CardStyle,
+    score_format::ScoreFormatter,
+    suit::Suit,
+    Deck,
+};
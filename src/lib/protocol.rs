@@ -0,0 +1,645 @@
+//! The line protocol used to play a game over the network.
+//!
+//! Each message is a single line starting with a keyword. Cards are written the way
+//! they're displayed and separated by spaces, e.g. `played 2 9♣ 9♦`.
+//!
+//! A client connects and sends `join <name>`. The server answers with `welcome`, then
+//! for each game sends the names at the table, the rules, the client's dealt hand, and
+//! every play and pass as it happens, along with any card `revealed` or penalty `marker`
+//! drawn under the pass card penalty. When it's the client's turn the server sends its
+//! current `hand` followed by `turn`, and the client answers with `play` or `pass`.
+//!
+//! The same messages can be sent as JSON instead, e.g. for browser clients. Each is an
+//! object with a `type` and, for messages that carry anything, its `data`:
+//! `{"type": "play", "data": ["9♣", "9♦"]}`. Servers that support reconnecting send a
+//! `session` token after `welcome`, which a client sends back with `resume` to take
+//! its seat again.
+//!
+//! A server with a lobby answers `join` with the `tables` that are open instead, each
+//! described by a `table` message. A client may `create` a table or `sit` at one, and
+//! is sent `welcome` once the table's game starts. Whoever's been at a table longest
+//! is its `host`, and may `start` the game early with AI players in the empty seats.
+//! Before it starts, anyone at the table may `propose` a rule by its [stable
+//! key](crate::r#match::keys), e.g. `propose revolutions=true`, and everyone at the table
+//! is sent the `proposal` and may `vote` on it. Once the host sends `confirm`, the
+//! proposals with more votes for than against are `agreed`, and the table's games are
+//! played by them.
+//!
+//! A server hosting a tournament across several tables sends everyone who played a
+//! `standing` for each of them, from first place to last, once every table's finished.
+//!
+//! A client that sends `watch <name>` instead of `join` is a spectator. It's sent
+//! `watching`, then everything a player would be sent except hands and turns. It's also
+//! sent `hand-sizes` when a game's dealt and just before each play, with the number of
+//! cards everyone holds once the play's made.
+
+use crate::{
+    cho_dai_di::{ChoDaiDi, GameEvent, FOUR_PLAYERS},
+    collections::Cards,
+};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// A message sent from the server to a client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "kebab-case")]
+pub enum ServerMessage<const PLAYERS: usize = FOUR_PLAYERS> {
+    /// The client has joined and will play `seat`
+    Welcome { seat: usize, players: usize },
+    /// A token the client can send with [`ClientMessage::Resume`] to reconnect
+    Session { token: String },
+    /// The name of the player at `seat`
+    Seat { seat: usize, name: String },
+    /// The rules of the game that's about to be dealt, by their [stable
+    /// keys](crate::r#match::keys), and the server's version and [fingerprint of the
+    /// rules](crate::r#match::Rules::fingerprint), for the client to check that it plays
+    /// by the same ones
+    Rules {
+        version: String,
+        fingerprint: String,
+        rules: BTreeMap<String, String>,
+    },
+    /// The cards dealt to the client
+    Deal(Cards<ChoDaiDi<PLAYERS>>),
+    /// The client's current hand, sent before each of their turns
+    Hand(Cards<ChoDaiDi<PLAYERS>>),
+    /// It's the client's turn. Holds the play to beat, if any.
+    Turn(Option<Cards<ChoDaiDi<PLAYERS>>>),
+    /// Something happened that everyone at the table can see
+    Event(GameEvent<PLAYERS>),
+    /// The client's last message was refused
+    Error(String),
+    /// The number of open tables in the lobby, each of which is described by a
+    /// [`ServerMessage::Table`] sent after this
+    Tables { count: usize },
+    /// A table in the lobby that's waiting for players
+    Table {
+        id: usize,
+        name: String,
+        seated: usize,
+        seats: usize,
+    },
+    /// The client is sitting at a table, waiting for its game to start
+    Seated { table: usize },
+    /// The client is now the host of the table it's sitting at
+    Host { table: usize },
+    /// The client is watching a game with this many players
+    Watching { players: usize },
+    /// How many cards each player holds, by seat
+    HandSizes(Vec<usize>),
+    /// A rule proposed at the client's table, with the votes cast on it so far. The
+    /// proposer votes for it.
+    Proposal {
+        id: usize,
+        name: String,
+        key: String,
+        value: String,
+        yes: usize,
+        no: usize,
+    },
+    /// The host confirmed the rules that the table's games are played by, by their
+    /// stable keys
+    Agreed(BTreeMap<String, String>),
+    /// Where a player finished in a tournament, counting from 1, with their total score
+    Standing {
+        place: usize,
+        name: String,
+        score: isize,
+    },
+}
+
+/// A message sent from a client to the server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "kebab-case")]
+pub enum ClientMessage<const PLAYERS: usize = FOUR_PLAYERS> {
+    /// Ask to join the game
+    Join {
+        name: String,
+    },
+    /// Take back a seat after losing the connection
+    Resume {
+        token: String,
+    },
+    /// Ask to watch the game without playing
+    Watch {
+        name: String,
+    },
+    Play(Cards<ChoDaiDi<PLAYERS>>),
+    Pass,
+    /// Ask for the open tables in the lobby
+    List,
+    /// Open a new table in the lobby and sit at it
+    Create {
+        name: String,
+    },
+    /// Sit at a table in the lobby
+    Sit {
+        table: usize,
+    },
+    /// Get up from a table before its game starts
+    Leave,
+    /// Start the game at the client's table, filling empty seats with AI players. Only
+    /// the host may do this.
+    Start,
+    /// Propose playing the rule `key` with `value` at the client's table
+    Propose {
+        key: String,
+        value: String,
+    },
+    /// Vote for or against a proposal at the client's table
+    Vote {
+        proposal: usize,
+        agree: bool,
+    },
+    /// Agree the rules that have more votes for than against. Only the host may do this.
+    Confirm,
+}
+
+impl<const PLAYERS: usize> fmt::Display for ServerMessage<PLAYERS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerMessage::Welcome { seat, players } => write!(f, "welcome {seat} {players}"),
+            ServerMessage::Session { token } => write!(f, "session {token}"),
+            ServerMessage::Seat { seat, name } => write!(f, "seat {seat} {name}"),
+            ServerMessage::Rules {
+                version,
+                fingerprint,
+                rules,
+            } => {
+                write!(f, "rules {version} {fingerprint}")?;
+                rules
+                    .iter()
+                    .try_for_each(|(key, value)| write!(f, " {key}={value}"))
+            }
+            ServerMessage::Deal(cards) => write!(f, "deal {}", card_list(cards)),
+            ServerMessage::Hand(cards) => write!(f, "hand {}", card_list(cards)),
+            ServerMessage::Turn(None) => write!(f, "turn"),
+            ServerMessage::Turn(Some(last_play)) => write!(f, "turn {}", card_list(last_play)),
+            ServerMessage::Event(GameEvent::CardsPlayed { player, cards }) => {
+                write!(f, "played {player} {}", card_list(cards))
+            }
+            ServerMessage::Event(GameEvent::PlayerPassed { player }) => {
+                write!(f, "passed {player}")
+            }
+            ServerMessage::Event(GameEvent::CardRevealed { player, card }) => {
+                write!(f, "revealed {player} {card}")
+            }
+            ServerMessage::Event(GameEvent::PenaltyMarkerDrawn { player, markers }) => {
+                write!(f, "marker {player} {markers}")
+            }
+            ServerMessage::Event(GameEvent::RoundEnded { leader }) => {
+                write!(f, "round-ended {leader}")
+            }
+            ServerMessage::Event(GameEvent::GameEnded { winner }) => {
+                write!(f, "game-ended {winner}")
+            }
+            ServerMessage::Error(message) => write!(f, "error {message}"),
+            ServerMessage::Tables { count } => write!(f, "tables {count}"),
+            ServerMessage::Table {
+                id,
+                name,
+                seated,
+                seats,
+            } => write!(f, "table {id} {seated} {seats} {name}"),
+            ServerMessage::Seated { table } => write!(f, "seated {table}"),
+            ServerMessage::Host { table } => write!(f, "host {table}"),
+            ServerMessage::Watching { players } => write!(f, "watching {players}"),
+            ServerMessage::HandSizes(sizes) => {
+                let sizes: Vec<_> = sizes.iter().map(ToString::to_string).collect();
+                write!(f, "hand-sizes {}", sizes.join(" "))
+            }
+            ServerMessage::Proposal {
+                id,
+                name,
+                key,
+                value,
+                yes,
+                no,
+            } => write!(f, "proposal {id} {yes} {no} {key}={value} {name}"),
+            ServerMessage::Agreed(rules) => {
+                write!(f, "agreed")?;
+                rules
+                    .iter()
+                    .try_for_each(|(key, value)| write!(f, " {key}={value}"))
+            }
+            ServerMessage::Standing { place, name, score } => {
+                write!(f, "standing {place} {score} {name}")
+            }
+        }
+    }
+}
+
+impl<const PLAYERS: usize> FromStr for ServerMessage<PLAYERS> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (keyword, rest) = split_keyword(s);
+        Ok(match keyword {
+            "welcome" => {
+                let (seat, players) = rest
+                    .split_once(' ')
+                    .context("usage: 'welcome <seat> <players>'")?;
+                ServerMessage::Welcome {
+                    seat: seat.parse()?,
+                    players: players.parse()?,
+                }
+            }
+            "session" if !rest.is_empty() => ServerMessage::Session {
+                token: rest.to_owned(),
+            },
+            "seat" => {
+                let (seat, name) = rest
+                    .split_once(' ')
+                    .context("usage: 'seat <seat> <name>'")?;
+                ServerMessage::Seat {
+                    seat: seat.parse()?,
+                    name: name.to_owned(),
+                }
+            }
+            "rules" => {
+                let usage = "usage: 'rules <version> <fingerprint> <rule>=<value>...'";
+                let mut words = rest.split_whitespace();
+                let version = words.next().context(usage)?.to_owned();
+                let fingerprint = words.next().context(usage)?.to_owned();
+                let rules = words
+                    .map(|rule| rule_setting(rule).context(usage))
+                    .collect::<anyhow::Result<_>>()?;
+                ServerMessage::Rules {
+                    version,
+                    fingerprint,
+                    rules,
+                }
+            }
+            "deal" => ServerMessage::Deal(rest.parse()?),
+            "hand" => ServerMessage::Hand(rest.parse()?),
+            "turn" if rest.is_empty() => ServerMessage::Turn(None),
+            "turn" => ServerMessage::Turn(Some(rest.parse()?)),
+            "played" => {
+                let (player, cards) = rest
+                    .split_once(' ')
+                    .context("usage: 'played <seat> <cards>'")?;
+                ServerMessage::Event(GameEvent::CardsPlayed {
+                    player: player.parse()?,
+                    cards: cards.parse()?,
+                })
+            }
+            "passed" => ServerMessage::Event(GameEvent::PlayerPassed {
+                player: rest.parse()?,
+            }),
+            "revealed" => {
+                let (player, card) = rest
+                    .split_once(' ')
+                    .context("usage: 'revealed <seat> <card>'")?;
+                ServerMessage::Event(GameEvent::CardRevealed {
+                    player: player.parse()?,
+                    card: card.parse()?,
+                })
+            }
+            "marker" => {
+                let (player, markers) = rest
+                    .split_once(' ')
+                    .context("usage: 'marker <seat> <markers>'")?;
+                ServerMessage::Event(GameEvent::PenaltyMarkerDrawn {
+                    player: player.parse()?,
+                    markers: markers.parse()?,
+                })
+            }
+            "round-ended" => ServerMessage::Event(GameEvent::RoundEnded {
+                leader: rest.parse()?,
+            }),
+            "game-ended" => ServerMessage::Event(GameEvent::GameEnded {
+                winner: rest.parse()?,
+            }),
+            "error" => ServerMessage::Error(rest.to_owned()),
+            "tables" => ServerMessage::Tables {
+                count: rest.parse()?,
+            },
+            "table" => {
+                let mut fields = rest.splitn(4, ' ');
+                let mut next = || {
+                    fields
+                        .next()
+                        .context("usage: 'table <id> <seated> <seats> <name>'")
+                };
+                ServerMessage::Table {
+                    id: next()?.parse()?,
+                    seated: next()?.parse()?,
+                    seats: next()?.parse()?,
+                    name: next()?.to_owned(),
+                }
+            }
+            "seated" => ServerMessage::Seated {
+                table: rest.parse()?,
+            },
+            "host" => ServerMessage::Host {
+                table: rest.parse()?,
+            },
+            "watching" => ServerMessage::Watching {
+                players: rest.parse()?,
+            },
+            "hand-sizes" => ServerMessage::HandSizes(
+                rest.split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()?,
+            ),
+            "proposal" => {
+                let usage = "usage: 'proposal <id> <yes> <no> <rule>=<value> <name>'";
+                let mut fields = rest.splitn(5, ' ');
+                let mut next = || fields.next().context(usage);
+                let (id, yes, no) = (next()?.parse()?, next()?.parse()?, next()?.parse()?);
+                let (key, value) = rule_setting(next()?).context(usage)?;
+                ServerMessage::Proposal {
+                    id,
+                    name: next()?.to_owned(),
+                    key,
+                    value,
+                    yes,
+                    no,
+                }
+            }
+            "agreed" => ServerMessage::Agreed(
+                rest.split_whitespace()
+                    .map(|rule| rule_setting(rule).context("usage: 'agreed <rule>=<value>...'"))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            "standing" => {
+                let mut fields = rest.splitn(3, ' ');
+                let mut next = || {
+                    fields
+                        .next()
+                        .context("usage: 'standing <place> <score> <name>'")
+                };
+                ServerMessage::Standing {
+                    place: next()?.parse()?,
+                    score: next()?.parse()?,
+                    name: next()?.to_owned(),
+                }
+            }
+            _ => anyhow::bail!("unknown message '{s}'"),
+        })
+    }
+}
+
+impl<const PLAYERS: usize> fmt::Display for ClientMessage<PLAYERS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientMessage::Join { name } => write!(f, "join {name}"),
+            ClientMessage::Resume { token } => write!(f, "resume {token}"),
+            ClientMessage::Watch { name } => write!(f, "watch {name}"),
+            ClientMessage::Play(cards) => write!(f, "play {}", card_list(cards)),
+            ClientMessage::Pass => write!(f, "pass"),
+            ClientMessage::List => write!(f, "list"),
+            ClientMessage::Create { name } => write!(f, "create {name}"),
+            ClientMessage::Sit { table } => write!(f, "sit {table}"),
+            ClientMessage::Leave => write!(f, "leave"),
+            ClientMessage::Start => write!(f, "start"),
+            ClientMessage::Propose { key, value } => write!(f, "propose {key}={value}"),
+            ClientMessage::Vote { proposal, agree } => {
+                let vote = if *agree { "yes" } else { "no" };
+                write!(f, "vote {proposal} {vote}")
+            }
+            ClientMessage::Confirm => write!(f, "confirm"),
+        }
+    }
+}
+
+impl<const PLAYERS: usize> FromStr for ClientMessage<PLAYERS> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (keyword, rest) = split_keyword(s);
+        Ok(match keyword {
+            "join" if !rest.is_empty() => ClientMessage::Join {
+                name: rest.to_owned(),
+            },
+            "join" => anyhow::bail!("usage: 'join <name>'"),
+            "resume" if !rest.is_empty() => ClientMessage::Resume {
+                token: rest.to_owned(),
+            },
+            "resume" => anyhow::bail!("usage: 'resume <token>'"),
+            "watch" if !rest.is_empty() => ClientMessage::Watch {
+                name: rest.to_owned(),
+            },
+            "watch" => anyhow::bail!("usage: 'watch <name>'"),
+            "play" if !rest.is_empty() => ClientMessage::Play(rest.parse()?),
+            "play" => anyhow::bail!("usage: 'play <cards>'"),
+            "pass" => ClientMessage::Pass,
+            "list" => ClientMessage::List,
+            "create" if !rest.is_empty() => ClientMessage::Create {
+                name: rest.to_owned(),
+            },
+            "create" => anyhow::bail!("usage: 'create <table name>'"),
+            "sit" => ClientMessage::Sit {
+                table: rest.parse().context("usage: 'sit <table>'")?,
+            },
+            "leave" => ClientMessage::Leave,
+            "start" => ClientMessage::Start,
+            "propose" => {
+                let (key, value) = rule_setting(rest).context("usage: 'propose <rule>=<value>'")?;
+                ClientMessage::Propose { key, value }
+            }
+            "vote" => {
+                let usage = "usage: 'vote <proposal> yes' or 'vote <proposal> no'";
+                let (proposal, vote) = rest.split_once(' ').context(usage)?;
+                ClientMessage::Vote {
+                    proposal: proposal.parse().context(usage)?,
+                    agree: match vote.trim() {
+                        "yes" | "y" => true,
+                        "no" | "n" => false,
+                        _ => anyhow::bail!(usage),
+                    },
+                }
+            }
+            "confirm" => ClientMessage::Confirm,
+            _ => anyhow::bail!("unknown message '{s}'"),
+        })
+    }
+}
+
+fn split_keyword(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    match s.split_once(' ') {
+        Some((keyword, rest)) => (keyword, rest.trim()),
+        None => (s, ""),
+    }
+}
+
+/// Read a rule written as `<key>=<value>`.
+fn rule_setting(s: &str) -> Option<(String, String)> {
+    let (key, value) = s.split_once('=')?;
+    let valid = |it: &str| !it.is_empty() && !it.contains(char::is_whitespace);
+    (valid(key) && valid(value)).then(|| (key.to_owned(), value.to_owned()))
+}
+
+fn card_list<G>(cards: &Cards<G>) -> String {
+    cards
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card;
+
+    #[test]
+    fn test_server_message_round_trip() {
+        let cards = Cards::try_from(vec!["9C", "9D"]).unwrap();
+        let messages: [ServerMessage; 21] = [
+            ServerMessage::Session {
+                token: "0123abcd".to_owned(),
+            },
+            ServerMessage::Welcome {
+                seat: 1,
+                players: 4,
+            },
+            ServerMessage::Seat {
+                seat: 2,
+                name: "Ada Lovelace".to_owned(),
+            },
+            ServerMessage::Rules {
+                version: "0.2.0".to_owned(),
+                fingerprint: "cbf29ce484222325".to_owned(),
+                rules: BTreeMap::from([("suits".to_owned(), "vietnamese".to_owned())]),
+            },
+            ServerMessage::Deal(cards.clone()),
+            ServerMessage::Turn(None),
+            ServerMessage::Turn(Some(cards.clone())),
+            ServerMessage::Event(GameEvent::CardsPlayed { player: 3, cards }),
+            ServerMessage::Event(GameEvent::PlayerPassed { player: 0 }),
+            ServerMessage::Event(GameEvent::CardRevealed {
+                player: 0,
+                card: Card::THREE_OF_DIAMONDS,
+            }),
+            ServerMessage::Event(GameEvent::PenaltyMarkerDrawn {
+                player: 1,
+                markers: 2,
+            }),
+            ServerMessage::Event(GameEvent::GameEnded { winner: 2 }),
+            ServerMessage::Error("that's not your turn".to_owned()),
+            ServerMessage::Tables { count: 1 },
+            ServerMessage::Table {
+                id: 3,
+                name: "Friday night".to_owned(),
+                seated: 2,
+                seats: 4,
+            },
+            ServerMessage::Host { table: 3 },
+            ServerMessage::Watching { players: 4 },
+            ServerMessage::HandSizes(vec![13, 9, 0, 11]),
+            ServerMessage::Proposal {
+                id: 2,
+                name: "Ada Lovelace".to_owned(),
+                key: "revolutions".to_owned(),
+                value: "true".to_owned(),
+                yes: 3,
+                no: 1,
+            },
+            ServerMessage::Agreed(BTreeMap::from([
+                ("revolutions".to_owned(), "true".to_owned()),
+                ("suits".to_owned(), "standard".to_owned()),
+            ])),
+            ServerMessage::Standing {
+                place: 2,
+                name: "Grace Hopper".to_owned(),
+                score: -17,
+            },
+        ];
+        for message in messages {
+            assert_eq!(
+                message.to_string().parse::<ServerMessage>().unwrap(),
+                message
+            );
+        }
+        assert_eq!(
+            ServerMessage::<4>::Turn(Some(Cards::try_from(vec!["9C", "9D"]).unwrap())).to_string(),
+            "turn 9♣ 9♦"
+        );
+    }
+
+    #[test]
+    fn test_client_message() {
+        assert_eq!(
+            "play 3d 5c".parse::<ClientMessage>().unwrap(),
+            ClientMessage::Play(Cards::try_from(vec!["3D", "5C"]).unwrap())
+        );
+        assert_eq!(
+            "pass".parse::<ClientMessage>().unwrap(),
+            ClientMessage::Pass
+        );
+        assert_eq!(
+            ClientMessage::<4>::Join {
+                name: "Ada".to_owned()
+            }
+            .to_string(),
+            "join Ada"
+        );
+        assert_eq!(
+            "create Friday night".parse::<ClientMessage>().unwrap(),
+            ClientMessage::Create {
+                name: "Friday night".to_owned()
+            }
+        );
+        assert_eq!(
+            "sit 3".parse::<ClientMessage>().unwrap(),
+            ClientMessage::Sit { table: 3 }
+        );
+        assert_eq!(
+            "watch Grace".parse::<ClientMessage>().unwrap(),
+            ClientMessage::Watch {
+                name: "Grace".to_owned()
+            }
+        );
+        assert_eq!(
+            "propose revolutions=true".parse::<ClientMessage>().unwrap(),
+            ClientMessage::Propose {
+                key: "revolutions".to_owned(),
+                value: "true".to_owned()
+            }
+        );
+        let vote = ClientMessage::<4>::Vote {
+            proposal: 2,
+            agree: false,
+        };
+        assert_eq!(vote.to_string(), "vote 2 no");
+        assert_eq!(vote.to_string().parse::<ClientMessage>().unwrap(), vote);
+        assert!("propose revolutions".parse::<ClientMessage>().is_err());
+        assert!("propose =true".parse::<ClientMessage>().is_err());
+        assert!("vote 2 maybe".parse::<ClientMessage>().is_err());
+        assert!("sit".parse::<ClientMessage>().is_err());
+        assert!("play".parse::<ClientMessage>().is_err());
+        assert!("join".parse::<ClientMessage>().is_err());
+        assert!("shuffle".parse::<ClientMessage>().is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"type":"play","data":["9♣","9d"]}"#).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Play(Cards::try_from(vec!["9C", "9D"]).unwrap())
+        );
+        let message: ClientMessage = serde_json::from_str(r#"{"type":"pass"}"#).unwrap();
+        assert_eq!(message, ClientMessage::Pass);
+
+        let message: ServerMessage = ServerMessage::Event(GameEvent::CardsPlayed {
+            player: 1,
+            cards: Cards::try_from(vec!["3D"]).unwrap(),
+        });
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            r#"{"type":"event","data":{"type":"cards-played","player":1,"cards":["3♦"]}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ServerMessage::<4>::Session {
+                token: "abc".to_owned()
+            })
+            .unwrap(),
+            r#"{"type":"session","data":{"token":"abc"}}"#
+        );
+    }
+}
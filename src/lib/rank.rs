@@ -39,6 +39,34 @@ impl fmt::Display for Rank {
     }
 }
 
+/// The names that each rank may be written as in other languages, for looking a rank
+/// up by its localized name.
+const LOCALIZED_NAMES: [(Rank, &[&str]); 13] = [
+    (Rank::Two, &["二", "兩", "两", "貳", "贰"]),
+    (Rank::Three, &["三"]),
+    (Rank::Four, &["四"]),
+    (Rank::Five, &["五"]),
+    (Rank::Six, &["六"]),
+    (Rank::Seven, &["七"]),
+    (Rank::Eight, &["八"]),
+    (Rank::Nine, &["九"]),
+    (Rank::Ten, &["十"]),
+    (Rank::Jack, &["勾", "鉤", "钩"]),
+    (Rank::Queen, &["圈", "皇后"]),
+    (Rank::King, &["老K", "國王", "国王"]),
+    (Rank::Ace, &["尖", "一"]),
+];
+
+impl Rank {
+    /// Look up a rank by one of its localized names, like "十".
+    pub fn from_localized_name(name: &str) -> Option<Rank> {
+        LOCALIZED_NAMES
+            .iter()
+            .find(|(_, names)| names.contains(&name))
+            .map(|(rank, _)| *rank)
+    }
+}
+
 impl FromStr for Rank {
     type Err = anyhow::Error;
 
@@ -70,7 +98,25 @@ impl FromStr for Rank {
         } else if s == "1" || s.eq_ignore_ascii_case("a") || s.eq_ignore_ascii_case("ace") {
             Ok(Rank::Ace)
         } else {
-            Err(anyhow::anyhow!("`{s}` is not a rank"))
+            Rank::from_localized_name(s).ok_or_else(|| anyhow::anyhow!("`{s}` is not a rank"))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Rank;
+
+    #[test]
+    fn test_from_localized_name() {
+        assert_eq!(Rank::from_localized_name("二"), Some(Rank::Two));
+        assert_eq!(Rank::from_localized_name("两"), Some(Rank::Two));
+        assert_eq!(Rank::from_localized_name("十"), Some(Rank::Ten));
+        assert_eq!(Rank::from_localized_name("钩"), Some(Rank::Jack));
+        assert_eq!(Rank::from_localized_name("尖"), Some(Rank::Ace));
+        assert_eq!(Rank::from_localized_name("ace"), None);
+
+        assert_eq!("九".parse::<Rank>().unwrap(), Rank::Nine);
+        assert!("十一".parse::<Rank>().is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! Displaying cards in a terminal.
+
+use crate::{card::Card, collections::Cards, palette::Palette};
+
+/// Options for displaying cards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CardStyle {
+    /// If true, cards are colored by suit using the palette
+    pub color: bool,
+    pub palette: Palette,
+}
+
+impl Default for CardStyle {
+    fn default() -> Self {
+        CardStyle {
+            color: false,
+            palette: Palette::Standard,
+        }
+    }
+}
+
+impl CardStyle {
+    /// Format a card, e.g. `10♥`.
+    pub fn card(&self, card: &Card) -> String {
+        let escape = self.color.then(|| self.palette.suit(card.suit())).flatten();
+        match escape {
+            Some(escape) => format!("{escape}{card}{}", Palette::RESET),
+            None => card.to_string(),
+        }
+    }
+
+    /// Format several cards the same way as [`Cards`]' `Display`, e.g. `9♣, 9♦`.
+    pub fn cards<G>(&self, cards: &Cards<G>) -> String {
+        cards
+            .iter()
+            .map(|card| self.card(card))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain() {
+        let cards: Cards<()> = Cards::try_from(vec!["9C", "9D"]).unwrap();
+        assert_eq!(CardStyle::default().cards(&cards), cards.to_string());
+    }
+
+    #[test]
+    fn test_color() {
+        let style = CardStyle {
+            color: true,
+            ..Default::default()
+        };
+        let cards: Cards<()> = Cards::try_from(vec!["9C", "9D"]).unwrap();
+        assert_eq!(style.cards(&cards), "9♣, \x1b[31m9♦\x1b[0m");
+    }
+}
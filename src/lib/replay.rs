@@ -0,0 +1,350 @@
+//! Recording games so that they can be played back turn by turn.
+//!
+//! A replay is saved as a short text file:
+//!
+//! ```txt
+//! dai-di replay 2
+//! seed 1234
+//! first 2
+//! version 0.2.0
+//! rules 8a96551c38291478
+//! rule flush-beats-full-house
+//! rated
+//! suits vietnamese
+//! seat Alice
+//! seat ChoBot
+//! seat AImy
+//! seat Hirayama
+//! 3♦, 4♦
+//! pass
+//! ```
+//!
+//! The `first` line is only there when someone other than the holder of the 3♦ was
+//! chosen to go first. The `version` of the game that recorded the replay and the
+//! [fingerprint](Rules::fingerprint) of its `rules` are checked when it's read back, so
+//! that it isn't played back under different rules. The `rated` line is there when the
+//! game was rated, the `suits` line when suits were in another order, and a `rule` line
+//! for each other rule that wasn't left at its default, by its [stable
+//! key](crate::r#match::keys), with its value unless it's turned on. After the seats,
+//! each line is one turn: the cards played, or `pass`.
+//!
+//! A replay can also be shared as a code, which is the file compressed and encoded as
+//! URL-safe base64, so that it can be pasted in a chat.
+
+use crate::{
+    cho_dai_di::{ChoDaiDi, TurnAction, FOUR_PLAYERS},
+    collections::Cards,
+    r#match::{
+        keys::{RATED, SUITS},
+        Rules,
+    },
+};
+use anyhow::{bail, Context};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+const HEADER: &str = "dai-di replay 2";
+/// The header of replays whose seeds were dealt with a random number generator that
+/// could deal differently from one platform or version to the next
+const OLD_HEADER: &str = "dai-di replay 1";
+/// The most bytes that a replay code may decompress to, so that a code can't be crafted
+/// to use up all of the memory
+const MAX_DECODED_LEN: usize = 1 << 20;
+
+/// Everything needed to play a game again: how it was dealt and every turn taken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay<const PLAYERS: usize = FOUR_PLAYERS> {
+    /// The seed the game was dealt with, see [`ChoDaiDi::new_game_with_seed`]
+    pub seed: u64,
+    /// The player chosen to go first with [`ChoDaiDi::set_first_player`], if any
+    pub first_player: Option<usize>,
+    /// The variant rules the game was played by
+    pub rules: Rules,
+    /// The name of the player in each seat
+    pub names: Vec<String>,
+    pub actions: Vec<TurnAction<PLAYERS>>,
+}
+
+impl<const PLAYERS: usize> Replay<PLAYERS> {
+    /// Record the turns taken so far in a game dealt with `seed`.
+    pub fn record(
+        game: &ChoDaiDi<PLAYERS>,
+        seed: u64,
+        first_player: Option<usize>,
+        names: Vec<String>,
+    ) -> Self {
+        Replay {
+            seed,
+            first_player,
+            rules: game.rules(),
+            names,
+            actions: game
+                .history()
+                .iter()
+                .map(|turn| turn.action.clone())
+                .collect(),
+        }
+    }
+
+    /// Deal the recorded game again, before any turns are taken.
+    pub fn new_game(&self) -> anyhow::Result<ChoDaiDi<PLAYERS>> {
+        let mut game = ChoDaiDi::new_game_with_seed(self.seed);
+        self.rules.apply(&mut game);
+        if let Some(player) = self.first_player {
+            game.set_first_player(player)?;
+        }
+
+        Ok(game)
+    }
+}
+
+impl<const PLAYERS: usize> Replay<PLAYERS> {
+    /// Encode this replay as a code that's short enough to paste in a chat.
+    pub fn to_base64(&self) -> String {
+        let compressed = miniz_oxide::deflate::compress_to_vec(self.to_string().as_bytes(), 10);
+        URL_SAFE_NO_PAD.encode(compressed)
+    }
+
+    /// Decode a replay from a code made with [`Replay::to_base64`].
+    pub fn from_base64(code: &str) -> anyhow::Result<Self> {
+        decode(code)?.parse()
+    }
+}
+
+/// Decode a code made with [`Replay::to_base64`] back into the text of the replay, for
+/// when the number of players isn't known yet.
+pub fn decode(code: &str) -> anyhow::Result<String> {
+    let compressed = URL_SAFE_NO_PAD
+        .decode(code.trim())
+        .context("the replay code isn't valid base64")?;
+    let text = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_DECODED_LEN)
+        .map_err(|e| anyhow::anyhow!("the replay code couldn't be decompressed: {e}"))?;
+
+    String::from_utf8(text).context("the replay code doesn't hold a replay")
+}
+
+/// Count the seats in a saved replay, so that it can be parsed as a
+/// [`Replay`] for that many players.
+pub fn number_of_players(s: &str) -> usize {
+    s.lines().filter(|line| line.starts_with("seat ")).count()
+}
+
+impl<const PLAYERS: usize> fmt::Display for Replay<PLAYERS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        writeln!(f, "seed {}", self.seed)?;
+        if let Some(player) = self.first_player {
+            writeln!(f, "first {player}")?;
+        }
+        writeln!(f, "version {}", crate::VERSION)?;
+        writeln!(f, "rules {}", self.rules.fingerprint())?;
+        for (key, value) in self.rules.changes() {
+            match (key.as_str(), value.as_str()) {
+                (RATED, _) => writeln!(f, "{RATED}")?,
+                (SUITS, _) => writeln!(f, "{SUITS} {value}")?,
+                (_, "true") => writeln!(f, "rule {key}")?,
+                _ => writeln!(f, "rule {key} {value}")?,
+            }
+        }
+        for name in &self.names {
+            writeln!(f, "seat {name}")?;
+        }
+        for action in &self.actions {
+            match action {
+                TurnAction::Play(cards) => writeln!(f, "{cards}")?,
+                TurnAction::Pass => writeln!(f, "pass")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<const PLAYERS: usize> FromStr for Replay<PLAYERS> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate().filter(|(_, line)| !line.is_empty());
+        match lines.next().map(|(_, line)| line) {
+            Some(HEADER) => {}
+            Some(OLD_HEADER) => {
+                bail!("this replay was saved by an older version of the game, which dealt its seed differently")
+            }
+            _ => bail!("this isn't a replay, or it was saved by a newer version of the game"),
+        }
+
+        let mut replay = Replay {
+            seed: 0,
+            first_player: None,
+            rules: Rules::default(),
+            names: Vec::new(),
+            actions: Vec::new(),
+        };
+        let mut seed = None;
+        let (mut version, mut fingerprint) = (None, None);
+        let mut rules = BTreeMap::new();
+        for (i, line) in lines {
+            let context = || format!("line {} of the replay", i + 1);
+            if let Some(value) = line.strip_prefix("seed ") {
+                seed = Some(value.parse().with_context(context)?);
+            } else if let Some(value) = line.strip_prefix("first ") {
+                replay.first_player = Some(value.parse().with_context(context)?);
+            } else if let Some(value) = line.strip_prefix("version ") {
+                version = Some(value.to_owned());
+            } else if let Some(value) = line.strip_prefix("rules ") {
+                fingerprint = Some(value.to_owned());
+            } else if line == RATED {
+                rules.insert(RATED.to_owned(), true.to_string());
+            } else if let Some(rule) = line.strip_prefix("rule ") {
+                // Rules that are on or off are only written when they're on
+                let (key, value) = rule.split_once(' ').unwrap_or((rule, "true"));
+                rules.insert(key.to_owned(), value.to_owned());
+            } else if let Some(value) = line.strip_prefix("suits ") {
+                rules.insert(SUITS.to_owned(), value.to_owned());
+            } else if let Some(name) = line.strip_prefix("seat ") {
+                replay.names.push(name.to_owned());
+            } else if line == "pass" {
+                replay.actions.push(TurnAction::Pass);
+            } else {
+                let cards: Cards<ChoDaiDi<PLAYERS>> = line.parse().with_context(context)?;
+                replay.actions.push(TurnAction::Play(cards));
+            }
+        }
+
+        replay.seed = seed.context("the replay has no seed")?;
+        let version = version.as_deref().unwrap_or("unknown");
+        replay.rules = Rules::from_map(&rules).with_context(|| {
+            format!("the replay was recorded by version {version} of the game, with rules this version doesn't know")
+        })?;
+        if let Some(fingerprint) = fingerprint {
+            replay.rules.check_fingerprint(&fingerprint, version)?;
+        }
+        if replay.names.len() != PLAYERS {
+            bail!(
+                "the replay has {} seats, but {PLAYERS} were expected",
+                replay.names.len()
+            );
+        }
+
+        Ok(replay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, number_of_players, Replay};
+    use crate::{
+        arbitrary::play_weakest,
+        cho_dai_di::{ChoDaiDi, SuitPrecedence, FOUR_PLAYERS},
+        r#match::Rules,
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let mut game = ChoDaiDi::<FOUR_PLAYERS>::new_game_with_seed(9);
+        game.set_max_candidate_plays(Some(100));
+        game.set_flush_beats_full_house(true);
+        game.set_suit_precedence(SuitPrecedence::DiamondsHigh);
+        game.set_must_play_lowest_single_first(true);
+        game.set_rated(true);
+        play_weakest(&mut game, 8);
+        let names = ["A", "B", "C", "D"].map(ToOwned::to_owned).to_vec();
+        let replay = Replay::record(&game, 9, None, names);
+        assert_eq!(replay.actions.len(), 8);
+        assert!(replay.rules.flush_beats_full_house);
+
+        let saved = replay.to_string();
+        assert_eq!(number_of_players(&saved), FOUR_PLAYERS);
+        let parsed = saved.parse::<Replay>().unwrap();
+        assert_eq!(
+            parsed.rules,
+            Rules::default()
+                .flush_beats_full_house(true)
+                .must_play_lowest_single_first(true)
+                .suit_precedence(SuitPrecedence::DiamondsHigh)
+                .max_candidate_plays(Some(100))
+                .rated(true)
+        );
+        assert!(
+            saved.contains("\nrule max-candidate-plays 100\n"),
+            "{saved}"
+        );
+        assert_eq!(parsed.actions, replay.actions);
+        assert!(saved.parse::<Replay<3>>().is_err());
+
+        // Playing the turns back reaches the same position
+        let mut replayed = replay.new_game().unwrap();
+        for action in replay.actions {
+            replayed.advance(action).unwrap();
+        }
+        assert_eq!(replayed.hands(), game.hands());
+        assert_eq!(replayed.whose_turn(), game.whose_turn());
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        let mut game = ChoDaiDi::<3>::new_game_with_seed(4);
+        play_weakest(&mut game, 30);
+        let names = ["Alice", "ChoBot", "Hirayama"]
+            .map(ToOwned::to_owned)
+            .to_vec();
+        let replay = Replay::record(&game, 4, None, names);
+
+        let code = replay.to_base64();
+        assert!(code.len() < replay.to_string().len(), "{code}");
+        assert!(
+            code.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "{code}"
+        );
+        assert_eq!(Replay::<3>::from_base64(&code).unwrap(), replay);
+        assert_eq!(number_of_players(&decode(&code).unwrap()), 3);
+
+        assert!(Replay::<3>::from_base64("not a code!").is_err());
+        assert!(Replay::<3>::from_base64("aGVsbG8").is_err());
+        assert!(Replay::<FOUR_PLAYERS>::from_base64(&code).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("seed 1".parse::<Replay>().is_err());
+        assert!("dai-di replay 1\nseed 1\nseat A\nseat B\nseat C\nseat D"
+            .parse::<Replay>()
+            .is_err());
+        assert!("dai-di replay 2\nseat A\nseat B\nseat C\nseat D"
+            .parse::<Replay>()
+            .is_err());
+        assert!(
+            "dai-di replay 2\nseed 1\nseat A\nseat B\nseat C\nseat D\n3x"
+                .parse::<Replay>()
+                .is_err()
+        );
+        assert!(
+            "dai-di replay 2\nseed 1\nrule jokers\nseat A\nseat B\nseat C\nseat D"
+                .parse::<Replay>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_rules_are_checked() {
+        let seats = "seat A\nseat B\nseat C\nseat D";
+        let fingerprint = Rules::default().revolutions_enabled(true).fingerprint();
+        let replay = format!(
+            "dai-di replay 2\nseed 1\nversion 0.1.0\nrules {fingerprint}\nrule revolutions\n{seats}"
+        );
+        assert!(replay.parse::<Replay>().unwrap().rules.revolutions_enabled);
+
+        // A replay whose rules are read differently from how they were recorded
+        let replay =
+            format!("dai-di replay 2\nseed 1\nversion 9.0.0\nrules {fingerprint}\n{seats}");
+        let e = replay.parse::<Replay>().unwrap_err();
+        assert!(format!("{e:#}").contains("version 9.0.0"), "{e:#}");
+
+        // A replay from a version with a rule this one doesn't have
+        let replay = format!("dai-di replay 2\nseed 1\nversion 9.0.0\nrule jokers\n{seats}");
+        let e = replay.parse::<Replay>().unwrap_err();
+        assert!(format!("{e:#}").contains("version 9.0.0"), "{e:#}");
+        assert!(format!("{e:#}").contains("jokers"), "{e:#}");
+    }
+}
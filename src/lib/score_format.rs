@@ -0,0 +1,121 @@
+use crate::palette::Palette;
+use std::fmt::Write;
+
+/// Options for displaying scores.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreFormatter {
+    /// The separator placed between groups of three digits, e.g. `,` for `1,000`
+    pub digit_separator: Option<char>,
+    /// If true, positive and negative scores are colored using the palette
+    pub color: bool,
+    pub palette: Palette,
+    /// A unit shown after the score, e.g. `chips`
+    pub unit: Option<String>,
+}
+
+/// Scores are colored by default, as they are for a new player's profile.
+impl Default for ScoreFormatter {
+    fn default() -> Self {
+        ScoreFormatter {
+            digit_separator: Some(','),
+            color: true,
+            palette: Palette::Standard,
+            unit: None,
+        }
+    }
+}
+
+impl ScoreFormatter {
+    /// Format a score, always including its sign.
+    pub fn format(&self, score: isize) -> String {
+        let sign = if score < 0 { '-' } else { '+' };
+        let digits = score.unsigned_abs().to_string();
+
+        let mut s = String::with_capacity(digits.len() + 16);
+        if self.color && score != 0 {
+            s.push_str(if score > 0 {
+                self.palette.positive()
+            } else {
+                self.palette.negative()
+            });
+        }
+        s.push(sign);
+        for (i, digit) in digits.chars().enumerate() {
+            let remaining = digits.len() - i;
+            if i != 0 && remaining % 3 == 0 {
+                if let Some(separator) = self.digit_separator {
+                    s.push(separator);
+                }
+            }
+            s.push(digit);
+        }
+        if let Some(unit) = &self.unit {
+            write!(s, " {unit}").expect("write to string will never fail");
+        }
+        if self.color && score != 0 {
+            s.push_str(Palette::RESET);
+        }
+
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plain() -> ScoreFormatter {
+        ScoreFormatter {
+            color: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_format_sign() {
+        let formatter = plain();
+        assert_eq!(formatter.format(0), "+0");
+        assert_eq!(formatter.format(7), "+7");
+        assert_eq!(formatter.format(-39), "-39");
+    }
+
+    #[test]
+    fn test_format_digit_grouping() {
+        let formatter = plain();
+        assert_eq!(formatter.format(100), "+100");
+        assert_eq!(formatter.format(1000), "+1,000");
+        assert_eq!(formatter.format(-1234567), "-1,234,567");
+
+        let formatter = ScoreFormatter {
+            digit_separator: Some('.'),
+            ..plain()
+        };
+        assert_eq!(formatter.format(-12345), "-12.345");
+
+        let formatter = ScoreFormatter {
+            digit_separator: None,
+            ..plain()
+        };
+        assert_eq!(formatter.format(12345), "+12345");
+    }
+
+    #[test]
+    fn test_format_color_and_unit() {
+        assert_eq!(ScoreFormatter::default().format(5), "\x1b[32m+5\x1b[0m");
+
+        let formatter = ScoreFormatter {
+            unit: Some("chips".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(formatter.format(5), "\x1b[32m+5 chips\x1b[0m");
+        assert_eq!(formatter.format(-5), "\x1b[31m-5 chips\x1b[0m");
+        assert_eq!(formatter.format(0), "+0 chips");
+
+        let formatter = ScoreFormatter {
+            palette: Palette::ColorblindSafe,
+            ..Default::default()
+        };
+        assert_eq!(formatter.format(5), "\x1b[38;5;33m+5\x1b[0m");
+        assert_eq!(formatter.format(-5), "\x1b[38;5;208m-5\x1b[0m");
+    }
+}
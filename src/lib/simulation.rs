@@ -0,0 +1,581 @@
+//! Playing games between computer players without any input or output, to compare how
+//! well their strategies do. Games are spread across every available core.
+//!
+//! ```
+//! use card_games::{player::ai::Strategy, r#match::Rules, simulation};
+//!
+//! let summary = simulation::simulate([Strategy::LowestFirst; 4], Rules::default(), 2).unwrap();
+//! assert_eq!(summary.games(), 2);
+//! ```
+//!
+//! An experiment can also be written down as a [`Scenario`], so that it can be run again
+//! or shared:
+//!
+//! ```toml
+//! # The strategy played by each seat, which sets the number of players
+//! seats = ["advised", "random", "random", "random"]
+//! # The number of games dealt from each seed range
+//! games = 500
+//! # Where each range of seeds starts. Left out, a random seed is picked.
+//! seeds = [0, 10000]
+//!
+//! [rules]
+//! flush_beats_full_house = true
+//! ```
+
+use crate::{
+    cho_dai_di::{ChoDaiDi, TurnAction, FOUR_PLAYERS},
+    dataset::{self, Perspective, Record},
+    player::ai::{self, AiStrategy, Strategy, Weights},
+    r#match::Rules,
+};
+use anyhow::{bail, Context};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How a simulated game ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+    pub winner: usize,
+    /// Each player's score, by seat
+    pub scores: Vec<isize>,
+    /// The number of turns taken, including passes
+    pub turns: usize,
+}
+
+/// Play one game between computer players using `strategies`, one per seat. Games played
+/// with the same seed are dealt the same hands and play out the same way.
+pub fn play_game<const PLAYERS: usize>(
+    strategies: [Strategy; PLAYERS],
+    rules: Rules,
+    seed: u64,
+) -> anyhow::Result<GameResult> {
+    play_game_with_weights(strategies, &Weights::default(), rules, seed)
+}
+
+/// Like [`play_game`], but [`Strategy::Weighted`] seats score their plays by `weights`.
+pub fn play_game_with_weights<const PLAYERS: usize>(
+    strategies: [Strategy; PLAYERS],
+    weights: &Weights,
+    rules: Rules,
+    seed: u64,
+) -> anyhow::Result<GameResult> {
+    play(strategies, weights, rules, seed, None)
+}
+
+/// Like [`play_game_with_weights`], but also returns a [`Record`] of every turn, in the
+/// order they were taken.
+pub fn record_game<const PLAYERS: usize>(
+    strategies: [Strategy; PLAYERS],
+    weights: &Weights,
+    rules: Rules,
+    seed: u64,
+) -> anyhow::Result<(GameResult, Vec<Record>)> {
+    let mut records = Vec::new();
+    let result = play(strategies, weights, rules, seed, Some(&mut records))?;
+
+    Ok((result, records))
+}
+
+fn play<const PLAYERS: usize>(
+    strategies: [Strategy; PLAYERS],
+    weights: &Weights,
+    rules: Rules,
+    seed: u64,
+    mut records: Option<&mut Vec<Record>>,
+) -> anyhow::Result<GameResult> {
+    let mut players = strategies.map(|strategy| {
+        let mut player = ai::Player::with_strategy(strategy.to_string(), strategy);
+        player.set_weights(*weights);
+        player
+    });
+    let mut game = ChoDaiDi::<PLAYERS>::new_game_with_seed(seed);
+    rules.apply(&mut game);
+    game.check_instant_wins();
+    // The players' choices come from a stream of the seed of their own, apart from the deal
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(1);
+    while !game.is_game_ended() {
+        let seat = game.whose_turn();
+        let hand = game.get_current_players_hand();
+        let action = players[seat].choose_play(&game.view(), &hand, &mut rng);
+        if let Some(records) = records.as_deref_mut() {
+            // Encoded before the play's made, since a revolution changes the order of cards
+            let play = match &action {
+                TurnAction::Play(cards) => {
+                    dataset::encode_cards(cards.iter().copied(), &game.ordering())
+                }
+                TurnAction::Pass => 0,
+            };
+            records.push(Record {
+                perspective: Perspective::of(&game.view(), &hand),
+                play,
+                score: 0,
+            });
+        }
+        game.advance(action)
+            .with_context(|| format!("seat {} ({}) made a bad play", seat + 1, strategies[seat]))?;
+    }
+
+    let scores = game.provisional_scores();
+    for record in records.into_iter().flatten() {
+        record.score = scores[record.perspective.seat as usize] as i16;
+    }
+
+    Ok(GameResult {
+        winner: game.winner().context("the game ended without a winner")?,
+        scores: scores.to_vec(),
+        turns: game.history().len(),
+    })
+}
+
+/// How each seat did over a number of simulated games.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    strategies: Vec<Strategy>,
+    wins: Vec<usize>,
+    total_scores: Vec<isize>,
+    total_turns: usize,
+    games: usize,
+}
+
+impl Summary {
+    /// An empty summary of games between `strategies`, one per seat.
+    pub fn new(strategies: &[Strategy]) -> Self {
+        Summary {
+            strategies: strategies.to_vec(),
+            wins: vec![0; strategies.len()],
+            total_scores: vec![0; strategies.len()],
+            total_turns: 0,
+            games: 0,
+        }
+    }
+
+    /// Add a game's result to the summary.
+    pub fn record(&mut self, result: &GameResult) {
+        self.wins[result.winner] += 1;
+        for (total, score) in self.total_scores.iter_mut().zip(&result.scores) {
+            *total += score;
+        }
+        self.total_turns += result.turns;
+        self.games += 1;
+    }
+
+    /// Add the games in another summary of the same strategies to this one.
+    pub fn merge(&mut self, other: &Summary) {
+        debug_assert_eq!(self.strategies, other.strategies);
+        for (wins, other_wins) in self.wins.iter_mut().zip(&other.wins) {
+            *wins += other_wins;
+        }
+        for (total, other_total) in self.total_scores.iter_mut().zip(&other.total_scores) {
+            *total += other_total;
+        }
+        self.total_turns += other.total_turns;
+        self.games += other.games;
+    }
+
+    /// The strategy played by each seat.
+    pub fn strategies(&self) -> &[Strategy] {
+        &self.strategies
+    }
+
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    /// The number of games won by each seat.
+    pub fn wins(&self) -> &[usize] {
+        &self.wins
+    }
+
+    /// The fraction of games won by each seat.
+    pub fn win_rates(&self) -> Vec<f64> {
+        self.wins
+            .iter()
+            .map(|&wins| wins as f64 / self.games.max(1) as f64)
+            .collect()
+    }
+
+    /// The average score of each seat.
+    pub fn average_scores(&self) -> Vec<f64> {
+        self.total_scores
+            .iter()
+            .map(|&total| total as f64 / self.games.max(1) as f64)
+            .collect()
+    }
+
+    /// The average number of turns a game lasted.
+    pub fn average_turns(&self) -> f64 {
+        self.total_turns as f64 / self.games.max(1) as f64
+    }
+}
+
+/// Play `games` games between computer players using `strategies`, one per seat, and
+/// summarize how each seat did.
+pub fn simulate<const PLAYERS: usize>(
+    strategies: [Strategy; PLAYERS],
+    rules: Rules,
+    games: usize,
+) -> anyhow::Result<Summary> {
+    simulate_with_seed(strategies, rules, games, rand::random())
+}
+
+/// Like [`simulate`], but the games are played from seeds counting up from `seed`, so the
+/// same seed deals the same hands and the players make the same choices.
+pub fn simulate_with_seed<const PLAYERS: usize>(
+    strategies: [Strategy; PLAYERS],
+    rules: Rules,
+    games: usize,
+    seed: u64,
+) -> anyhow::Result<Summary> {
+    simulate_with_weights(strategies, &Weights::default(), rules, games, seed)
+}
+
+/// Like [`simulate_with_seed`], but [`Strategy::Weighted`] seats score their plays by
+/// `weights`. Every game is played by the same weights, so to change them during a long
+/// run, play it in batches and [merge](Summary::merge) their summaries.
+pub fn simulate_with_weights<const PLAYERS: usize>(
+    strategies: [Strategy; PLAYERS],
+    weights: &Weights,
+    rules: Rules,
+    games: usize,
+    seed: u64,
+) -> anyhow::Result<Summary> {
+    (0..games as u64)
+        .into_par_iter()
+        .map(|game| play_game_with_weights(strategies, weights, rules, seed.wrapping_add(game)))
+        .try_fold(
+            || Summary::new(&strategies),
+            |mut summary, result| {
+                summary.record(&result?);
+                anyhow::Ok(summary)
+            },
+        )
+        .try_reduce(
+            || Summary::new(&strategies),
+            |mut summary, other| {
+                summary.merge(&other);
+                Ok(summary)
+            },
+        )
+}
+
+/// Like [`simulate_with_weights`], but also returns a [`Record`] of every turn of every
+/// game, game by game, for a [dataset](crate::dataset).
+pub fn simulate_recording<const PLAYERS: usize>(
+    strategies: [Strategy; PLAYERS],
+    weights: &Weights,
+    rules: Rules,
+    games: usize,
+    seed: u64,
+) -> anyhow::Result<(Summary, Vec<Record>)> {
+    let played: Vec<_> = (0..games as u64)
+        .into_par_iter()
+        .map(|game| record_game(strategies, weights, rules, seed.wrapping_add(game)))
+        .collect::<anyhow::Result<_>>()?;
+    let mut summary = Summary::new(&strategies);
+    let mut records = Vec::new();
+    for (result, game_records) in played {
+        summary.record(&result);
+        records.extend(game_records);
+    }
+
+    Ok((summary, records))
+}
+
+/// A simulation that can be saved as TOML: who plays, by which rules, and which deals.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// The strategy played by each seat
+    pub seats: Vec<Strategy>,
+    #[serde(default)]
+    pub rules: Rules,
+    /// The number of games played from each seed range
+    pub games: usize,
+    /// The first seed of each range. The games of a range are dealt from seeds counting
+    /// up from it.
+    #[serde(default)]
+    pub seeds: Vec<u64>,
+}
+
+impl Scenario {
+    /// Play every range of the scenario. If it has no seeds, one is picked at random and
+    /// kept in the results, so the run can be repeated.
+    pub fn run(&self) -> anyhow::Result<ScenarioResults> {
+        let mut scenario = self.clone();
+        if scenario.seeds.is_empty() {
+            scenario.seeds.push(rand::random());
+        }
+
+        let summaries = match scenario.seats.len() {
+            2 => scenario.simulate_ranges::<2>()?,
+            3 => scenario.simulate_ranges::<3>()?,
+            FOUR_PLAYERS => scenario.simulate_ranges::<FOUR_PLAYERS>()?,
+            n => bail!("games must have from 2 to {FOUR_PLAYERS} seats, not {n}"),
+        };
+        let mut total = Summary::new(&scenario.seats);
+        for summary in &summaries {
+            total.merge(summary);
+        }
+
+        Ok(ScenarioResults {
+            seats: SeatResults::of(&total),
+            average_turns: total.average_turns(),
+            ranges: scenario
+                .seeds
+                .iter()
+                .zip(&summaries)
+                .map(|(&first_seed, summary)| RangeResults {
+                    first_seed,
+                    seats: SeatResults::of(summary),
+                    average_turns: summary.average_turns(),
+                })
+                .collect(),
+            scenario,
+        })
+    }
+
+    fn simulate_ranges<const PLAYERS: usize>(&self) -> anyhow::Result<Vec<Summary>> {
+        let strategies: [Strategy; PLAYERS] = self.seats.clone().try_into().unwrap();
+        self.seeds
+            .iter()
+            .map(|&seed| simulate_with_seed(strategies, self.rules, self.games, seed))
+            .collect()
+    }
+}
+
+/// How the games of a [`Scenario`] went, for saving as TOML.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioResults {
+    /// The scenario that was played, with the seed that was picked if it had none
+    pub scenario: Scenario,
+    /// How each seat did over every game
+    pub seats: Vec<SeatResults>,
+    pub average_turns: f64,
+    /// How each seat did in the games of each seed range
+    pub ranges: Vec<RangeResults>,
+}
+
+/// How the games dealt from one range of seeds went.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeResults {
+    pub first_seed: u64,
+    pub seats: Vec<SeatResults>,
+    pub average_turns: f64,
+}
+
+/// How one seat did over a number of games.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeatResults {
+    pub strategy: Strategy,
+    pub wins: usize,
+    pub win_rate: f64,
+    pub average_score: f64,
+}
+
+impl SeatResults {
+    /// How each seat did in the games of `summary`.
+    pub fn of(summary: &Summary) -> Vec<SeatResults> {
+        let seats = summary.strategies().iter().zip(summary.wins());
+        seats
+            .zip(summary.win_rates())
+            .zip(summary.average_scores())
+            .map(
+                |(((&strategy, &wins), win_rate), average_score)| SeatResults {
+                    strategy,
+                    wins,
+                    win_rate,
+                    average_score,
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulate() {
+        let strategies = [
+            Strategy::Random,
+            Strategy::Advised,
+            Strategy::LowestFirst,
+            Strategy::HandPreserving,
+        ];
+        let summary = simulate(strategies, Rules::default(), 2).unwrap();
+
+        assert_eq!(summary.games(), 2);
+        assert_eq!(summary.strategies(), strategies);
+        assert!((summary.win_rates().iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        // The winner collects what everyone else loses
+        assert!(summary.average_scores().iter().sum::<f64>().abs() < 1e-9);
+        assert!(summary.average_turns() >= 4.0);
+    }
+
+    #[test]
+    fn test_seed() {
+        let strategies = [
+            Strategy::Random,
+            Strategy::Advised,
+            Strategy::Random,
+            Strategy::Advised,
+        ];
+        for seed in 0..5 {
+            assert_eq!(
+                play_game(strategies, Rules::default(), seed).unwrap(),
+                play_game(strategies, Rules::default(), seed).unwrap()
+            );
+        }
+
+        let strategies = [Strategy::Random; 4];
+        assert_eq!(
+            simulate_with_seed(strategies, Rules::default(), 8, 3).unwrap(),
+            simulate_with_seed(strategies, Rules::default(), 8, 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_weights() {
+        let strategies = [
+            Strategy::Weighted,
+            Strategy::Advised,
+            Strategy::Advised,
+            Strategy::Advised,
+        ];
+        let weights = Weights {
+            breaking_up: 3.0,
+            ..Weights::default()
+        };
+        // Played in batches, the games are the same as when they're played at once
+        let mut batches =
+            simulate_with_weights(strategies, &weights, Rules::default(), 2, 9).unwrap();
+        batches
+            .merge(&simulate_with_weights(strategies, &weights, Rules::default(), 2, 11).unwrap());
+        assert_eq!(
+            batches,
+            simulate_with_weights(strategies, &weights, Rules::default(), 4, 9).unwrap()
+        );
+
+        // By default, a weighted seat plays like an advised one that makes no mistakes
+        for seed in 0..5 {
+            assert_eq!(
+                play_game([Strategy::Weighted; 3], Rules::default(), seed).unwrap(),
+                play_game([Strategy::Advised; 3], Rules::default(), seed).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulate_recording() {
+        let strategies = [Strategy::HandPreserving; 4];
+        let weights = Weights::default();
+        let (summary, records) =
+            simulate_recording(strategies, &weights, Rules::default(), 3, 5).unwrap();
+        assert_eq!(
+            summary,
+            simulate_with_weights(strategies, &weights, Rules::default(), 3, 5).unwrap()
+        );
+        assert_eq!(records.len() as f64, summary.average_turns() * 3.0);
+
+        let (result, records) = record_game(strategies, &weights, Rules::default(), 5).unwrap();
+        assert_eq!(records.len(), result.turns);
+        for record in &records {
+            let seat = record.perspective.seat as usize;
+            assert_eq!(record.score as isize, result.scores[seat]);
+            // Only cards in the hand are played
+            assert_eq!(record.play & !record.perspective.hand, 0);
+        }
+        // The winner played their last cards
+        let last = records.last().unwrap();
+        assert_eq!(last.perspective.seat as usize, result.winner);
+        assert_eq!(last.play, last.perspective.hand);
+    }
+
+    #[test]
+    fn test_summary_record() {
+        let mut summary = Summary::new(&[Strategy::Random, Strategy::Advised]);
+        summary.record(&GameResult {
+            winner: 1,
+            scores: vec![-6, 6],
+            turns: 10,
+        });
+        summary.record(&GameResult {
+            winner: 1,
+            scores: vec![-2, 2],
+            turns: 20,
+        });
+
+        assert_eq!(summary.win_rates(), [0.0, 1.0]);
+        assert_eq!(summary.average_scores(), [-4.0, 4.0]);
+        assert_eq!(summary.average_turns(), 15.0);
+
+        let mut merged = Summary::new(&[Strategy::Random, Strategy::Advised]);
+        merged.merge(&summary);
+        merged.merge(&summary);
+        assert_eq!(merged.games(), 4);
+        assert_eq!(merged.win_rates(), summary.win_rates());
+        assert_eq!(merged.average_scores(), summary.average_scores());
+    }
+
+    #[test]
+    fn test_scenario() {
+        let scenario: Scenario = toml::from_str(
+            r#"
+            seats = ["advised", "random", "lowest-first"]
+            games = 3
+            seeds = [7, 1000]
+
+            [rules]
+            flush_beats_full_house = true
+            "#,
+        )
+        .unwrap();
+        assert!(scenario.rules.flush_beats_full_house);
+
+        let results = scenario.run().unwrap();
+        assert_eq!(results.scenario, scenario);
+        assert_eq!(results.ranges.len(), 2);
+        assert_eq!(results.ranges[0].first_seed, 7);
+        let wins = |seats: &[SeatResults]| seats.iter().map(|seat| seat.wins).sum::<usize>();
+        assert_eq!(wins(&results.seats), 6);
+        assert_eq!(wins(&results.ranges[1].seats), 3);
+        assert_eq!(results.seats[2].strategy, Strategy::LowestFirst);
+
+        let range = simulate_with_seed(
+            [Strategy::Advised, Strategy::Random, Strategy::LowestFirst],
+            scenario.rules,
+            3,
+            1000,
+        )
+        .unwrap();
+        assert_eq!(results.ranges[1].seats, SeatResults::of(&range));
+
+        // The results can be read back
+        let saved = toml::to_string(&results).unwrap();
+        assert_eq!(toml::from_str::<ScenarioResults>(&saved).unwrap(), results);
+    }
+
+    #[test]
+    fn test_scenario_picks_a_seed() {
+        let scenario = Scenario {
+            seats: vec![Strategy::Random; 2],
+            rules: Rules::default(),
+            games: 1,
+            seeds: Vec::new(),
+        };
+        let results = scenario.run().unwrap();
+        assert_eq!(results.scenario.seeds.len(), 1);
+        assert_eq!(results.scenario.run().unwrap(), results);
+
+        let too_many = Scenario {
+            seats: vec![Strategy::Random; 5],
+            ..scenario
+        };
+        assert!(too_many.run().is_err());
+    }
+}
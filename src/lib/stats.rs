@@ -0,0 +1,566 @@
+//! Each player's record across every game they've played, along with an Elo-style rating.
+//!
+//! A game is rated as if every pair of players at the table had played each other, with
+//! whoever scored higher winning. Beating a higher rated player gains more than beating a
+//! lower rated one.
+//!
+//! Games played on paper can be added from a ledger, see [`read_ledger`].
+
+use crate::{
+    card::Card,
+    cho_dai_di::{ChoDaiDi, Combination, TurnAction},
+};
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io};
+
+/// The rating that players start with.
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// The most a rating can change by in one game.
+const K_FACTOR: f64 = 32.0;
+
+/// The most games kept in each player's [`PlayerStats::history`].
+pub const HISTORY_LEN: usize = 1000;
+
+/// A player's record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerStats {
+    pub wins: usize,
+    pub losses: usize,
+    /// The number of their games that were rated, see [`ChoDaiDi::set_rated`]
+    pub rated_games: usize,
+    /// The number of cards left in their hand when each game ended, added up
+    pub total_cards_left: usize,
+    /// The number of their games that were imported from a ledger, which don't count
+    /// towards the cards they had left
+    pub imported_games: usize,
+    /// The highest five-card hand they've played
+    pub best_hand: Option<BestHand>,
+    pub rating: f64,
+    /// How each of their last [`HISTORY_LEN`] games went, oldest first. Games recorded
+    /// by versions before this was kept aren't in it.
+    pub history: Vec<GameResult>,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        PlayerStats {
+            wins: 0,
+            losses: 0,
+            rated_games: 0,
+            total_cards_left: 0,
+            imported_games: 0,
+            best_hand: None,
+            rating: INITIAL_RATING,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl PlayerStats {
+    pub fn games(&self) -> usize {
+        self.wins + self.losses
+    }
+
+    /// The average number of cards left in their hand when a game ended, over the games
+    /// played in the app.
+    pub fn average_cards_left(&self) -> f64 {
+        let games = self.games() - self.imported_games;
+        self.total_cards_left as f64 / games.max(1) as f64
+    }
+
+    /// The share of games they won in each run of `games` games in their history, oldest
+    /// first. The last run may be shorter.
+    pub fn win_rates(&self, games: usize) -> Vec<f64> {
+        self.history
+            .chunks(games.max(1))
+            .map(|chunk| {
+                let wins = chunk.iter().filter(|game| game.won).count();
+                wins as f64 / chunk.len() as f64
+            })
+            .collect()
+    }
+
+    /// The average number of cards left in their hand in each run of `games` games in
+    /// their history, oldest first. Runs of games imported from a ledger are `None`.
+    pub fn cards_left_over_time(&self, games: usize) -> Vec<Option<f64>> {
+        self.history
+            .chunks(games.max(1))
+            .map(|chunk| {
+                let known: Vec<_> = chunk.iter().filter_map(|game| game.cards_left).collect();
+                (!known.is_empty()).then(|| known.iter().sum::<usize>() as f64 / known.len() as f64)
+            })
+            .collect()
+    }
+
+    /// How many games in their history they scored in each of `bins` equal ranges of
+    /// scores, from the lowest score to the highest. Each range is given by its lowest
+    /// score.
+    pub fn score_distribution(&self, bins: usize) -> Vec<(isize, usize)> {
+        let scores = self.history.iter().map(|game| game.score);
+        let (Some(low), Some(high)) = (scores.clone().min(), scores.max()) else {
+            return Vec::new();
+        };
+        let bins = bins.max(1);
+        // Round up, so the highest score lands in the last range
+        let width = ((high - low) as usize / bins + 1) as isize;
+        let mut counts = vec![0; bins];
+        for game in &self.history {
+            counts[((game.score - low) / width) as usize] += 1;
+        }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(bin, count)| (low + bin as isize * width, count))
+            .collect()
+    }
+}
+
+/// How a player did in one game, as kept in [`PlayerStats::history`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameResult {
+    pub won: bool,
+    pub score: isize,
+    /// The number of cards left in their hand, if the game was played in the app
+    pub cards_left: Option<usize>,
+}
+
+/// A five-card hand that someone played.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BestHand {
+    #[serde(rename = "kind")]
+    pub combination: Combination,
+    pub cards: Vec<Card>,
+}
+
+/// A game played away from the app, as written down in a ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerGame {
+    /// The day the game was played, as `YYYY-MM-DD`
+    pub date: String,
+    /// The name and score of each player in the game
+    pub scores: Vec<(String, isize)>,
+}
+
+/// Read a ledger of games played on paper, oldest first.
+///
+/// The ledger is a CSV with a `date` column, then a column for each player. Each row is a
+/// game, with a score for each player who played and the other cells left empty. Games
+/// on the same day are kept in the ledger's order.
+///
+/// ```
+/// use card_games::stats::read_ledger;
+///
+/// let ledger = "date,Alice,Bob,Carol\n2024-03-02,6,-2,-4\n2024-03-01,-5,,5\n";
+/// let games = read_ledger(ledger.as_bytes()).unwrap();
+/// assert_eq!(games[0].date, "2024-03-01");
+/// assert_eq!(games[0].scores, [("Alice".to_owned(), -5), ("Carol".to_owned(), 5)]);
+/// ```
+pub fn read_ledger(reader: impl io::Read) -> anyhow::Result<Vec<LedgerGame>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = reader
+        .headers()
+        .context("reading the ledger's header")?
+        .clone();
+    if !headers
+        .get(0)
+        .is_some_and(|it| it.eq_ignore_ascii_case("date"))
+    {
+        bail!("the ledger's first column must be the date");
+    }
+    let players: Vec<_> = headers.iter().skip(1).collect();
+
+    let mut games = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        // The header is line 1
+        let line = i + 2;
+        let record = record.with_context(|| format!("reading line {line} of the ledger"))?;
+        let date = record.get(0).unwrap_or_default();
+        if !is_date(date) {
+            bail!("'{date}' on line {line} of the ledger isn't a date like 2024-03-01");
+        }
+        let mut scores = Vec::new();
+        for (player, cell) in players.iter().zip(record.iter().skip(1)) {
+            if cell.is_empty() {
+                continue;
+            }
+            let score = cell.parse().with_context(|| {
+                format!("{player}'s score on line {line} of the ledger isn't a number")
+            })?;
+            scores.push((player.to_string(), score));
+        }
+        if scores.len() < 2 {
+            bail!("the game on line {line} of the ledger has fewer than two players");
+        }
+        let top = scores.iter().map(|(_, score)| *score).max();
+        if scores
+            .iter()
+            .filter(|(_, score)| Some(*score) == top)
+            .count()
+            > 1
+        {
+            bail!("the game on line {line} of the ledger has more than one top score");
+        }
+
+        games.push(LedgerGame {
+            date: date.to_owned(),
+            scores,
+        });
+    }
+    // Ratings depend on the order games were played in
+    games.sort_by(|a, b| a.date.cmp(&b.date));
+
+    Ok(games)
+}
+
+fn is_date(s: &str) -> bool {
+    let parts: Vec<_> = s.split('-').collect();
+    matches!(parts.as_slice(), [year, month, day]
+        if year.len() == 4 && month.len() == 2 && day.len() == 2
+            && parts.iter().all(|part| part.bytes().all(|b| b.is_ascii_digit())))
+}
+
+/// How one player did in a game.
+struct Outcome {
+    name: String,
+    won: bool,
+    rated: bool,
+    /// If true, the game was imported from a ledger, and the cards left aren't known
+    imported: bool,
+    score: isize,
+    cards_left: usize,
+    best_hand: Option<BestHand>,
+}
+
+/// The records of everyone who's played, by name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    players: BTreeMap<String, PlayerStats>,
+}
+
+impl Stats {
+    /// Get a player's record, if they've finished a game.
+    pub fn player(&self, name: &str) -> Option<&PlayerStats> {
+        self.players.get(name)
+    }
+
+    /// Get every player's record, from the highest rated to the lowest.
+    pub fn leaderboard(&self) -> Vec<(&str, &PlayerStats)> {
+        let mut players: Vec<_> = self
+            .players
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+            .collect();
+        players.sort_by(|(_, a), (_, b)| b.rating.total_cmp(&a.rating));
+        players
+    }
+
+    /// Record a finished game. `names` are the names of the players in each seat.
+    pub fn record_game<const PLAYERS: usize>(
+        &mut self,
+        names: &[String],
+        game: &ChoDaiDi<PLAYERS>,
+    ) -> anyhow::Result<()> {
+        if names.len() != PLAYERS {
+            anyhow::bail!(
+                "{} names were given for a {PLAYERS} player game",
+                names.len()
+            );
+        }
+        let Some(winner) = game.winner() else {
+            anyhow::bail!("only finished games can be recorded");
+        };
+
+        let mut best_hands: [Option<BestHand>; PLAYERS] = std::array::from_fn(|_| None);
+        for turn in game.history() {
+            let TurnAction::Play(cards) = &turn.action else {
+                continue;
+            };
+            if let Some(combination) = cards.five_card_hand() {
+                let best = &mut best_hands[turn.player];
+                if best
+                    .as_ref()
+                    .is_none_or(|best| combination > best.combination)
+                {
+                    *best = Some(BestHand {
+                        combination,
+                        cards: cards.iter().copied().collect(),
+                    });
+                }
+            }
+        }
+        let scores = game.provisional_scores();
+        let outcomes = names
+            .iter()
+            .zip(best_hands)
+            .enumerate()
+            .map(|(seat, (name, best_hand))| Outcome {
+                name: name.clone(),
+                won: seat == winner,
+                rated: game.is_rated(),
+                imported: false,
+                score: scores[seat],
+                cards_left: game.hands()[seat].len(),
+                best_hand,
+            })
+            .collect();
+        self.record(outcomes);
+
+        Ok(())
+    }
+
+    /// Record a game from a ledger. Whoever scored highest won it.
+    pub fn record_ledger_game(&mut self, game: &LedgerGame) {
+        let top = game.scores.iter().map(|(_, score)| *score).max();
+        let outcomes = game
+            .scores
+            .iter()
+            .map(|(name, score)| Outcome {
+                name: name.clone(),
+                won: Some(*score) == top,
+                rated: false,
+                imported: true,
+                score: *score,
+                cards_left: 0,
+                best_hand: None,
+            })
+            .collect();
+        self.record(outcomes);
+    }
+
+    fn record(&mut self, outcomes: Vec<Outcome>) {
+        let ratings: Vec<_> = outcomes
+            .iter()
+            .map(|outcome| {
+                self.players
+                    .get(&outcome.name)
+                    .map_or(INITIAL_RATING, |it| it.rating)
+            })
+            .collect();
+        // Each pairing is worth a share, so a game's worth the same however many play
+        let k = K_FACTOR / (outcomes.len().max(2) - 1) as f64;
+
+        for (i, outcome) in outcomes.iter().enumerate() {
+            let change: f64 = outcomes
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(j, other)| {
+                    let expected = 1.0 / (1.0 + 10f64.powf((ratings[j] - ratings[i]) / 400.0));
+                    let actual = match outcome.score.cmp(&other.score) {
+                        std::cmp::Ordering::Greater => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Less => 0.0,
+                    };
+                    k * (actual - expected)
+                })
+                .sum();
+
+            let stats = self.players.entry(outcome.name.clone()).or_default();
+            stats.rating += change;
+            if outcome.won {
+                stats.wins += 1;
+            } else {
+                stats.losses += 1;
+            }
+            if outcome.rated {
+                stats.rated_games += 1;
+            }
+            if outcome.imported {
+                stats.imported_games += 1;
+            }
+            stats.total_cards_left += outcome.cards_left;
+            if stats.history.len() == HISTORY_LEN {
+                stats.history.remove(0);
+            }
+            stats.history.push(GameResult {
+                won: outcome.won,
+                score: outcome.score,
+                cards_left: (!outcome.imported).then_some(outcome.cards_left),
+            });
+            if let Some(hand) = &outcome.best_hand {
+                if stats
+                    .best_hand
+                    .as_ref()
+                    .is_none_or(|best| hand.combination > best.combination)
+                {
+                    stats.best_hand = Some(hand.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(name: &str, score: isize, cards_left: usize) -> Outcome {
+        Outcome {
+            name: name.to_owned(),
+            won: cards_left == 0,
+            rated: false,
+            imported: false,
+            score,
+            cards_left,
+            best_hand: None,
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut stats = Stats::default();
+        stats.record(vec![
+            outcome("A", 9, 0),
+            outcome("B", -2, 2),
+            outcome("C", -7, 7),
+        ]);
+
+        let a = stats.player("A").unwrap();
+        assert_eq!((a.wins, a.losses), (1, 0));
+        assert_eq!(a.rating, INITIAL_RATING + K_FACTOR / 2.0);
+        let b = stats.player("B").unwrap();
+        assert_eq!((b.wins, b.losses), (0, 1));
+        assert_eq!(b.rating, INITIAL_RATING);
+        assert_eq!(
+            stats.player("C").unwrap().rating,
+            INITIAL_RATING - K_FACTOR / 2.0
+        );
+        // Ratings move from one player to another
+        let total: f64 = stats.leaderboard().iter().map(|(_, it)| it.rating).sum();
+        assert!((total - 3.0 * INITIAL_RATING).abs() < 1e-9);
+
+        let mut rated = outcome("A", -4, 4);
+        rated.rated = true;
+        stats.record(vec![rated, outcome("B", 4, 0)]);
+        let a = stats.player("A").unwrap();
+        assert_eq!(a.games(), 2);
+        assert_eq!(a.rated_games, 1);
+        assert_eq!(a.average_cards_left(), 2.0);
+        // A was rated higher than B, so beating them is worth more than an even match
+        assert!(stats.player("B").unwrap().rating > INITIAL_RATING + K_FACTOR / 2.0);
+        assert_eq!(
+            stats
+                .leaderboard()
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>(),
+            ["B", "A", "C"]
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let mut stats = Stats::default();
+        for (score, cards_left) in [(9, 0), (-3, 3), (-10, 10), (6, 0), (-1, 1)] {
+            stats.record(vec![outcome("A", score, cards_left), outcome("B", 0, 5)]);
+        }
+        stats.record_ledger_game(&LedgerGame {
+            date: "2024-03-01".to_owned(),
+            scores: vec![("A".to_owned(), 4), ("B".to_owned(), -4)],
+        });
+
+        let a = stats.player("A").unwrap();
+        assert_eq!(a.history.len(), 6);
+        assert_eq!(a.history[5].cards_left, None);
+        assert_eq!(a.win_rates(2), [0.5, 0.5, 0.5]);
+        assert_eq!(a.cards_left_over_time(4), [Some(3.25), Some(1.0)]);
+        assert_eq!(a.cards_left_over_time(1)[5], None);
+        // Scores run from -10 to 9, so three ranges are 7 wide
+        assert_eq!(a.score_distribution(3), [(-10, 1), (-3, 2), (4, 3)]);
+        assert_eq!(PlayerStats::default().score_distribution(3), []);
+
+        for _ in 0..HISTORY_LEN {
+            stats.record(vec![outcome("A", 1, 0)]);
+        }
+        assert_eq!(stats.player("A").unwrap().history.len(), HISTORY_LEN);
+    }
+
+    #[test]
+    fn test_best_hand() {
+        let hand = |combination| BestHand {
+            combination,
+            cards: Vec::new(),
+        };
+        let mut stats = Stats::default();
+        for combination in [
+            Combination::FullHouse,
+            Combination::Straight,
+            Combination::FourOfAKindPlusOne,
+            Combination::Flush,
+        ] {
+            let mut outcome = outcome("A", 1, 0);
+            outcome.best_hand = Some(hand(combination));
+            stats.record(vec![outcome]);
+        }
+
+        assert_eq!(
+            stats.player("A").unwrap().best_hand,
+            Some(hand(Combination::FourOfAKindPlusOne))
+        );
+    }
+
+    #[test]
+    fn test_record_unfinished_game() {
+        let game = ChoDaiDi::<3>::new_game_with_seed(1);
+        let names = ["A", "B", "C"].map(ToOwned::to_owned);
+        assert!(Stats::default().record_game(&names, &game).is_err());
+        assert!(Stats::default().record_game(&names[..2], &game).is_err());
+    }
+
+    #[test]
+    fn test_read_ledger() {
+        let ledger = "\
+            Date, Alice, Bob, Carol, Dan
+            2024-03-02, 9, -2, -3, -4
+            2024-03-01, -1, 3, , -2
+            2024-03-01, , -6, 12, -6";
+        let games = read_ledger(ledger.as_bytes()).unwrap();
+        let dates: Vec<_> = games.iter().map(|game| game.date.as_str()).collect();
+        assert_eq!(dates, ["2024-03-01", "2024-03-01", "2024-03-02"]);
+        assert_eq!(
+            games[0].scores,
+            [
+                ("Alice".to_owned(), -1),
+                ("Bob".to_owned(), 3),
+                ("Dan".to_owned(), -2)
+            ]
+        );
+        assert_eq!(games[1].scores.len(), 3);
+
+        for bad in [
+            "Player,Alice,Bob\n2024-03-01,1,-1\n",
+            "date,Alice,Bob\nMarch 1st,1,-1\n",
+            "date,Alice,Bob\n2024-03-01,one,-1\n",
+            "date,Alice,Bob\n2024-03-01,1,\n",
+            "date,Alice,Bob,Carol\n2024-03-01,2,2,-4\n",
+        ] {
+            assert!(read_ledger(bad.as_bytes()).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_record_ledger_game() {
+        let mut stats = Stats::default();
+        stats.record(vec![outcome("A", 2, 0), outcome("B", -2, 2)]);
+        stats.record_ledger_game(&LedgerGame {
+            date: "2024-03-01".to_owned(),
+            scores: vec![("A".to_owned(), -5), ("B".to_owned(), 5)],
+        });
+
+        let a = stats.player("A").unwrap();
+        assert_eq!((a.wins, a.losses, a.imported_games), (1, 1, 1));
+        // The ledger doesn't say how many cards were left, so only the game played in
+        // the app counts
+        let b = stats.player("B").unwrap();
+        assert_eq!((b.wins, b.losses), (1, 1));
+        assert_eq!(b.average_cards_left(), 2.0);
+        // B beat a higher rated player, so gained more than they lost
+        assert!(b.rating > a.rating);
+    }
+}
@@ -21,6 +21,25 @@ impl fmt::Display for Suit {
     }
 }
 
+/// The names that each suit may be written as in other languages, for looking a suit
+/// up by its localized name.
+const LOCALIZED_NAMES: [(Suit, &[&str]); 4] = [
+    (Suit::Diamonds, &["方塊", "方块", "階磚", "阶砖"]),
+    (Suit::Clubs, &["梅花", "草花"]),
+    (Suit::Hearts, &["紅心", "红心", "紅桃", "红桃"]),
+    (Suit::Spades, &["黑桃", "葵扇"]),
+];
+
+impl Suit {
+    /// Look up a suit by one of its localized names, like "紅心".
+    pub fn from_localized_name(name: &str) -> Option<Suit> {
+        LOCALIZED_NAMES
+            .iter()
+            .find(|(_, names)| names.contains(&name))
+            .map(|(suit, _)| *suit)
+    }
+}
+
 impl FromStr for Suit {
     type Err = anyhow::Error;
 
@@ -47,7 +66,24 @@ impl FromStr for Suit {
         {
             Ok(Suit::Spades)
         } else {
-            Err(anyhow::anyhow!("`{s}` is not a suit"))
+            Suit::from_localized_name(s).ok_or_else(|| anyhow::anyhow!("`{s}` is not a suit"))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Suit;
+
+    #[test]
+    fn test_from_localized_name() {
+        assert_eq!(Suit::from_localized_name("方塊"), Some(Suit::Diamonds));
+        assert_eq!(Suit::from_localized_name("草花"), Some(Suit::Clubs));
+        assert_eq!(Suit::from_localized_name("红桃"), Some(Suit::Hearts));
+        assert_eq!(Suit::from_localized_name("黑桃"), Some(Suit::Spades));
+        assert_eq!(Suit::from_localized_name("spades"), None);
+
+        assert_eq!("葵扇".parse::<Suit>().unwrap(), Suit::Spades);
+        assert!("紅".parse::<Suit>().is_err());
+    }
+}
@@ -1,28 +1,211 @@
+#[macro_use]
+mod transcript;
+
+mod attract;
+mod cli;
+mod dashboard;
+mod export;
+mod input;
+mod minimize;
+mod net;
+mod persist;
+mod personality;
 mod player;
+mod preset;
+mod profile;
+mod quiz;
+mod saved_match;
+mod simulate;
+mod stats;
+mod table_setup;
 
-use tracing::info;
+use anyhow::Context;
+use card_games::{cho_dai_di::FOUR_PLAYERS, player::ai::Difficulty, replay};
+use clap::Parser;
+use cli::{Cli, Command, RuleArgs};
+use input::InputSource;
+use std::{fs, path::PathBuf};
+use tracing::{info, Level};
 
 fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
+    let cli = Cli::parse();
+    let level = match (cli.quiet, cli.verbose) {
+        (true, _) => Level::WARN,
+        (false, 0) => Level::INFO,
+        (false, 1) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    };
+    tracing_subscriber::fmt().with_max_level(level).init();
+
+    // Fail before the game starts if the arguments are wrong
+    let args = match cli.command.unwrap_or(Command::Play(cli.play)) {
+        Command::Play(play) => play.into_args(cli.no_color)?,
+        Command::Serve(serve) => serve.into_args(cli.no_color)?,
+        Command::Replay { file, code } => {
+            let contents = match (file, code) {
+                (_, Some(code)) => replay::decode(&code)?,
+                (Some(file), None) => fs::read_to_string(&file)
+                    .with_context(|| format!("reading replay {}", file.display()))?,
+                (None, None) => unreachable!("clap requires a file or a code"),
+            };
+            return watch_replay(&contents, cli.no_color);
+        }
+        Command::Export { files, output } => return export::run(&files, output.as_deref()),
+        Command::Minimize { file, output } => return minimize::run(&file, &output),
+        Command::Simulate(simulate) => return simulate::run(simulate),
+        Command::Quiz => return quiz::run(),
+        Command::Stats { import } => return stats::run(import.as_deref()),
+        Command::Dashboard {
+            player,
+            games_per_point,
+        } => return dashboard::run(player.as_deref(), games_per_point),
+        Command::Preset { command } => return preset::run(command),
+    };
+    if let Some(address) = &args.connect {
+        let profile = profile::Profile::load()?.unwrap_or_default();
+        let name = args.name.clone().unwrap_or(profile.name);
+        return net::connect(address, &name, args.spectate);
+    }
+    match args.players {
+        2 => run::<2>(args),
+        3 => run::<3>(args),
+        FOUR_PLAYERS => run::<FOUR_PLAYERS>(args),
+        n => anyhow::bail!("games must have from 2 to {FOUR_PLAYERS} players, not {n}"),
+    }
+}
 
-    let mut state_machine = StateMachine {
-        inner: Some(State::StartNewGame),
+/// Run the state machine from `initial_state` until it ends.
+fn run_from<const PLAYERS: usize>(initial_state: State<PLAYERS>) -> anyhow::Result<()> {
+    let mut state_machine = StateMachine::<PLAYERS> {
+        inner: Some(initial_state),
     };
+    while !state_machine.is_end() {
+        state_machine.tick()?;
+    }
+
+    Ok(())
+}
 
-    loop {
+/// Play a match at a table whose players have already been seated, returning it once
+/// it's over.
+fn play_table<const PLAYERS: usize>(
+    table: start_new_game::Table<PLAYERS>,
+) -> anyhow::Result<card_games::r#match::Match> {
+    let mut r#match = table.r#match.clone();
+    let mut state_machine = StateMachine::<PLAYERS> {
+        inner: Some(State::NextGame(table)),
+    };
+    while !state_machine.is_end() {
         state_machine.tick()?;
-        if state_machine.is_end() {
-            info!("Thank you for playing!");
-            break Ok(());
+        // The table's dropped once the match is over, so keep its scores from the last
+        // game
+        if let Some(State::PostGame(post_game)) = &state_machine.inner {
+            r#match = post_game.table.r#match.clone();
         }
     }
+
+    Ok(r#match)
 }
 
-struct StateMachine {
-    inner: Option<State>,
+/// Play back the replay in `contents` from start to finish.
+fn watch_replay(contents: &str, no_color: bool) -> anyhow::Result<()> {
+    match replay::number_of_players(contents) {
+        2 => run_from(State::<2>::Replay(playback_state(contents, no_color)?)),
+        3 => run_from(State::<3>::Replay(playback_state(contents, no_color)?)),
+        FOUR_PLAYERS => run_from(State::<FOUR_PLAYERS>::Replay(playback_state(
+            contents, no_color,
+        )?)),
+        n => anyhow::bail!("the replay has {n} players, but games have from 2 to {FOUR_PLAYERS}"),
+    }
 }
 
-impl StateMachine {
+fn playback_state<const PLAYERS: usize>(
+    contents: &str,
+    no_color: bool,
+) -> anyhow::Result<playback::State<PLAYERS>> {
+    let profile = profile::Profile::load()?.unwrap_or_default();
+    let mut card_style = profile.card_style();
+    card_style.color &= !no_color;
+    playback::State::new(contents.parse()?, profile.replay_delay(), card_style)
+}
+
+fn run<const PLAYERS: usize>(args: Args) -> anyhow::Result<()> {
+    let initial_state = match args.menu {
+        true => State::MainMenu(args),
+        false => State::StartNewGame(args),
+    };
+    run_from::<PLAYERS>(initial_state)?;
+    info!("Thank you for playing!");
+
+    Ok(())
+}
+
+/// Options for a game, given on the command line.
+#[derive(Clone)]
+pub struct Args {
+    /// The script given with `--script <file>`, if any
+    script: Option<PathBuf>,
+    /// The number of seats at the table, given with `--players <n>`
+    players: usize,
+    /// The number of seats played by people, given with `--humans <n>`
+    humans: usize,
+    /// How well the computer players play, given with `--difficulty <level>`
+    difficulty: Option<Difficulty>,
+    /// The number of games in the match, given with `--games <n>`
+    games: usize,
+    /// The seed the match's first game is dealt from, given with `--seed <n>`
+    seed: Option<u64>,
+    /// House rules to play by on top of the profile's
+    rules: RuleArgs,
+    /// If true, nothing is colored, given with `--no-color`
+    no_color: bool,
+    /// If true, human seats are played by people connecting over the network, given
+    /// with `serve`
+    server: bool,
+    /// If true, the server accepts WebSocket connections, given with `--websocket`
+    websocket: bool,
+    /// If true, the server lets people choose a table to play at, given with `--lobby`
+    lobby: bool,
+    /// The number of tables the server hosts a tournament across, given with
+    /// `--tables <n>`
+    tables: usize,
+    /// If true, every seat is played by the computer, or the game at `connect` is
+    /// watched rather than joined, given with `--spectate`
+    spectate: bool,
+    /// The port to host a game on, given with `--port <n>`
+    port: u16,
+    /// The address of a game to join, given with `--connect <host:port>`
+    connect: Option<String>,
+    /// The name to play under instead of the profile's, given with `--name <name>` or
+    /// the `DAI_DI_PLAYER_NAME` environment variable
+    name: Option<String>,
+    /// If true, the main menu is shown instead of starting a game straight away. It's
+    /// only shown to someone at the keyboard who hasn't asked for anything else.
+    menu: bool,
+    /// The table to play at instead of the one given on the command line, given with
+    /// `--preset <name>`
+    preset: Option<preset::Preset>,
+}
+
+/// Ask the person at the keyboard a question, returning `None` if there's no more input.
+fn prompt(question: &str) -> anyhow::Result<Option<String>> {
+    print!("{question}");
+    transcript::record_partial(question);
+    std::io::Write::flush(&mut std::io::stdout())
+        .with_context(|| format!("flushing '{question}' prompt"))?;
+    let answer = input::Stdin
+        .read_line()?
+        .map(|answer| answer.trim().to_owned());
+    transcript::record(answer.as_deref().unwrap_or_default());
+
+    Ok(answer)
+}
+
+struct StateMachine<const PLAYERS: usize> {
+    inner: Option<State<PLAYERS>>,
+}
+
+impl<const PLAYERS: usize> StateMachine<PLAYERS> {
     fn tick(&mut self) -> anyhow::Result<()> {
         let inner = self.inner.take().unwrap();
         let next_state = inner.tick()?;
@@ -31,24 +214,30 @@ impl StateMachine {
     }
 
     fn is_end(&self) -> bool {
-        self.inner.as_ref().map_or(false, |state| state.is_end())
+        self.inner.as_ref().is_some_and(|state| state.is_end())
     }
 }
 
 #[allow(clippy::large_enum_variant)]
-enum State {
-    StartNewGame,
-    Play(play_game::State),
-    PostGame(post_game::State),
+enum State<const PLAYERS: usize> {
+    MainMenu(Args),
+    StartNewGame(Args),
+    NextGame(start_new_game::Table<PLAYERS>),
+    Play(play_game::State<PLAYERS>),
+    PostGame(post_game::State<PLAYERS>),
+    Replay(playback::State<PLAYERS>),
     End,
 }
 
-impl State {
-    fn tick(self) -> anyhow::Result<State> {
+impl<const PLAYERS: usize> State<PLAYERS> {
+    fn tick(self) -> anyhow::Result<State<PLAYERS>> {
         match self {
-            State::StartNewGame => start_new_game::tick(),
+            State::MainMenu(args) => main_menu::run(args),
+            State::StartNewGame(args) => start_new_game::tick(args),
+            State::NextGame(table) => start_new_game::deal(table),
             State::Play(play_state) => play_game::run(play_state),
             State::PostGame(post_game_state) => post_game::run(post_game_state),
+            State::Replay(playback_state) => playback::run(playback_state),
             State::End => Ok(State::End),
         }
     }
@@ -58,68 +247,799 @@ impl State {
     }
 }
 
+mod main_menu {
+    use crate::{attract::Demo, profile::Profile, prompt, saved_match, start_new_game, Args};
+    use anyhow::Context;
+    use card_games::cho_dai_di::FOUR_PLAYERS;
+    use std::{
+        fs,
+        io::IsTerminal,
+        path::{Path, PathBuf},
+    };
+
+    const OPTIONS: [&str; 6] = [
+        "New game",
+        "Resume saved game",
+        "Watch a replay",
+        "View stats",
+        "Settings",
+        "Quit",
+    ];
+
+    pub fn run<const PLAYERS: usize>(args: Args) -> anyhow::Result<super::State<PLAYERS>> {
+        say!();
+        say!("Dai Di");
+        for (i, option) in OPTIONS.iter().enumerate() {
+            say!("  {}. {option}", i + 1);
+        }
+        // No one's about to answer a script or a pipe, so there's no one to attract
+        let profile = Profile::load()?.unwrap_or_default();
+        let demo = profile
+            .attract_mode_after()
+            .filter(|_| std::io::stdin().is_terminal())
+            .map(|idle| {
+                let card_style = start_new_game::styles(&profile, args.no_color).0;
+                Demo::start::<PLAYERS>(idle, profile.rules(), card_style)
+            });
+        let choice = prompt("Choose an option: ")?;
+        if demo.is_some_and(Demo::stop) {
+            return Ok(super::State::MainMenu(args));
+        }
+        let Some(choice) = choice else {
+            return Ok(super::State::End);
+        };
+
+        let result = match choice.as_str() {
+            "1" => return Ok(super::State::StartNewGame(args)),
+            "2" => resume(&args),
+            "3" => watch_replay(&args),
+            "4" => crate::stats::load().map(|stats| crate::stats::print_stats(&stats)),
+            "5" => crate::profile::edit_settings(),
+            "6" | "q" | "quit" => return Ok(super::State::End),
+            _ => {
+                say!(
+                    "'{choice}' isn't an option, enter a number from 1 to {}",
+                    OPTIONS.len()
+                );
+                Ok(())
+            }
+        };
+        // Whatever went wrong, there's still the rest of the menu
+        if let Err(e) = result {
+            say!("{e:#}");
+        }
+
+        Ok(super::State::MainMenu(args))
+    }
+
+    fn resume(args: &Args) -> anyhow::Result<()> {
+        let Some(saved) = saved_match::load()? else {
+            say!("There's no saved game to resume. Matches of more than one game are saved between games.");
+            return Ok(());
+        };
+        match saved.seats.len() {
+            2 => start_new_game::resume::<2>(saved, args.no_color),
+            3 => start_new_game::resume::<3>(saved, args.no_color),
+            FOUR_PLAYERS => start_new_game::resume::<FOUR_PLAYERS>(saved, args.no_color),
+            n => anyhow::bail!(
+                "the saved match has {n} players, but games have from 2 to {FOUR_PLAYERS}"
+            ),
+        }
+    }
+
+    fn watch_replay(args: &Args) -> anyhow::Result<()> {
+        let profile = Profile::load()?.unwrap_or_default();
+        let latest = profile.replay_dir.as_deref().and_then(latest_replay);
+        let question = match &latest {
+            Some(path) => format!("Which replay? [{}] ", path.display()),
+            None => "Which replay? ".to_owned(),
+        };
+        let path = match (prompt(&question)?, latest) {
+            (Some(answer), _) if !answer.is_empty() => PathBuf::from(answer),
+            (_, Some(latest)) => latest,
+            (_, None) => return Ok(()),
+        };
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("reading replay {}", path.display()))?;
+        crate::watch_replay(&contents, args.no_color)
+    }
+
+    /// Find the most recently saved replay in `dir`.
+    fn latest_replay(dir: &Path) -> Option<PathBuf> {
+        fs::read_dir(dir)
+            .ok()?
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name.starts_with("replay-") && name.ends_with(".txt")
+            })
+            .max_by_key(|entry| entry.metadata().and_then(|it| it.modified()).ok())
+            .map(|entry| entry.path())
+    }
+}
+
 mod start_new_game {
-    use std::env;
+    use std::{
+        cell::RefCell,
+        io::{IsTerminal, Write},
+        path::Path,
+        rc::Rc,
+        thread,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use crate::{
+        input::{self, InputSource},
+        net::{self, gallery::Gallery},
+        personality::Roster,
+        play_game,
+        player::{AiSeat, HumanSeat, Player},
+        profile::Profile,
+        saved_match::SavedMatch,
+        table_setup::{self, SeatSetup, TableSetup},
+        transcript, Args,
+    };
+    use anyhow::Context;
+    use card_games::{
+        card::Card,
+        cho_dai_di::{CardOrdering, ChoDaiDi, GameEvent},
+        collections::{Cards, SortCardsBy},
+        player::{
+            ai::{self, Difficulty},
+            new_ai_players, new_human_player,
+        },
+        r#match::{Match, Rules, Termination},
+        render::CardStyle,
+        score_format::ScoreFormatter,
+    };
+
+    /// The players and settings that stay the same for every game of a match.
+    pub struct Table<const PLAYERS: usize> {
+        pub players: Vec<Box<dyn Player<PLAYERS>>>,
+        pub profile: Profile,
+        pub r#match: Match,
+        /// If true, the people at the table share a keyboard
+        pub hot_seat: bool,
+        /// If true, every seat's played by the computer for someone watching
+        pub spectating: bool,
+        /// Whoever's watching over the network
+        pub gallery: Gallery,
+        pub card_style: CardStyle,
+        pub score_formatter: ScoreFormatter,
+        /// The seed the match's first game is dealt from, if one was chosen. Each game
+        /// after it is dealt from the next seed up.
+        pub seed: Option<u64>,
+        /// The options to go back to the main menu with once the match is over, if it was
+        /// started from there
+        pub menu: Option<Args>,
+        /// If true, the match is saved after each game so that it can be resumed
+        pub saved: bool,
+        /// If true, the person at the keyboard is asked whether to play another game with
+        /// the same players once the match is over
+        pub ask_to_play_again: bool,
+    }
+
+    pub fn tick<const PLAYERS: usize>(args: Args) -> anyhow::Result<super::State<PLAYERS>> {
+        let stdin_is_terminal = std::io::stdin().is_terminal();
+        let mut profile = match Profile::load()? {
+            Some(profile) => profile,
+            // There's no one to answer the onboarding questions, so use the defaults
+            // The host of a server doesn't play, so there's no one to ask, and nor does
+            // a spectator
+            None if args.script.is_some() || args.server || args.spectate || !stdin_is_terminal => {
+                Profile::default()
+            }
+            None => crate::profile::onboard()?,
+        };
+        args.rules.apply(&mut profile);
+        if let Some(preset) = &args.preset {
+            preset.apply(&mut profile);
+        }
+        // The command line takes precedence over the saved profile
+        let player_name = args.name.clone().unwrap_or_else(|| profile.name.clone());
+        let (card_style, score_formatter) = styles(&profile, args.no_color);
+
+        if args.lobby || args.tables > 1 {
+            let (difficulty, games, seed) = (args.difficulty, args.games, args.seed);
+            let rules = profile.rules();
+            let host = move |seats: Vec<net::RemoteSeat>, rules: Rules, gallery: Gallery| {
+                let mut players: Vec<Option<Box<dyn Player<PLAYERS>>>> = Vec::new();
+                for seat in seats {
+                    players.push(Some(Box::new(seat)));
+                }
+                players.resize_with(PLAYERS, || None);
+                let players = seat_ai_players(players, &profile, difficulty)?;
+                let r#match = new_match(&players, rules, games);
+                crate::play_table(Table {
+                    players,
+                    profile: profile.clone(),
+                    r#match,
+                    hot_seat: false,
+                    spectating: false,
+                    gallery,
+                    card_style,
+                    score_formatter: score_formatter.clone(),
+                    seed,
+                    menu: None,
+                    saved: false,
+                    ask_to_play_again: false,
+                })
+            };
+            match args.lobby {
+                true => net::lobby::run::<PLAYERS>(
+                    args.port,
+                    args.websocket,
+                    rules,
+                    move |seats, rules| host(seats, rules, Gallery::default()).map(|_| ()),
+                )?,
+                false => net::tournament::run::<PLAYERS>(
+                    args.port,
+                    args.websocket,
+                    args.tables,
+                    args.humans,
+                    move |seats, gallery| host(seats, rules, gallery),
+                )?,
+            }
+            return Ok(super::State::End);
+        }
+
+        say!("Starting a new {PLAYERS}-player game");
+        let mut seats: Vec<Option<Box<dyn Player<PLAYERS>>>> = Vec::new();
+        let mut hot_seat = false;
+        let gallery = Gallery::default();
+        if args.server {
+            let remote_seats = if args.websocket {
+                net::ws::accept_players::<PLAYERS>(args.port, args.humans, &gallery)?
+            } else {
+                net::accept_players::<PLAYERS>(args.port, args.humans, &gallery)?
+            };
+            for seat in remote_seats {
+                seats.push(Some(Box::new(seat)));
+            }
+        } else if !args.spectate {
+            let setup = match &args.preset {
+                Some(preset) => preset.table(),
+                None => TableSetup::new(PLAYERS, args.humans, &player_name),
+            };
+            // Someone started the game from the menu, so they can choose who sits where
+            let setup = match args.menu {
+                true => table_setup::choose(setup)?,
+                false => setup,
+            };
+            // People sharing a keyboard hand it over between turns. Scripts have no one to
+            // hand it to.
+            hot_seat = setup.humans() > 1 && args.script.is_none() && stdin_is_terminal;
+            seats = seat_setup(
+                setup,
+                &profile,
+                args.script.as_deref(),
+                hot_seat,
+                card_style,
+                &score_formatter,
+            )?;
+        }
+        seats.resize_with(PLAYERS, || None);
+        let players = seat_ai_players(seats, &profile, args.difficulty)?;
+        if let Some(dir) = &profile.transcript_dir {
+            let started_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let path = dir.join(format!("session-{started_at}.txt"));
+            // Hands are only hidden when more than one person is looking at this terminal
+            let humans = players.iter().filter(|player| player.is_human()).count();
+            match transcript::start(&path, humans > 1) {
+                Ok(()) => println!("Saving a transcript to {}", path.display()),
+                Err(e) => println!("A transcript can't be saved: {e:#}"),
+            }
+        }
+
+        let r#match = new_match(&players, profile.rules(), args.games);
+
+        if args.server {
+            say!("Everyone's here. Good luck!");
+        } else if args.spectate {
+            say!("The computer is playing every seat, so sit back and watch.");
+        } else {
+            let mut names: Vec<_> = players
+                .iter()
+                .filter(|player| player.is_human())
+                .map(|player| player.name())
+                .collect();
+            let last = names.pop().unwrap_or_default();
+            let names = match names.is_empty() {
+                true => last.to_owned(),
+                false => format!("{} and {last}", names.join(", ")),
+            };
+            say!("Good luck {names}! Enter \"help\" if you need some guidance.");
+        }
+        if profile.rated {
+            say!("This is a rated game, so hints, shortcuts that pick cards, pass explanations, score previews, confirmations, and revealed hands are off.");
+        }
+
+        deal(Table {
+            players,
+            profile,
+            r#match,
+            hot_seat,
+            spectating: args.spectate,
+            gallery,
+            card_style,
+            score_formatter,
+            seed: args.seed,
+            saved: args.menu,
+            ask_to_play_again: args.menu,
+            menu: args.menu.then_some(args),
+        })
+    }
+
+    /// Seat the players of a saved match again, and play the rest of it.
+    pub fn resume<const PLAYERS: usize>(saved: SavedMatch, no_color: bool) -> anyhow::Result<()> {
+        let profile = Profile::load()?.unwrap_or_default();
+        let (card_style, score_formatter) = styles(&profile, no_color);
+        let humans = saved.seats.iter().filter(|seat| seat.human).count();
+        let hot_seat = humans > 1 && std::io::stdin().is_terminal();
+        let input = open_input(None, &profile)?;
+        // Personalities from the roster are seated as themselves again
+        let personalities = match profile.rotate_ai_personalities {
+            true => Roster::load_or_create()?.personalities,
+            false => Vec::new(),
+        };
+
+        let mut players: Vec<Box<dyn Player<PLAYERS>>> = Vec::new();
+        for (name, seat) in saved.r#match.players().iter().zip(&saved.seats) {
+            if seat.human {
+                players.push(Box::new(human_seat(
+                    name,
+                    &profile,
+                    &input,
+                    hot_seat,
+                    card_style,
+                    &score_formatter,
+                )));
+            } else if let Some(personality) = personalities.iter().find(|it| it.name == *name) {
+                players.push(Box::new(AiSeat::from(personality.to_player())));
+            } else {
+                let ai_player = match seat.difficulty {
+                    Some(difficulty) => ai::Player::with_difficulty(name, difficulty),
+                    None => ai::Player::new(name),
+                };
+                players.push(Box::new(AiSeat::from(ai_player)));
+            }
+        }
+        say!("Picking up where you left off");
+        announce_seats(&players);
+
+        crate::play_table(Table {
+            players,
+            profile,
+            r#match: saved.r#match,
+            hot_seat,
+            spectating: false,
+            gallery: Gallery::default(),
+            card_style,
+            score_formatter,
+            seed: None,
+            menu: None,
+            saved: true,
+            ask_to_play_again: true,
+        })?;
+
+        Ok(())
+    }
+
+    /// How cards and scores are shown, going without color if `no_color` is true.
+    pub fn styles(profile: &Profile, no_color: bool) -> (CardStyle, ScoreFormatter) {
+        let mut card_style = profile.card_style();
+        let mut score_formatter = profile.score_formatter();
+        if no_color {
+            card_style.color = false;
+            score_formatter.color = false;
+        }
+
+        (card_style, score_formatter)
+    }
+
+    /// Open the input that human seats read their moves from. Every human seat reads
+    /// from the same input, taking turns.
+    fn open_input(
+        script: Option<&Path>,
+        profile: &Profile,
+    ) -> anyhow::Result<Rc<RefCell<dyn InputSource>>> {
+        Ok(match (script, &profile.input_terminal) {
+            (Some(path), _) => Rc::new(RefCell::new(input::Script::open(path)?)),
+            (None, Some(path)) => Rc::new(RefCell::new(input::Terminal::open(path)?)),
+            (None, None) => {
+                if !std::io::stdin().is_terminal() {
+                    println!(
+                        "stdin isn't a terminal, so your moves will be read from it line by line"
+                    );
+                }
+                Rc::new(RefCell::new(input::Stdin))
+            }
+        })
+    }
+
+    fn human_seat(
+        name: &str,
+        profile: &Profile,
+        input: &Rc<RefCell<dyn InputSource>>,
+        hot_seat: bool,
+        card_style: CardStyle,
+        score_formatter: &ScoreFormatter,
+    ) -> HumanSeat {
+        let mut human_player = new_human_player(name);
+        human_player.set_sort_cards_by(profile.sort_cards_by);
+        human_player.set_explain_passes(profile.explain_passes);
+        // Moves written beforehand don't have answers to the confirmation in between them
+        human_player.set_confirm_plays(profile.confirm_plays && input.borrow().is_interactive());
+        human_player.set_keybindings(profile.keybindings.clone());
+        let mut seat = HumanSeat::new(human_player, Box::new(Rc::clone(input)));
+        seat.set_hand_off(hot_seat);
+        seat.set_card_style(card_style);
+        seat.set_score_formatter(score_formatter.clone());
+
+        seat
+    }
+
+    /// Seat the people and chosen computer players of a table that's been set up. Seats
+    /// left to any computer player are empty.
+    fn seat_setup<const PLAYERS: usize>(
+        setup: TableSetup,
+        profile: &Profile,
+        script: Option<&Path>,
+        hot_seat: bool,
+        card_style: CardStyle,
+        score_formatter: &ScoreFormatter,
+    ) -> anyhow::Result<Vec<Option<Box<dyn Player<PLAYERS>>>>> {
+        let input = open_input(script, profile)?;
+        let chosen_personality = |seat: &SeatSetup| {
+            matches!(
+                seat,
+                SeatSetup::Computer {
+                    personality: Some(_)
+                }
+            )
+        };
+        let roster = match setup.seats.iter().any(chosen_personality) {
+            true => Some(Roster::load_or_create()?),
+            false => None,
+        };
+
+        let mut seats: Vec<Option<Box<dyn Player<PLAYERS>>>> = Vec::new();
+        for seat in setup.seats {
+            seats.push(match seat {
+                SeatSetup::Human { name } => Some(Box::new(human_seat(
+                    &name,
+                    profile,
+                    &input,
+                    hot_seat,
+                    card_style,
+                    score_formatter,
+                ))),
+                SeatSetup::Computer {
+                    personality: Some(name),
+                } => match roster.as_ref().and_then(|roster| roster.get(&name)) {
+                    Some(personality) => {
+                        say!("{}: \"{}\"", personality.name, personality.greeting);
+                        Some(Box::new(AiSeat::from(personality.to_player())))
+                    }
+                    None => {
+                        say!("{name} has left the roster, so another computer player sits in for them");
+                        None
+                    }
+                },
+                SeatSetup::Computer { personality: None } => None,
+            });
+        }
+
+        Ok(seats)
+    }
+
+    /// Give every empty seat to an AI player, then say who's sitting where.
+    fn seat_ai_players<const PLAYERS: usize>(
+        seats: Vec<Option<Box<dyn Player<PLAYERS>>>>,
+        profile: &Profile,
+        difficulty: Option<Difficulty>,
+    ) -> anyhow::Result<Vec<Box<dyn Player<PLAYERS>>>> {
+        let taken: Vec<_> = seats.iter().flatten().map(|player| player.name()).collect();
+        let empty_seats = seats.iter().filter(|seat| seat.is_none()).count();
+        let mut ai_players: Vec<Box<dyn Player<PLAYERS>>> = Vec::new();
+        if profile.rotate_ai_personalities {
+            let roster = Roster::load_or_create()?;
+            for personality in roster.choose(empty_seats, &taken)? {
+                say!("{}: \"{}\"", personality.name, personality.greeting);
+                ai_players.push(Box::new(AiSeat::from(personality.to_player())));
+            }
+        } else {
+            for mut ai_player in new_ai_players(empty_seats, &taken)? {
+                // The command line takes precedence over the saved profile
+                if let Some(difficulty) = difficulty.or(profile.ai_difficulty) {
+                    ai_player.set_difficulty(difficulty);
+                }
+                ai_players.push(Box::new(AiSeat::from(ai_player)));
+            }
+        }
+        let mut ai_players = ai_players.into_iter();
+        let players: Vec<_> = seats
+            .into_iter()
+            .map(|seat| seat.or_else(|| ai_players.next()))
+            .collect::<Option<_>>()
+            .context("there weren't enough AI players for every empty seat")?;
+        announce_seats(&players);
+
+        Ok(players)
+    }
+
+    /// Say who's sitting where.
+    fn announce_seats<const PLAYERS: usize>(players: &[Box<dyn Player<PLAYERS>>]) {
+        for (seat, player) in players.iter().enumerate() {
+            match player.difficulty() {
+                Some(difficulty) => say!("Seat {}: {} ({difficulty})", seat + 1, player.name()),
+                None => say!("Seat {}: {}", seat + 1, player.name()),
+            }
+        }
+    }
+
+    fn new_match<const PLAYERS: usize>(
+        players: &[Box<dyn Player<PLAYERS>>],
+        rules: Rules,
+        games: usize,
+    ) -> Match {
+        let names = players.iter().map(|it| it.name().to_owned()).collect();
+        Match::new(names, rules, Termination::Games(games))
+    }
+
+    /// Deal the next game of the match.
+    pub fn deal<const PLAYERS: usize>(
+        mut table: Table<PLAYERS>,
+    ) -> anyhow::Result<super::State<PLAYERS>> {
+        if let Termination::Games(games @ 2..) = table.r#match.termination() {
+            say!();
+            say!(
+                "Game {} of {games}. {} deals.",
+                table.r#match.results().len() + 1,
+                table.players[table.r#match.dealer()].name()
+            );
+        }
+
+        // The seed is kept so that the deal can be replayed
+        let seed = match table.seed {
+            Some(seed) => seed.wrapping_add(table.r#match.results().len() as u64),
+            None => rand::random(),
+        };
+        let mut game = table.r#match.new_game_with_seed::<PLAYERS>(seed)?;
+        let names: Vec<_> = table
+            .players
+            .iter()
+            .map(|it| it.name().to_owned())
+            .collect();
+        for (seat, player) in table.players.iter_mut().enumerate() {
+            player.join_game(seat, &names, &mut game)?;
+        }
+        table.gallery.join_game(&names, &mut game);
+        // Penalties are handed out inside the game as someone passes, so they're
+        // announced as they happen
+        if game.rules().pass_card_penalty {
+            let names = names.clone();
+            let card_style = table.card_style;
+            game.on_event(move |event| match event {
+                GameEvent::CardRevealed { player, card } => say!(
+                    "{} could have led, so they reveal {} for passing.",
+                    names[player],
+                    card_style.card(&card)
+                ),
+                GameEvent::PenaltyMarkerDrawn { player, markers } => say!(
+                    "{} could have led, so they draw a penalty marker for passing. They have {markers}.",
+                    names[player]
+                ),
+                _ => {}
+            });
+        }
 
-    use crate::play_game;
-    use card_games::player::{new_ai_player, new_human_player};
+        let Table {
+            players,
+            profile,
+            card_style,
+            ..
+        } = &table;
+        // The dealt hand would stay on screen for everyone sharing the keyboard to see,
+        // and a spectator has no hand of their own
+        let human = players.iter().position(|player| player.is_human());
+        if let Some(human) = human
+            .filter(|_| !profile.deal_delay().is_zero() && !table.hot_seat && !table.spectating)
+        {
+            reveal_hand(
+                game.hands()[human].clone(),
+                &game.ordering(),
+                profile.sort_cards_by,
+                profile.deal_delay(),
+                card_style,
+            )?;
+        }
+
+        let mut first_player = None;
+        if let Some(winner) = game.winner() {
+            say!(
+                "{} was dealt a card of every rank from 3 to A. That's a dragon, and it wins the game!",
+                players[winner].name()
+            );
+        } else if profile.seat_draw {
+            let (drawn, winner) = ChoDaiDi::<PLAYERS>::seat_draw();
+            for (player, card) in players.iter().zip(drawn) {
+                say!("{} draws {}", player.name(), card_style.card(&card));
+            }
+            game.set_first_player(winner)?;
+            first_player = Some(winner);
+            say!(
+                "{} drew the highest card and will go first.",
+                players[winner].name()
+            );
+        } else if game
+            .hands()
+            .iter()
+            .any(|hand| hand.contains(&Card::THREE_OF_DIAMONDS))
+        {
+            say!("The player with the 3♦ will go first.");
+        } else {
+            say!("The 3♦ wasn't dealt, so the player with the lowest card will go first.");
+        }
 
-    pub fn tick() -> anyhow::Result<super::State> {
-        println!("Starting a new four-player game");
-        let player_name = env::var("DAI_DI_PLAYER_NAME").unwrap_or_else(|_| "Player".to_string());
         let play_state = play_game::State {
-            game: card_games::cho_dai_di::new_4p_game(),
-            players: vec![
-                Box::new(new_human_player(&player_name)),
-                Box::new(new_ai_player()),
-                Box::new(new_ai_player()),
-                Box::new(new_ai_player()),
-            ],
+            turn_delay: profile.turn_delay(),
+            score_formatter: table.score_formatter.clone(),
+            reveal_hands_at_game_end: profile.reveal_hands_at_game_end && !game.is_rated(),
+            seed,
+            first_player,
+            // There are no turns to replay after an instant win
+            replay_dir: profile.replay_dir.clone().filter(|_| !game.is_game_ended()),
+            stand_ins: (0..PLAYERS).map(|_| None).collect(),
+            game,
+            table,
         };
         let next_state = super::State::Play(play_state);
-        println!("Good luck Player! Enter \"help\" if you need some guidance.");
-        println!("The player with the 3♦ will go first.");
 
         Ok(next_state)
     }
+
+    /// Show the cards dealt to the human player one at a time.
+    fn reveal_hand<const PLAYERS: usize>(
+        mut hand: Cards<ChoDaiDi<PLAYERS>>,
+        ordering: &CardOrdering,
+        sort_cards_by: SortCardsBy,
+        delay: Duration,
+        card_style: &CardStyle,
+    ) -> anyhow::Result<()> {
+        match sort_cards_by {
+            SortCardsBy::Rank => hand.sort_by_rank_under(ordering),
+            SortCardsBy::Suit => hand.sort_by_suit_under(ordering),
+        }
+
+        print!("Dealing your hand:");
+        for card in hand.iter() {
+            thread::sleep(delay);
+            print!(" {}", card_style.card(card));
+            // Flush so each card appears as it's dealt rather than all at once
+            std::io::stdout().flush()?;
+        }
+        println!();
+        let hand = hand.iter().map(ToString::to_string).collect::<Vec<_>>();
+        transcript::record_hand(&format!("Dealing your hand: {}", hand.join(" ")));
+
+        Ok(())
+    }
 }
 
 mod play_game {
-    use crate::player::{Player, TurnAction};
+    use crate::{
+        player::{AiSeat, Player, TurnAction},
+        start_new_game::Table,
+    };
     use anyhow::Context;
-    use card_games::{cho_dai_di::ChoDaiDi, collections::Cards};
+    use card_games::{
+        cho_dai_di::{self, ChoDaiDi, PlayError, TurnOutcome},
+        collections::Cards,
+        player::new_ai_player,
+        replay::Replay,
+        score_format::ScoreFormatter,
+    };
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        thread,
+        time::Duration,
+    };
 
-    pub struct State {
-        pub game: ChoDaiDi,
-        pub players: Vec<Box<dyn Player>>,
+    pub struct State<const PLAYERS: usize> {
+        pub game: ChoDaiDi<PLAYERS>,
+        pub table: Table<PLAYERS>,
+        /// How long to pause after a computer player's turn
+        pub turn_delay: Duration,
+        pub score_formatter: ScoreFormatter,
+        pub reveal_hands_at_game_end: bool,
+        /// The seed the game was dealt with
+        pub seed: u64,
+        /// The player chosen to go first, if it wasn't the holder of the 3♦
+        pub first_player: Option<usize>,
+        /// If set, a replay of the game is saved to this directory when it ends
+        pub replay_dir: Option<PathBuf>,
+        /// The computer players taking the turns of anyone who's gone away, by seat
+        pub stand_ins: Vec<Option<AiSeat<PLAYERS>>>,
     }
 
-    impl State {
-        fn play_cards(&mut self, cards: Cards<ChoDaiDi>) -> anyhow::Result<()> {
-            self.game.play_cards(cards)
+    impl<const PLAYERS: usize> State<PLAYERS> {
+        fn play_cards(
+            &mut self,
+            cards: Cards<ChoDaiDi<PLAYERS>>,
+        ) -> Result<TurnOutcome, PlayError> {
+            self.game.advance(cho_dai_di::TurnAction::Play(cards))
         }
 
         fn pass(&mut self) {
-            self.game.pass();
+            self.game
+                .advance(cho_dai_di::TurnAction::Pass)
+                .expect("passing is always allowed");
         }
 
         pub fn get_current_player_name(&self) -> Option<&str> {
             let current_player = self.game.whose_turn();
-            self.players.get(current_player).map(|it| it.name())
+            self.table.players.get(current_player).map(|it| it.name())
         }
 
-        fn take_turn(&mut self) -> anyhow::Result<TurnAction> {
+        fn take_turn(&mut self) -> anyhow::Result<TurnAction<PLAYERS>> {
             let current_player = self.game.whose_turn();
             let player = self
+                .table
                 .players
                 .get_mut(current_player)
                 .context("taking turn")?;
             let hand = self.game.get_current_players_hand();
-            player.take_turn(&self.game, hand)
+            if !player.is_absent() {
+                self.stand_ins[current_player] = None;
+                match player.take_turn(&self.game.view(), hand.clone()) {
+                    Err(e) if player.is_absent() => say!("{e:#}"),
+                    result => return result,
+                }
+            }
+
+            let stand_in = self.stand_ins[current_player].get_or_insert_with(|| {
+                say!(
+                    "The computer will play for {} until they're back.",
+                    player.name()
+                );
+                let mut ai_player = new_ai_player();
+                ai_player.set_difficulty(self.table.profile.stand_in_difficulty);
+                AiSeat::from(ai_player)
+            });
+            stand_in.take_turn(&self.game.view(), hand)
+        }
+
+        /// Save a replay of the game to `dir`, returning the replay's path and the code
+        /// it can be shared as.
+        fn save_replay(&self, dir: &Path) -> anyhow::Result<(PathBuf, String)> {
+            let replay = Replay::record(&self.game, self.seed, self.first_player, self.names());
+            fs::create_dir_all(dir)
+                .with_context(|| format!("creating replay directory {}", dir.display()))?;
+            let path = dir.join(format!("replay-{}.txt", self.game.id()));
+            crate::persist::write_atomically(&path, &replay.to_string())?;
+
+            Ok((path, replay.to_base64()))
+        }
+
+        /// The name of the player in each seat
+        fn names(&self) -> Vec<String> {
+            self.table
+                .players
+                .iter()
+                .map(|it| it.name().to_owned())
+                .collect()
         }
 
         fn longest_name_length(&self) -> usize {
-            self.players
+            self.table
+                .players
                 .iter()
                 .map(|it| it.name().len())
                 .max()
@@ -127,21 +1047,57 @@ mod play_game {
         }
     }
 
-    pub fn run(mut state: State) -> anyhow::Result<super::State> {
+    pub fn run<const PLAYERS: usize>(
+        mut state: State<PLAYERS>,
+    ) -> anyhow::Result<super::State<PLAYERS>> {
+        let span = tracing::info_span!("game", game_id = %state.game.id());
+        let _enter = span.enter();
+
         // Pad things out
-        println!();
+        say!();
 
-        if state.game.is_game_ended() {
+        if let Some(winner) = state.game.winner() {
+            state.table.r#match.record_game(&state.game)?;
+            if let Err(e) = crate::stats::record_game(&state.names(), &state.game) {
+                say!("The game couldn't be added to your stats: {e:#}");
+            }
+            if state.table.saved {
+                let table = &state.table;
+                let saved = match table.r#match.is_over() {
+                    true => crate::saved_match::remove(),
+                    false => crate::saved_match::save(&table.r#match, &table.players),
+                };
+                if let Err(e) = saved {
+                    say!("The match couldn't be saved: {e:#}");
+                }
+            }
+            if let Some(dir) = &state.replay_dir {
+                match state.save_replay(dir) {
+                    Ok((path, code)) => {
+                        say!("Saved a replay of the game to {}", path.display());
+                        say!("Share it with: dai-di replay --code {code}");
+                    }
+                    Err(e) => say!("A replay of the game can't be saved: {e:#}"),
+                }
+            }
             let post_game_state = super::post_game::State {
                 longest_name_length: state.longest_name_length(),
-                hand_sizes: state.game.hands().iter().map(|it| it.len()).collect(),
-                players: state.players,
+                winner,
+                scores: state.game.provisional_scores().to_vec(),
+                bonuses: state.game.bonuses(),
+                // Hands stay hidden unless the table has agreed to reveal them
+                remaining_hands: state
+                    .reveal_hands_at_game_end
+                    .then(|| state.game.hands().to_vec()),
+                ordering: state.game.ordering(),
+                table: state.table,
+                score_formatter: state.score_formatter,
             };
             return Ok(super::State::PostGame(post_game_state));
         }
 
-        let mut highest_card_was_played = false;
         let current_player_name = state.get_current_player_name().unwrap().to_owned();
+        let current_player_is_human = state.table.players[state.game.whose_turn()].is_human();
         loop {
             let turn_action = state
                 .take_turn()
@@ -149,56 +1105,81 @@ mod play_game {
             match turn_action {
                 TurnAction::PlayCards(cards) => {
                     // Verify that the player has the cards they want to play.
+                    let shown = state.table.card_style.cards(&cards);
                     if !state.game.current_players_hand_includes(&cards) {
-                        println!("{current_player_name} doesn't have {cards} in their hand so the play is invalid",);
+                        say!("{current_player_name} doesn't have {shown} in their hand so the play is invalid",);
                         continue;
                     }
 
-                    let current_player_name = current_player_name.to_owned();
-                    match state.play_cards(cards.clone()) {
-                        Ok(()) => {
-                            if let Some(card) = state.game.highest_card_still_in_play() {
-                                if cards.contains(card) {
-                                    highest_card_was_played = true;
-                                }
-                            }
-                            state.game.reset_pass_counter();
+                    // A spectator can't see anyone's hand, so they're told what's left
+                    let left = match state.table.spectating {
+                        true => format!(
+                            " ({} left)",
+                            state.game.get_current_players_hand().len() - cards.len()
+                        ),
+                        false => String::new(),
+                    };
+                    match state.play_cards(cards) {
+                        Ok(TurnOutcome::RoundEnded) => {
+                            say!("{current_player_name} plays {shown}{left}, ending the round.")
                         }
+                        Ok(_) => say!("{current_player_name} plays {shown}{left}"),
                         Err(e) => {
-                            println!("can't play '{cards}': {e}");
+                            // The cards weren't played, so they're still part of the hand
+                            say_hand!("can't play '{shown}': {e}");
                             continue;
                         }
                     }
 
-                    if highest_card_was_played {
-                        println!("{current_player_name} plays {cards}, ending the round.");
-                    } else {
-                        println!("{current_player_name} plays {cards}");
-                    }
-
                     break;
                 }
                 TurnAction::Pass => {
-                    println!("{} will pass", current_player_name);
+                    say!("{} will pass", current_player_name);
                     state.pass();
 
-                    // If all players pass, then a new round is started
-                    if state.game.pass_counter() == state.game.number_of_players() - 1 {
-                        state.game.reset_pass_counter();
-                        state.game.unset_last_play();
-                    }
-
                     break;
                 }
+                #[cfg(feature = "dev-cheats")]
+                TurnAction::Cheat(_) if state.game.is_rated() => {
+                    say!("cheats aren't available in rated games");
+                    continue;
+                }
+                #[cfg(feature = "dev-cheats")]
+                TurnAction::Cheat(cheat) => {
+                    // Cheats aren't recorded, so a replay wouldn't match the game
+                    state.replay_dir = None;
+                    match cheat {
+                        crate::player::Cheat::Give(card) => {
+                            let player = state.game.whose_turn();
+                            match state.game.give_card(player, card) {
+                                Ok(()) => say!(
+                                    "{current_player_name} now holds {}",
+                                    state.table.card_style.card(&card)
+                                ),
+                                Err(e) => say!("can't give {card}: {e}"),
+                            }
+                            continue;
+                        }
+                        crate::player::Cheat::Reveal => {
+                            for (i, hand) in state.game.hands().iter().enumerate() {
+                                // Not recorded in the transcript since it shows every
+                                // player's hand
+                                println!("Player {i}: {hand}");
+                            }
+                            continue;
+                        }
+                        crate::player::Cheat::Skip => {
+                            say!("{current_player_name}'s turn is skipped");
+                            state.game.increment_turn_counter();
+                            break;
+                        }
+                    }
+                }
             }
         }
 
-        // If the highest card was played, then a new round is started
-        if highest_card_was_played {
-            state.game.reset_pass_counter();
-            state.game.unset_last_play();
-        } else {
-            state.game.increment_turn_counter();
+        if !current_player_is_human {
+            thread::sleep(state.turn_delay);
         }
 
         Ok(super::State::Play(state))
@@ -206,36 +1187,262 @@ mod play_game {
 }
 
 mod post_game {
-    use crate::player::Player;
-    use card_games::cho_dai_di::hand_size_to_score;
+    use crate::start_new_game::Table;
+    use card_games::{
+        cho_dai_di::{Bonus, CardOrdering, ChoDaiDi},
+        collections::Cards,
+        r#match::{Match, Termination},
+        score_format::ScoreFormatter,
+    };
+    use std::cmp::Reverse;
 
-    pub struct State {
-        pub hand_sizes: Vec<usize>,
-        pub players: Vec<Box<dyn Player>>,
+    pub struct State<const PLAYERS: usize> {
+        /// The seat of the player who played all of their cards
+        pub winner: usize,
+        /// Each player's score for the game, by seat
+        pub scores: Vec<isize>,
+        /// The bonuses that the winner's score includes
+        pub bonuses: Vec<Bonus>,
+        /// Every player's remaining cards, if they're to be revealed
+        pub remaining_hands: Option<Vec<Cards<ChoDaiDi<PLAYERS>>>>,
+        /// The order the game ended in, which the remaining hands are sorted by
+        pub ordering: CardOrdering,
+        pub table: Table<PLAYERS>,
         pub longest_name_length: usize,
+        pub score_formatter: ScoreFormatter,
     }
 
-    pub fn run(state: State) -> anyhow::Result<super::State> {
-        let hand_sizes = state.hand_sizes;
-        let players = state.players;
+    pub fn run<const PLAYERS: usize>(
+        mut state: State<PLAYERS>,
+    ) -> anyhow::Result<super::State<PLAYERS>> {
+        let players = &state.table.players;
         let lnl = state.longest_name_length;
 
-        let player_scores: Vec<_> = hand_sizes.into_iter().map(hand_size_to_score).collect();
-        // The winning player collects the sum of all other players' scores
-        let sum = player_scores.iter().sum::<isize>().abs();
-        let player_scores: Vec<_> = player_scores.into_iter().zip(players).collect();
+        say!("Game over. Let's see the scores:");
+        say!();
 
-        println!("Game over. Let's see the scores:");
-        println!();
+        for (score, player) in state.scores.iter().zip(players) {
+            let score = state.score_formatter.format(*score);
+            say!("\t{:n$}:\t{score}", player.name(), n = lnl);
+        }
+
+        if !state.bonuses.is_empty() {
+            say!();
+            say!("Including bonuses:");
+            for bonus in &state.bonuses {
+                let points = state.score_formatter.format(bonus.points);
+                let player = players[bonus.player].name();
+                say!(
+                    "\t{player} collects {points} from each other player for {}",
+                    bonus.name
+                );
+            }
+        }
 
-        for (score, player) in player_scores.iter() {
-            let score = if *score == 0 { sum } else { *score };
-            println!("\t{:n$}:\t{score:+}", player.name(), n = lnl);
+        if let Some(remaining_hands) = state.remaining_hands {
+            say!();
+            say!("Cards left in hand:");
+            for (seat, (mut hand, player)) in remaining_hands.into_iter().zip(players).enumerate() {
+                if seat == state.winner {
+                    continue;
+                }
+                hand.sort_by_rank_under(&state.ordering);
+                let hand = state.table.card_style.cards(&hand);
+                say!("\t{:n$}:\t{hand}", player.name(), n = lnl);
+            }
         }
 
-        println!();
-        println!("Congratulations {}!", player_scores[0].1.name());
+        say!();
+        say!("Congratulations {}!", players[state.winner].name());
+
+        let Termination::Games(games @ 2..) = state.table.r#match.termination() else {
+            return end_match(state.table);
+        };
+        let played = state.table.r#match.results().len();
+        let mut standings: Vec<_> = state
+            .table
+            .r#match
+            .cumulative_scores()
+            .into_iter()
+            .zip(players)
+            .collect();
+        standings.sort_by_key(|(total, _)| Reverse(*total));
+
+        say!();
+        say!("Standings after {played} of {games} games:");
+        say!();
+        for (place, (total, player)) in standings.iter().enumerate() {
+            let total = state.score_formatter.format(*total);
+            say!("\t{}. {:n$}:\t{total}", place + 1, player.name(), n = lnl);
+        }
+
+        if !state.table.r#match.is_over() {
+            if !offers_to_end_early(&state.table.r#match, state.table.ask_to_play_again)
+                || !offer_to_end_early(standings[0].1.name())?
+            {
+                return Ok(super::State::NextGame(state.table));
+            }
+            state.table.r#match.end_early();
+            if state.table.saved {
+                if let Err(e) = crate::saved_match::remove() {
+                    say!("The saved match couldn't be removed: {e:#}");
+                }
+            }
+        }
+        say!();
+        say!("{} wins the match!", standings[0].1.name());
+
+        end_match(state.table)
+    }
+
+    /// Whether to ask about stopping a match that's been decided. Only someone at the
+    /// keyboard who'd be asked to play again is asked; otherwise the match is played out,
+    /// so that a script's or a pipe's next line is still read as the next move.
+    fn offers_to_end_early(r#match: &Match, ask_to_play_again: bool) -> bool {
+        ask_to_play_again && !r#match.is_over() && r#match.is_decided()
+    }
+
+    /// Ask whether to stop a match that `leader` has already won. Returns true to stop.
+    fn offer_to_end_early(leader: &str) -> anyhow::Result<bool> {
+        say!();
+        say!("No one can catch {leader} in the games that are left.");
+        loop {
+            let answer = crate::prompt("End the match now? (y/n) ")?;
+            match answer.unwrap_or_default().to_ascii_lowercase().as_str() {
+                "y" | "yes" => return Ok(true),
+                // Just pressing Enter, or running out of input, plays on
+                "" | "n" | "no" => return Ok(false),
+                _ => say!("enter 'y' to end the match or 'n' to play on"),
+            }
+        }
+    }
+
+    /// Offer to play on with the same players. Otherwise, go back to the main menu if the
+    /// match was started from there, or end.
+    fn end_match<const PLAYERS: usize>(
+        mut table: Table<PLAYERS>,
+    ) -> anyhow::Result<super::State<PLAYERS>> {
+        if table.ask_to_play_again {
+            say!();
+            loop {
+                let answer = crate::prompt("Play another game with the same players? (y/n) ")?;
+                match answer.unwrap_or_default().to_ascii_lowercase().as_str() {
+                    "y" | "yes" => {
+                        table.r#match.play_another_game();
+                        return Ok(super::State::NextGame(table));
+                    }
+                    // Just pressing Enter, or running out of input, counts as a no
+                    "" | "n" | "no" => break,
+                    _ => say!("enter 'y' to play another game or 'n' to stop"),
+                }
+            }
+        }
+
+        Ok(match table.menu {
+            Some(args) => super::State::MainMenu(args),
+            None => super::State::End,
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::offers_to_end_early;
+        use card_games::r#match::Match;
+
+        #[test]
+        fn test_offers_to_end_early() {
+            // The first of two games was won by more than anyone can make up in the second
+            let decided: Match = serde_json::from_value(serde_json::json!({
+                "id": "00000000-0000-0000-0000-000000000520",
+                "players": ["A", "B", "C", "D"],
+                "rules": {},
+                "termination": { "games": 2 },
+                "results": [{
+                    "game_id": "00000000-0000-0000-0000-000000000001",
+                    "scores": [157, -1, -78, -78],
+                }],
+            }))
+            .unwrap();
+            assert!(decided.is_decided() && !decided.is_over());
+
+            assert!(offers_to_end_early(&decided, true));
+            // A scripted or piped match isn't asked, or the answer would be read from its
+            // next move
+            assert!(!offers_to_end_early(&decided, false));
+
+            let mut over = decided.clone();
+            over.end_early();
+            assert!(!offers_to_end_early(&over, true));
+        }
+    }
+}
+
+mod playback {
+    use anyhow::Context;
+    use card_games::{
+        cho_dai_di::{ChoDaiDi, TurnAction, TurnOutcome},
+        render::CardStyle,
+        replay::Replay,
+    };
+    use std::{thread, time::Duration};
+
+    pub struct State<const PLAYERS: usize> {
+        game: ChoDaiDi<PLAYERS>,
+        names: Vec<String>,
+        actions: std::vec::IntoIter<TurnAction<PLAYERS>>,
+        /// How long to pause after each turn
+        delay: Duration,
+        card_style: CardStyle,
+    }
+
+    impl<const PLAYERS: usize> State<PLAYERS> {
+        pub fn new(
+            replay: Replay<PLAYERS>,
+            delay: Duration,
+            card_style: CardStyle,
+        ) -> anyhow::Result<Self> {
+            say!("Replaying a game between {}", replay.names.join(", "));
+
+            Ok(State {
+                game: replay.new_game()?,
+                names: replay.names,
+                actions: replay.actions.into_iter(),
+                delay,
+                card_style,
+            })
+        }
+    }
+
+    /// Play back one turn of the replay.
+    pub fn run<const PLAYERS: usize>(
+        mut state: State<PLAYERS>,
+    ) -> anyhow::Result<super::State<PLAYERS>> {
+        let Some(action) = state.actions.next() else {
+            say!("That's the end of the replay.");
+            return Ok(super::State::End);
+        };
+
+        let name = state.names[state.game.whose_turn()].clone();
+        let turn = state.game.history().len() + 1;
+        let outcome = state
+            .game
+            .advance(action.clone())
+            .with_context(|| format!("turn {turn} of the replay can't be played"))?;
+        let style = state.card_style;
+        match (action, outcome) {
+            (TurnAction::Play(cards), TurnOutcome::RoundEnded) => {
+                say!("{name} plays {}, ending the round.", style.cards(&cards))
+            }
+            (TurnAction::Play(cards), TurnOutcome::GameEnded) => {
+                say!("{name} plays {} and wins the game!", style.cards(&cards))
+            }
+            (TurnAction::Play(cards), TurnOutcome::TurnTaken) => {
+                say!("{name} plays {}", style.cards(&cards))
+            }
+            (TurnAction::Pass, _) => say!("{name} will pass"),
+        }
+        thread::sleep(state.delay);
 
-        Ok(super::State::End)
+        Ok(super::State::Replay(state))
     }
 }
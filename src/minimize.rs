@@ -0,0 +1,45 @@
+//! Shrinking a replay that makes the game fail into a test case to attach to a bug
+//! report, run with `dai-di minimize`.
+
+use anyhow::Context;
+use card_games::{cho_dai_di::FOUR_PLAYERS, minimize, replay};
+use std::{fs, panic, path::Path};
+
+pub fn run(file: &Path, output: &Path) -> anyhow::Result<()> {
+    let contents =
+        fs::read_to_string(file).with_context(|| format!("reading replay {}", file.display()))?;
+    // Every panic while shrinking is expected, so they'd only fill the terminal
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let shrunk = match replay::number_of_players(&contents) {
+        2 => shrink::<2>(&contents),
+        3 => shrink::<3>(&contents),
+        FOUR_PLAYERS => shrink::<FOUR_PLAYERS>(&contents),
+        n => Err(anyhow::anyhow!(
+            "the replay has {n} players, but games have from 2 to {FOUR_PLAYERS}"
+        )),
+    };
+    panic::set_hook(hook);
+    let (summary, test_case) = shrunk?;
+
+    crate::persist::write_atomically(output, &test_case)?;
+    println!("{summary}");
+    println!("Wrote a test case to {}", output.display());
+
+    Ok(())
+}
+
+/// Shrink the replay, returning a summary of what's left and the test case.
+fn shrink<const PLAYERS: usize>(contents: &str) -> anyhow::Result<(String, String)> {
+    let replay: replay::Replay<PLAYERS> = contents.parse()?;
+    let case = minimize::minimize(&replay)?;
+    let summary = format!(
+        "Shrunk from {} turns to {}, with {} cards in hand; {}",
+        replay.actions.len(),
+        case.actions.len(),
+        case.cards(),
+        case.failure
+    );
+
+    Ok((summary, case.to_string()))
+}
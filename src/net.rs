@@ -0,0 +1,936 @@
+//! Playing over the network, using the protocol in [`card_games::protocol`].
+//!
+//! `dai-di serve` hosts a game and seats each person who connects in place of a human
+//! seat. `dai-di --connect <host:port>` joins a hosted game from another terminal.
+//! `dai-di serve --websocket` hosts a game for WebSocket clients, such as a browser, instead.
+//! Either can be given `--lobby` to let people choose a table to play at, or `--tables`
+//! to play a tournament across several tables at once.
+
+pub mod gallery;
+pub mod lobby;
+pub mod tournament;
+pub mod ws;
+
+#[cfg(test)]
+mod chaos;
+
+use crate::player::{Player, TurnAction};
+use anyhow::Context;
+use card_games::{
+    cho_dai_di::{CardOrdering, ChoDaiDi, GameEvent, GameView},
+    collections::Cards,
+    player::human,
+    protocol::{ClientMessage, ServerMessage},
+    r#match::Rules,
+};
+use gallery::Gallery;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write as _},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    str::FromStr,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long [`Transport::poll`] waits for a message.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The port a server listens on unless `--port` is given.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// How long a player who's lost their connection has to reconnect when it's their turn,
+/// before the computer takes it for them.
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a player has to answer when it's their turn, before the computer takes it
+/// for them.
+const TURN_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The longest line a client may send, in bytes. Every message fits in far less, so a
+/// longer line is refused rather than read into memory without end.
+const MAX_LINE_LEN: usize = 4096;
+
+/// A connection to one client, over which whole messages are exchanged.
+pub trait Transport: Send {
+    fn send(&mut self, text: &str) -> anyhow::Result<()>;
+
+    /// Read the next message. Returns `None` once the client has disconnected.
+    fn receive(&mut self) -> anyhow::Result<Option<String>>;
+
+    /// Read the next message if one arrives within [`POLL_INTERVAL`].
+    fn poll(&mut self) -> anyhow::Result<Polled>;
+}
+
+/// What [`Transport::poll`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Polled {
+    Message(String),
+    /// Nothing's arrived yet
+    Nothing,
+    Disconnected,
+}
+
+/// True if a read failed because it timed out rather than because the connection broke.
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+/// A plain TCP connection with one message per line.
+struct TcpTransport {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    /// The start of a line whose end hadn't arrived when a poll timed out
+    partial: Vec<u8>,
+}
+
+impl TcpTransport {
+    fn new(stream: TcpStream) -> anyhow::Result<Self> {
+        let reader = BufReader::new(stream.try_clone().context("reading a connection")?);
+
+        Ok(TcpTransport {
+            reader,
+            writer: stream,
+            partial: Vec::new(),
+        })
+    }
+
+    /// Read the rest of the current line. Returns `None` at the end of the stream, and
+    /// an error for a line longer than [`MAX_LINE_LEN`].
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        // If this fails, what was read so far is kept in `partial` for next time
+        let room = (MAX_LINE_LEN + 1).saturating_sub(self.partial.len());
+        (&mut self.reader)
+            .take(room as u64)
+            .read_until(b'\n', &mut self.partial)?;
+        if self.partial.is_empty() {
+            return Ok(None);
+        }
+        if self.partial.len() > MAX_LINE_LEN && !self.partial.ends_with(b"\n") {
+            self.partial.clear();
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("a line was longer than {MAX_LINE_LEN} bytes"),
+            ));
+        }
+        let line = String::from_utf8_lossy(&std::mem::take(&mut self.partial))
+            .trim_end_matches(['\r', '\n'])
+            .to_owned();
+
+        Ok(Some(line))
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, text: &str) -> anyhow::Result<()> {
+        writeln!(self.writer, "{text}").context("sending a message")
+    }
+
+    fn receive(&mut self) -> anyhow::Result<Option<String>> {
+        self.read_line().context("reading a message")
+    }
+
+    fn poll(&mut self) -> anyhow::Result<Polled> {
+        let stream = self.reader.get_ref();
+        stream.set_read_timeout(Some(POLL_INTERVAL))?;
+        let read = self.read_line();
+        self.reader.get_ref().set_read_timeout(None)?;
+
+        match read {
+            Ok(Some(line)) => Ok(Polled::Message(line)),
+            Ok(None) => Ok(Polled::Disconnected),
+            Err(e) if is_timeout(&e) => Ok(Polled::Nothing),
+            Err(e) => Err(e).context("reading a message"),
+        }
+    }
+}
+
+/// How messages are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// The line protocol, e.g. `play 9♣ 9♦`
+    Lines,
+    /// JSON objects, e.g. `{"type": "play", "data": ["9♣", "9♦"]}`
+    Json,
+}
+
+impl Encoding {
+    fn encode<T: fmt::Display + Serialize>(self, message: &T) -> String {
+        match self {
+            Encoding::Lines => message.to_string(),
+            Encoding::Json => {
+                serde_json::to_string(message).expect("protocol messages always serialize")
+            }
+        }
+    }
+
+    fn decode<T>(self, text: &str) -> anyhow::Result<T>
+    where
+        T: FromStr<Err = anyhow::Error> + DeserializeOwned,
+    {
+        match self {
+            Encoding::Lines => text.parse(),
+            Encoding::Json => serde_json::from_str(text).context("reading a JSON message"),
+        }
+    }
+}
+
+/// What a [`RemoteSeat`] heard from its client.
+enum Received<const PLAYERS: usize> {
+    Message(ClientMessage<PLAYERS>),
+    /// The client lost its connection and has since reconnected, so it needs to be
+    /// caught up
+    Reconnected,
+}
+
+/// A seat played by someone connected over the network.
+pub struct RemoteSeat {
+    name: String,
+    encoding: Encoding,
+    /// Shared with the callback that sends game events to the client
+    connection: Arc<Mutex<Box<dyn Transport>>>,
+    /// New connections from the client after it's lost its connection, if reconnecting
+    /// is supported
+    reconnections: Option<Receiver<Box<dyn Transport>>>,
+    /// The seat's number and the names at the table, kept to catch up a reconnecting
+    /// client
+    seat: usize,
+    names: Vec<String>,
+    /// True if the client has disconnected or stopped answering, so the computer is
+    /// playing for it
+    absent: bool,
+    /// How long the client has to answer on its turn, and to reconnect once it's lost
+    /// its connection. Tests shorten these.
+    turn_timeout: Duration,
+    reconnect_timeout: Duration,
+}
+
+impl RemoteSeat {
+    fn new(name: String, encoding: Encoding, transport: Box<dyn Transport>) -> Self {
+        RemoteSeat {
+            name,
+            encoding,
+            connection: Arc::new(Mutex::new(transport)),
+            reconnections: None,
+            seat: 0,
+            names: Vec::new(),
+            absent: false,
+            turn_timeout: TURN_TIMEOUT,
+            reconnect_timeout: RECONNECT_TIMEOUT,
+        }
+    }
+
+    fn send<const PLAYERS: usize>(
+        &mut self,
+        message: &ServerMessage<PLAYERS>,
+    ) -> anyhow::Result<()> {
+        let text = self.encoding.encode(message);
+        self.connection
+            .lock()
+            .unwrap()
+            .send(&text)
+            .with_context(|| format!("sending to {}", self.name))
+    }
+
+    /// Read the next message from the client. Messages that can't be understood are
+    /// refused, and the client may try again. If the client disconnects and doesn't
+    /// come back, or doesn't answer within [`TURN_TIMEOUT`], it's marked absent.
+    fn receive<const PLAYERS: usize>(&mut self) -> anyhow::Result<Received<PLAYERS>> {
+        let deadline = Instant::now() + self.turn_timeout;
+        loop {
+            // A failed read is treated like a disconnection, so the client can reconnect
+            let polled = self
+                .connection
+                .lock()
+                .unwrap()
+                .poll()
+                .unwrap_or(Polled::Disconnected);
+            let text = match polled {
+                Polled::Message(text) => text,
+                Polled::Nothing if Instant::now() < deadline => continue,
+                Polled::Nothing => {
+                    self.absent = true;
+                    anyhow::bail!("{} didn't answer in time", self.name);
+                }
+                Polled::Disconnected => {
+                    if self.wait_for_reconnection()? {
+                        return Ok(Received::Reconnected);
+                    }
+                    self.absent = true;
+                    anyhow::bail!("{} disconnected", self.name);
+                }
+            };
+            match self.encoding.decode(&text) {
+                Ok(message) => return Ok(Received::Message(message)),
+                Err(e) => self.send(&ServerMessage::<PLAYERS>::Error(format!("{e:#}")))?,
+            }
+        }
+    }
+
+    /// Wait for the client to reconnect. Returns false if it can't or doesn't in time.
+    fn wait_for_reconnection(&mut self) -> anyhow::Result<bool> {
+        let Some(reconnections) = &self.reconnections else {
+            return Ok(false);
+        };
+        say!(
+            "{} lost their connection. Waiting {} seconds for them to reconnect.",
+            self.name,
+            self.reconnect_timeout.as_secs()
+        );
+        match reconnections.recv_timeout(self.reconnect_timeout) {
+            Ok(transport) => {
+                *self.connection.lock().unwrap() = transport;
+                say!("{} is back.", self.name);
+                Ok(true)
+            }
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => Ok(false),
+        }
+    }
+
+    /// Switch to a connection the client made since it was last heard from, if any.
+    /// Returns true if it did.
+    fn take_reconnection(&mut self) -> bool {
+        let Some(transport) = self
+            .reconnections
+            .as_ref()
+            .and_then(|reconnections| reconnections.try_recv().ok())
+        else {
+            return false;
+        };
+        *self.connection.lock().unwrap() = transport;
+        say!("{} is back.", self.name);
+        true
+    }
+
+    /// Tell a reconnected client which seat it's in and who's at the table.
+    fn catch_up<const PLAYERS: usize>(&mut self) -> anyhow::Result<()> {
+        self.send(&ServerMessage::<PLAYERS>::Welcome {
+            seat: self.seat,
+            players: PLAYERS,
+        })?;
+        for (seat, name) in self.names.clone().into_iter().enumerate() {
+            self.send(&ServerMessage::<PLAYERS>::Seat { seat, name })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const PLAYERS: usize> Player<PLAYERS> for RemoteSeat {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+
+    fn join_game(
+        &mut self,
+        seat: usize,
+        names: &[String],
+        game: &mut ChoDaiDi<PLAYERS>,
+    ) -> anyhow::Result<()> {
+        self.seat = seat;
+        self.names = names.to_vec();
+        self.take_reconnection();
+        let introduced = names
+            .iter()
+            .enumerate()
+            .try_for_each(|(seat, name)| {
+                self.send(&ServerMessage::<PLAYERS>::Seat {
+                    seat,
+                    name: name.clone(),
+                })
+            })
+            .and_then(|()| {
+                let rules = game.rules();
+                self.send(&ServerMessage::<PLAYERS>::Rules {
+                    version: card_games::VERSION.to_owned(),
+                    fingerprint: rules.fingerprint(),
+                    rules: rules.to_map(),
+                })
+            })
+            .and_then(|()| self.send(&ServerMessage::Deal(game.hands()[seat].clone())));
+        // The computer plays for a client that's gone away, so the game carries on
+        if introduced.is_err() {
+            self.absent = true;
+        }
+
+        let connection = Arc::clone(&self.connection);
+        let encoding = self.encoding;
+        // A client that's gone away is noticed on its next turn, so failed sends are
+        // ignored here
+        game.on_event(move |event| {
+            let text = encoding.encode(&ServerMessage::Event(event));
+            let _ = connection.lock().unwrap().send(&text);
+        });
+        // A dragon wins before anyone can subscribe
+        if let Some(winner) = game.winner() {
+            self.send(&ServerMessage::<PLAYERS>::Event(GameEvent::GameEnded {
+                winner,
+            }))?;
+        }
+
+        Ok(())
+    }
+
+    fn is_absent(&mut self) -> bool {
+        if !self.absent {
+            return false;
+        }
+        if self.take_reconnection() {
+            self.absent = self.catch_up::<PLAYERS>().is_err();
+            return self.absent;
+        }
+        // A client that stopped answering is back once it says something. Whatever it
+        // said was meant for a turn that's already been played.
+        let polled = self.connection.lock().unwrap().poll();
+        if let Ok(Polled::Message(_)) = polled {
+            say!("{} is back.", self.name);
+            self.absent = self
+                .send(&ServerMessage::<PLAYERS>::Error(
+                    "you took too long, so the computer played for you".to_owned(),
+                ))
+                .is_err();
+        }
+
+        self.absent
+    }
+
+    fn take_turn(
+        &mut self,
+        view: &GameView<'_, PLAYERS>,
+        hand: Cards<ChoDaiDi<PLAYERS>>,
+    ) -> anyhow::Result<TurnAction<PLAYERS>> {
+        if self.take_reconnection() {
+            self.catch_up::<PLAYERS>()?;
+        }
+        loop {
+            // A connection that's gone away may only be noticed when sending to it, which
+            // is handled the same as when reading from it
+            let prompted = self
+                .send(&ServerMessage::Hand(hand.clone()))
+                .and_then(|()| self.send(&ServerMessage::Turn(view.last_play())));
+            if let Err(e) = prompted {
+                if self.wait_for_reconnection()? {
+                    self.catch_up::<PLAYERS>()?;
+                    continue;
+                }
+                self.absent = true;
+                return Err(e);
+            }
+            // Plays are checked here, since the client can't be told why a play was
+            // refused once it's been returned
+            let refusal = match self.receive()? {
+                Received::Reconnected => {
+                    self.catch_up::<PLAYERS>()?;
+                    continue;
+                }
+                Received::Message(ClientMessage::Pass) => return Ok(TurnAction::Pass),
+                Received::Message(ClientMessage::Play(cards))
+                    if !cards.iter().all(|card| hand.contains(card)) =>
+                {
+                    format!("you don't have {cards}")
+                }
+                Received::Message(ClientMessage::Play(cards)) => match view.is_valid_play(&cards) {
+                    Ok(()) => return Ok(TurnAction::PlayCards(cards)),
+                    Err(e) => format!("can't play {cards}: {e}"),
+                },
+                Received::Message(ClientMessage::Join { .. } | ClientMessage::Resume { .. }) => {
+                    "you've already joined".to_owned()
+                }
+                Received::Message(_) => "the game has already started".to_owned(),
+            };
+            self.send(&ServerMessage::<PLAYERS>::Error(refusal))?;
+        }
+    }
+}
+
+/// Seat someone who's just connected, once they've sent `join`. Returns `None` if they
+/// didn't join properly, in which case they lose their place but the server carries on,
+/// or if they've asked to watch instead.
+fn seat_client<const PLAYERS: usize>(
+    mut seat: RemoteSeat,
+    address: &str,
+    number: usize,
+    gallery: &Gallery,
+) -> anyhow::Result<Option<RemoteSeat>> {
+    match seat.receive::<PLAYERS>() {
+        Ok(Received::Message(ClientMessage::Join { name })) => seat.name = name,
+        Ok(Received::Message(ClientMessage::Watch { name })) => {
+            seat.name = name;
+            gallery.admit::<PLAYERS>(seat);
+            return Ok(None);
+        }
+        Ok(_) => {
+            let _ = seat.send(&ServerMessage::<PLAYERS>::Error(
+                "join before playing".to_owned(),
+            ));
+            return Ok(None);
+        }
+        Err(e) => {
+            say!("{address} couldn't join: {e:#}");
+            return Ok(None);
+        }
+    }
+    seat.seat = number;
+    seat.send(&ServerMessage::<PLAYERS>::Welcome {
+        seat: number,
+        players: PLAYERS,
+    })?;
+    say!(
+        "{} joined from {address} and will play seat {}",
+        seat.name,
+        number + 1
+    );
+
+    Ok(Some(seat))
+}
+
+/// Wait for `count` people to connect on `port`, seating them in the order they join.
+/// Anyone who asks to watch, before or after the game starts, joins `gallery`.
+pub fn accept_players<const PLAYERS: usize>(
+    port: u16,
+    count: usize,
+    gallery: &Gallery,
+) -> anyhow::Result<Vec<RemoteSeat>> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .with_context(|| format!("listening on port {port}"))?;
+    say!("Waiting for {count} player(s) to join on port {port}");
+
+    let mut seats = Vec::with_capacity(count);
+    while seats.len() < count {
+        let (stream, address) = listener.accept().context("accepting a connection")?;
+        let transport = TcpTransport::new(stream)?;
+        let seat = RemoteSeat::new(address.to_string(), Encoding::Lines, Box::new(transport));
+        if let Some(seat) =
+            seat_client::<PLAYERS>(seat, &address.to_string(), seats.len(), gallery)?
+        {
+            seats.push(seat);
+        }
+    }
+    let gallery = gallery.clone();
+    thread::spawn(move || admit_spectators::<PLAYERS>(listener, gallery));
+
+    Ok(seats)
+}
+
+/// Let anyone who connects once the game's started watch it. Each connection is greeted
+/// on its own thread, so a client that's slow to say `watch` only holds up itself.
+fn admit_spectators<const PLAYERS: usize>(listener: TcpListener, gallery: Gallery) {
+    for stream in listener.incoming() {
+        let Ok(transport) = stream
+            .map_err(anyhow::Error::from)
+            .and_then(TcpTransport::new)
+        else {
+            continue;
+        };
+        let gallery = gallery.clone();
+        thread::spawn(move || admit_spectator::<PLAYERS>(transport, &gallery));
+    }
+}
+
+fn admit_spectator<const PLAYERS: usize>(transport: TcpTransport, gallery: &Gallery) {
+    let mut spectator = RemoteSeat::new(String::new(), Encoding::Lines, Box::new(transport));
+    match spectator.receive::<PLAYERS>() {
+        Ok(Received::Message(ClientMessage::Watch { name })) => {
+            spectator.name = name;
+            gallery.admit::<PLAYERS>(spectator);
+        }
+        Ok(_) => {
+            let _ = spectator.send(&ServerMessage::<PLAYERS>::Error(
+                "the game has already started, but you can 'watch' it".to_owned(),
+            ));
+        }
+        Err(_) => {}
+    }
+}
+
+/// Something the terminal client heard.
+enum Input {
+    /// A line from the server
+    Server(String),
+    /// A line typed at this terminal
+    Keyboard(String),
+    /// The server closed the connection
+    Disconnected,
+    /// Nothing more will be typed at this terminal
+    KeyboardClosed,
+}
+
+/// Join a game hosted at `address` and play it from this terminal. If `spectate` is
+/// true, watch it instead.
+pub fn connect(address: &str, name: &str, spectate: bool) -> anyhow::Result<()> {
+    let stream = TcpStream::connect(address).with_context(|| format!("connecting to {address}"))?;
+    let writer = stream.try_clone().context("connecting to the server")?;
+
+    // The server and the keyboard are each read on their own thread, so that lobby
+    // commands can be typed while waiting to hear from the server
+    let (sender, inputs) = mpsc::channel();
+    let keyboard = sender.clone();
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if sender.send(Input::Server(line)).is_err() {
+                return;
+            }
+        }
+        let _ = sender.send(Input::Disconnected);
+    });
+    thread::spawn(move || {
+        for line in io::stdin().lines().map_while(Result::ok) {
+            if keyboard.send(Input::Keyboard(line)).is_err() {
+                return;
+            }
+        }
+        let _ = keyboard.send(Input::KeyboardClosed);
+    });
+
+    let mut client = Client {
+        writer,
+        seat: 0,
+        names: Vec::new(),
+        hand: Cards::from(Vec::new()),
+        ordering: CardOrdering::STANDARD,
+        playing: false,
+        last_play: None,
+        hand_sizes: Vec::new(),
+        typed: VecDeque::new(),
+        keyboard_closed: false,
+    };
+    let name = name.to_owned();
+    client.send(&match spectate {
+        true => ClientMessage::Watch { name },
+        false => ClientMessage::Join { name },
+    })?;
+    for input in inputs {
+        match input {
+            Input::Server(line) => client.hear(&line)?,
+            Input::Keyboard(line) => client.typed.push_back(line),
+            Input::KeyboardClosed => client.keyboard_closed = true,
+            Input::Disconnected => break,
+        }
+        client.answer()?;
+    }
+    println!("The server closed the connection. Thanks for playing.");
+
+    Ok(())
+}
+
+/// The state of the terminal client.
+struct Client {
+    writer: TcpStream,
+    seat: usize,
+    names: Vec<String>,
+    // The number of players isn't known until the server says, but it doesn't change
+    // how messages are read, so they're read as if there were four
+    hand: Cards<ChoDaiDi>,
+    /// The order the hand is sorted in, set by the rules the server sends before each
+    /// deal
+    ordering: CardOrdering,
+    /// False until a game has started, while the client's in the lobby
+    playing: bool,
+    /// If it's the client's turn, the play to beat, if any
+    last_play: Option<Option<Cards<ChoDaiDi>>>,
+    /// How many cards each player holds, if the client is spectating
+    hand_sizes: Vec<usize>,
+    /// Lines typed at the terminal that haven't been answered yet. During a game,
+    /// they're kept until it's the client's turn.
+    typed: VecDeque<String>,
+    keyboard_closed: bool,
+}
+
+impl Client {
+    fn send(&mut self, message: &ClientMessage) -> anyhow::Result<()> {
+        writeln!(self.writer, "{message}").context("sending to the server")
+    }
+
+    fn name_of(&self, seat: usize) -> String {
+        self.names
+            .get(seat)
+            .cloned()
+            .unwrap_or_else(|| format!("Seat {}", seat + 1))
+    }
+
+    /// Show a message from the server.
+    fn hear(&mut self, line: &str) -> anyhow::Result<()> {
+        let message: ServerMessage = match line.parse() {
+            Ok(message) => message,
+            Err(e) => {
+                tracing::warn!("ignoring a message from the server: {e}");
+                return Ok(());
+            }
+        };
+        match message {
+            ServerMessage::Welcome { seat, players } => {
+                self.seat = seat;
+                self.playing = true;
+                self.names = (0..players)
+                    .map(|seat| format!("Seat {}", seat + 1))
+                    .collect();
+                println!(
+                    "Joined as seat {} of {players}. Waiting for the game to start.",
+                    seat + 1
+                );
+            }
+            // Only WebSocket servers let players reconnect
+            ServerMessage::Session { .. } => {}
+            ServerMessage::Seat { seat, name } => {
+                if let Some(it) = self.names.get_mut(seat) {
+                    *it = name;
+                }
+            }
+            ServerMessage::Rules {
+                version,
+                fingerprint,
+                rules,
+            } => {
+                let rules = Rules::from_map(&rules)
+                    .and_then(|rules| rules.check_fingerprint(&fingerprint, &version).map(|()| rules))
+                    .with_context(|| {
+                        format!("the server is running version {version} of the game, so join with that version")
+                    })?;
+                self.ordering.set_suit_precedence(rules.suit_precedence);
+                self.ordering
+                    .set_flush_beats_full_house(rules.flush_beats_full_house);
+            }
+            ServerMessage::Deal(mut cards) => {
+                cards.sort_by_rank_under(&self.ordering);
+                println!();
+                println!("At the table: {}", self.names.join(", "));
+                println!("You were dealt: {cards}");
+                self.hand = cards;
+            }
+            ServerMessage::Hand(mut cards) => {
+                cards.sort_by_rank_under(&self.ordering);
+                self.hand = cards;
+            }
+            ServerMessage::Turn(last_play) => {
+                self.last_play = Some(last_play);
+                self.prompt()?;
+            }
+            ServerMessage::Event(GameEvent::CardsPlayed { player, cards }) => {
+                match self.hand_sizes.get(player) {
+                    Some(left) => println!("{} plays {cards} ({left} left)", self.name_of(player)),
+                    None => println!("{} plays {cards}", self.name_of(player)),
+                }
+            }
+            ServerMessage::Event(GameEvent::PlayerPassed { player }) => {
+                println!("{} will pass", self.name_of(player));
+            }
+            ServerMessage::Event(GameEvent::CardRevealed { player, card }) => {
+                println!(
+                    "{} could have led, so they reveal {card} for passing.",
+                    self.name_of(player)
+                );
+            }
+            ServerMessage::Event(GameEvent::PenaltyMarkerDrawn { player, markers }) => {
+                println!(
+                    "{} could have led, so they draw a penalty marker for passing. They have {markers}.",
+                    self.name_of(player)
+                );
+            }
+            ServerMessage::Event(GameEvent::RoundEnded { leader }) => {
+                println!("The round is over. {} leads.", self.name_of(leader));
+            }
+            ServerMessage::Event(GameEvent::GameEnded { winner }) if !self.hand_sizes.is_empty() => {
+                self.hand_sizes.clear();
+                println!("{} wins the game.", self.name_of(winner));
+            }
+            ServerMessage::Event(GameEvent::GameEnded { winner }) if winner == self.seat => {
+                println!("You win the game!");
+            }
+            ServerMessage::Event(GameEvent::GameEnded { winner }) => {
+                println!("{} wins the game.", self.name_of(winner));
+            }
+            ServerMessage::Error(message) => println!("The server refused that: {message}"),
+            ServerMessage::Tables { count: 0 } => {
+                println!("There are no open tables. Enter 'create <name>' to open one.");
+            }
+            ServerMessage::Tables { .. } => {
+                println!("Open tables (enter 'sit <number>' to sit at one, or 'create <name>' to open another):");
+            }
+            ServerMessage::Table {
+                id,
+                name,
+                seated,
+                seats,
+            } => println!("\t{id}. {name} ({seated} of {seats} seats taken)"),
+            ServerMessage::Seated { table } => println!(
+                "You're sitting at table {table}. The game starts once every seat is taken."
+            ),
+            ServerMessage::Watching { players } => {
+                self.playing = true;
+                self.names = (0..players)
+                    .map(|seat| format!("Seat {}", seat + 1))
+                    .collect();
+                println!("Watching a {players}-player game.");
+            }
+            ServerMessage::HandSizes(hand_sizes) => {
+                // Spectators aren't dealt a hand, so they're told who's playing here
+                if self.hand_sizes.is_empty() {
+                    println!();
+                    println!("At the table: {}", self.names.join(", "));
+                }
+                self.hand_sizes = hand_sizes;
+            }
+            ServerMessage::Host { table } => println!(
+                "You're hosting table {table}. Enter 'start' to start now, with the computer playing any empty seats, or 'confirm' to agree the rules that have been voted for."
+            ),
+            ServerMessage::Proposal {
+                id,
+                name,
+                key,
+                value,
+                yes,
+                no,
+            } => println!(
+                "Proposal {id}, from {name}: play with {key}={value} ({yes} for, {no} against). Enter 'vote {id} yes' or 'vote {id} no'."
+            ),
+            ServerMessage::Agreed(rules) => {
+                let defaults = Rules::default().to_map();
+                let changes: Vec<_> = rules
+                    .iter()
+                    .filter(|(key, value)| defaults.get(*key) != Some(value))
+                    .map(|(key, value)| format!("{key}={value}"))
+                    .collect();
+                match changes.is_empty() {
+                    true => println!("The host confirmed the rules: the standard ones."),
+                    false => println!("The host confirmed the rules: {}.", changes.join(", ")),
+                }
+            }
+            ServerMessage::Standing { place, name, score } => {
+                if place == 1 {
+                    println!();
+                    println!("Every table has finished. The tournament standings:");
+                }
+                println!("\t{place}. {name}: {score}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Answer what's been typed at the terminal, as far as possible for now.
+    fn answer(&mut self) -> anyhow::Result<()> {
+        while !self.playing || self.last_play.is_some() {
+            let Some(line) = self.typed.pop_front() else {
+                break;
+            };
+            match line.trim() {
+                "q" | "quit" => {
+                    println!("Leaving the game. Thanks for playing.");
+                    std::process::exit(0);
+                }
+                input if self.playing => self.answer_turn(input)?,
+                input => self.answer_lobby(input)?,
+            }
+        }
+        if self.playing && self.last_play.is_some() && self.keyboard_closed && self.typed.is_empty()
+        {
+            anyhow::bail!("ran out of moves: stdin has ended");
+        }
+
+        Ok(())
+    }
+
+    /// Show the hand and ask the person at this terminal what they'd like to play.
+    fn prompt(&self) -> anyhow::Result<()> {
+        println!();
+        if let Some(Some(last_play)) = &self.last_play {
+            println!("Last play: {last_play}");
+        }
+        let hand_str = self
+            .hand
+            .iter()
+            .enumerate()
+            .fold(String::new(), |mut s, (i, card)| {
+                write!(s, "[{}] {card} ", i + 1).expect("write to string will never fail");
+                s
+            });
+        println!("Your hand: {hand_str}");
+        print!("Your play: ");
+        std::io::stdout()
+            .flush()
+            .context("flushing 'Your play: ' prompt")
+    }
+
+    fn answer_turn(&mut self, input: &str) -> anyhow::Result<()> {
+        let message = match input {
+            "p" | "pass" => ClientMessage::Pass,
+            "" | "help" => {
+                println!(
+                    "Enter the cards you want to play, e.g. '7C 7D', or their numbers, e.g. '1 2'"
+                );
+                println!("You may pass your turn: enter 'p' or 'pass'");
+                println!("You may leave the game: enter 'q' or 'quit'");
+                return self.prompt();
+            }
+            input => match human::parse_selection(input, &self.hand) {
+                Ok(cards) => ClientMessage::Play(cards),
+                Err(e) => {
+                    println!("invalid input: {e}");
+                    return self.prompt();
+                }
+            },
+        };
+        self.last_play = None;
+        self.send(&message)
+    }
+
+    fn answer_lobby(&mut self, input: &str) -> anyhow::Result<()> {
+        match input.parse::<ClientMessage>() {
+            Ok(
+                message @ (ClientMessage::List
+                | ClientMessage::Create { .. }
+                | ClientMessage::Sit { .. }
+                | ClientMessage::Leave
+                | ClientMessage::Start
+                | ClientMessage::Propose { .. }
+                | ClientMessage::Vote { .. }
+                | ClientMessage::Confirm),
+            ) => self.send(&message)?,
+            Ok(_) => println!("The game hasn't started yet."),
+            Err(_) => {
+                println!("Enter 'list' to see the open tables, 'create <name>' to open one, or 'sit <number>' to sit at one");
+                println!("At a table, you may get up: enter 'leave'. The host may start the game: enter 'start'");
+                println!("Before it starts, you may propose a rule, e.g. 'propose revolutions=true', and vote on proposals, e.g. 'vote 1 yes'. The host locks in the rules voted for: enter 'confirm'");
+                println!("You may leave: enter 'q' or 'quit'");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TcpTransport, MAX_LINE_LEN};
+    use std::{
+        io::Write,
+        net::{TcpListener, TcpStream},
+    };
+
+    #[test]
+    fn test_long_lines_are_refused() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let mut transport = TcpTransport::new(listener.accept().unwrap().0).unwrap();
+
+        let longest = "x".repeat(MAX_LINE_LEN);
+        writeln!(client, "{longest}").unwrap();
+        assert_eq!(transport.read_line().unwrap(), Some(longest));
+
+        writeln!(client, "{}", "x".repeat(MAX_LINE_LEN + 1)).unwrap();
+        assert!(transport.read_line().is_err());
+    }
+}
@@ -0,0 +1,375 @@
+//! A connection for tests that passes messages between a client and the seat it plays
+//! through a proxy that delays, reorders, and drops them, to check that a game carries
+//! on when the network misbehaves.
+//!
+//! Each direction is a queue of messages with the time each one arrives, so nothing waits
+//! on a thread of its own. Faults are drawn from a seeded RNG, so a test that fails does
+//! so the same way again.
+
+use super::{Polled, Transport, POLL_INTERVAL};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// What goes wrong between the client and the server.
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    /// How long each message takes to arrive
+    pub latency: Duration,
+    /// The chance that a message overtakes the one sent just before it
+    pub reordering: f64,
+    /// The connection is cut when the server sends a message this is true of, which is
+    /// lost along with everything else still on its way
+    pub cut_when: Option<fn(&str) -> bool>,
+}
+
+/// Messages on their way in one direction, each with when it arrives, in the order they
+/// arrive.
+#[derive(Default)]
+struct Wire(VecDeque<(Instant, String)>);
+
+struct Link {
+    faults: Faults,
+    rng: SmallRng,
+    to_server: Wire,
+    to_client: Wire,
+    /// True once the connection's been cut, losing whatever was on its way
+    cut: bool,
+    /// True once either end's been dropped. Whatever was already sent still arrives.
+    closed: bool,
+}
+
+/// One end of a connection made with [`connect`].
+pub struct End {
+    link: Arc<Mutex<Link>>,
+    /// True for the server's end
+    server: bool,
+}
+
+/// Connect a client to the server through a proxy that causes `faults`, returning the
+/// server's end and then the client's.
+pub fn connect(faults: Faults, seed: u64) -> (End, End) {
+    let link = Arc::new(Mutex::new(Link {
+        faults,
+        rng: SmallRng::seed_from_u64(seed),
+        to_server: Wire::default(),
+        to_client: Wire::default(),
+        cut: false,
+        closed: false,
+    }));
+    let server = End {
+        link: Arc::clone(&link),
+        server: true,
+    };
+
+    (
+        server,
+        End {
+            link,
+            server: false,
+        },
+    )
+}
+
+impl End {
+    /// The next message to arrive at this end if one has, or `None` if none has yet.
+    fn arrived(&self) -> Option<Polled> {
+        let mut link = self.link.lock().unwrap();
+        if link.cut {
+            return Some(Polled::Disconnected);
+        }
+        let closed = link.closed;
+        let wire = match self.server {
+            true => &mut link.to_server,
+            false => &mut link.to_client,
+        };
+        match wire.0.front() {
+            Some((arrives, _)) if *arrives <= Instant::now() => {
+                wire.0.pop_front().map(|(_, text)| Polled::Message(text))
+            }
+            Some(_) => None,
+            None if closed => Some(Polled::Disconnected),
+            None => None,
+        }
+    }
+}
+
+impl Transport for End {
+    fn send(&mut self, text: &str) -> anyhow::Result<()> {
+        let mut link = self.link.lock().unwrap();
+        let link = &mut *link;
+        if link.cut || link.closed {
+            anyhow::bail!("the connection is closed");
+        }
+        // Like a write to a socket that's about to break, the send itself succeeds
+        if self.server && link.faults.cut_when.is_some_and(|cut_when| cut_when(text)) {
+            link.cut = true;
+            return Ok(());
+        }
+
+        let wire = match self.server {
+            true => &mut link.to_client,
+            false => &mut link.to_server,
+        };
+        let mut arrives = Instant::now() + link.faults.latency;
+        let mut at = wire.0.len();
+        // Only a message still on its way can be overtaken
+        if let Some((before, _)) = wire.0.back() {
+            if link.rng.gen_bool(link.faults.reordering) {
+                arrives = *before;
+                at -= 1;
+            }
+        }
+        wire.0.insert(at, (arrives, text.to_owned()));
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> anyhow::Result<Option<String>> {
+        loop {
+            match self.poll()? {
+                Polled::Message(text) => return Ok(Some(text)),
+                Polled::Nothing => continue,
+                Polled::Disconnected => return Ok(None),
+            }
+        }
+    }
+
+    fn poll(&mut self) -> anyhow::Result<Polled> {
+        if let Some(polled) = self.arrived() {
+            return Ok(polled);
+        }
+        thread::sleep(POLL_INTERVAL);
+
+        Ok(self.arrived().unwrap_or(Polled::Nothing))
+    }
+}
+
+impl Drop for End {
+    /// The other end finds the connection's closed once it's read everything sent to it,
+    /// as it would a socket's.
+    fn drop(&mut self) {
+        self.link.lock().unwrap().closed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{connect, End, Faults};
+    use crate::{
+        net::{Encoding, Polled, RemoteSeat, Transport},
+        player::{AiSeat, Player, TurnAction},
+    };
+    use card_games::{
+        cho_dai_di::{self, CardOrdering, ChoDaiDi, GameEvent},
+        collections::Cards,
+        player::{new_ai_player, new_ai_players},
+        protocol::{ClientMessage, ServerMessage},
+        r#match::{Match, Rules, Termination},
+    };
+    use std::{
+        iter,
+        sync::mpsc::{self, Sender},
+        thread,
+        time::Duration,
+    };
+
+    const SEED: u64 = 509;
+
+    /// How a game went for the client.
+    #[derive(Debug, Default)]
+    struct Outcome {
+        /// True if the client heard the game end
+        finished: bool,
+        /// How many times the client was caught up after reconnecting
+        caught_up: usize,
+        /// How many turns the client answered since it was last caught up
+        answered: usize,
+    }
+
+    /// A client that leads its lowest card and passes otherwise. Once its connection's
+    /// cut, it waits `reconnect_after` and connects again, if given.
+    fn play(
+        mut connection: End,
+        reconnections: Sender<Box<dyn Transport>>,
+        reconnect_after: Option<Duration>,
+    ) -> Outcome {
+        let mut outcome = Outcome::default();
+        let mut hand: Cards<ChoDaiDi> = Cards::from(Vec::new());
+        loop {
+            let text = match connection.poll().unwrap() {
+                Polled::Message(text) => text,
+                Polled::Nothing => continue,
+                Polled::Disconnected => match reconnect_after {
+                    Some(delay) => {
+                        thread::sleep(delay);
+                        let (server, client) = connect(Faults::default(), SEED);
+                        reconnections.send(Box::new(server)).unwrap();
+                        connection = client;
+                        continue;
+                    }
+                    None => return outcome,
+                },
+            };
+            let answer: ClientMessage = match text.parse::<ServerMessage>().unwrap() {
+                ServerMessage::Welcome { .. } => {
+                    outcome.caught_up += 1;
+                    outcome.answered = 0;
+                    continue;
+                }
+                ServerMessage::Deal(cards) | ServerMessage::Hand(cards) => {
+                    hand = cards;
+                    hand.sort_by_rank_under(&CardOrdering::STANDARD);
+                    continue;
+                }
+                ServerMessage::Turn(None) => {
+                    ClientMessage::Play(Cards::from(vec![*hand.first().unwrap()]))
+                }
+                ServerMessage::Turn(Some(_)) => ClientMessage::Pass,
+                ServerMessage::Event(GameEvent::GameEnded { .. }) => {
+                    outcome.finished = true;
+                    return outcome;
+                }
+                _ => continue,
+            };
+            outcome.answered += 1;
+            // A cut connection is noticed on the next poll
+            let _ = connection.send(&Encoding::Lines.encode(&answer));
+        }
+    }
+
+    /// Play a game with `remote` in the first seat and the computer in the rest, the
+    /// way the server does, returning how many turns `remote` played itself.
+    fn host(mut remote: RemoteSeat) -> anyhow::Result<usize> {
+        let mut ais: Vec<AiSeat> = new_ai_players(3, &[])?
+            .into_iter()
+            .map(AiSeat::from)
+            .collect();
+        let names: Vec<_> = iter::once(remote.name.clone())
+            .chain(ais.iter().map(|ai| ai.name().to_owned()))
+            .collect();
+        let r#match = Match::new(names.clone(), Rules::default(), Termination::Games(1));
+        let mut game = r#match.new_game_with_seed(SEED)?;
+        let remote: &mut dyn Player = &mut remote;
+        remote.join_game(0, &names, &mut game)?;
+
+        let mut stand_in = AiSeat::from(new_ai_player());
+        let mut played = 0;
+        while !game.is_game_ended() {
+            let hand = game.get_current_players_hand();
+            let action = match game.whose_turn() {
+                0 if !remote.is_absent() => match remote.take_turn(&game.view(), hand.clone()) {
+                    Ok(action) => {
+                        played += 1;
+                        action
+                    }
+                    Err(_) if remote.is_absent() => stand_in.take_turn(&game.view(), hand)?,
+                    Err(e) => return Err(e),
+                },
+                0 => stand_in.take_turn(&game.view(), hand)?,
+                seat => ais[seat - 1].take_turn(&game.view(), hand)?,
+            };
+            game.advance(match action {
+                TurnAction::PlayCards(cards) => cho_dai_di::TurnAction::Play(cards),
+                TurnAction::Pass => cho_dai_di::TurnAction::Pass,
+                #[cfg(feature = "dev-cheats")]
+                TurnAction::Cheat(_) => unreachable!("only people cheat"),
+            })?;
+        }
+
+        Ok(played)
+    }
+
+    /// Play a game over a connection with `faults`, with the client in the first seat.
+    /// Returns how many turns the server heard from the client and how the game went
+    /// for the client.
+    fn play_through(
+        faults: Faults,
+        reconnect_after: Option<Duration>,
+        timeouts: (Duration, Duration),
+    ) -> (usize, Outcome) {
+        let (server, client) = connect(faults, SEED);
+        let (reconnections, reconnected) = mpsc::channel();
+        let mut remote = RemoteSeat::new("Client".to_owned(), Encoding::Lines, Box::new(server));
+        remote.reconnections = Some(reconnected);
+        (remote.turn_timeout, remote.reconnect_timeout) = timeouts;
+
+        let client = thread::spawn(move || play(client, reconnections, reconnect_after));
+        let played = host(remote).unwrap();
+
+        (played, client.join().unwrap())
+    }
+
+    const TIMEOUTS: (Duration, Duration) = (Duration::from_secs(5), Duration::from_secs(5));
+
+    #[test]
+    fn test_game_survives_latency_and_reordering() {
+        let faults = Faults {
+            latency: Duration::from_millis(5),
+            reordering: 0.3,
+            cut_when: None,
+        };
+        let (played, outcome) = play_through(faults, None, TIMEOUTS);
+        assert!(outcome.finished);
+        assert_eq!(outcome.caught_up, 0);
+        assert!(played > 0);
+    }
+
+    #[test]
+    fn test_reconnecting_during_a_prompt() {
+        let faults = Faults {
+            cut_when: Some(|text| text.starts_with("turn")),
+            ..Faults::default()
+        };
+        let (played, outcome) = play_through(faults, Some(Duration::ZERO), TIMEOUTS);
+        assert!(outcome.finished);
+        assert_eq!(outcome.caught_up, 1);
+        // The turn that was cut off is asked for again rather than played by the computer
+        assert!(outcome.answered > 0);
+        assert_eq!(played, outcome.answered);
+    }
+
+    #[test]
+    fn test_reconnecting_during_an_ai_turn() {
+        // Cut while the computer in the next seat plays, and reconnect after the game's
+        // come back around, so the client's missed the start of its turn
+        let faults = Faults {
+            cut_when: Some(|text| text.starts_with("played 1 ") || text == "passed 1"),
+            ..Faults::default()
+        };
+        let reconnect_after = Some(Duration::from_millis(100));
+        let (played, outcome) = play_through(faults, reconnect_after, TIMEOUTS);
+        assert!(outcome.finished);
+        assert_eq!(outcome.caught_up, 1);
+        assert!(outcome.answered > 0);
+        assert!(played >= outcome.answered);
+    }
+
+    #[test]
+    fn test_computer_plays_for_a_client_that_doesnt_reconnect() {
+        let faults = Faults {
+            cut_when: Some(|text| text.starts_with("turn")),
+            ..Faults::default()
+        };
+        let timeouts = (Duration::from_secs(5), Duration::from_millis(100));
+        let (played, outcome) = play_through(faults, None, timeouts);
+        assert!(!outcome.finished);
+        assert_eq!(played, 0);
+    }
+
+    #[test]
+    fn test_computer_plays_for_a_client_that_doesnt_answer_in_time() {
+        let faults = Faults {
+            latency: Duration::from_millis(150),
+            ..Faults::default()
+        };
+        let timeouts = (Duration::from_millis(100), Duration::from_secs(5));
+        let (_, outcome) = play_through(faults, None, timeouts);
+        // Every answer arrives too late, but the game carries on without waiting for them
+        assert!(outcome.finished);
+    }
+}
@@ -0,0 +1,98 @@
+//! Spectators, who are sent everything that happens at the table but never take a turn.
+
+use super::RemoteSeat;
+use card_games::{
+    cho_dai_di::{ChoDaiDi, GameEvent},
+    collections::Cards,
+    protocol::ServerMessage,
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Audience {
+    /// The connections of the people watching. They're never asked for a turn.
+    spectators: Vec<RemoteSeat>,
+    names: Vec<String>,
+    /// How many cards each player holds, by seat
+    hand_sizes: Vec<usize>,
+}
+
+impl Audience {
+    /// Send a message to every spectator, letting go of any who've disconnected.
+    fn broadcast<const PLAYERS: usize>(&mut self, message: &ServerMessage<PLAYERS>) {
+        self.spectators.retain_mut(|spectator| {
+            let sent = spectator.send(message).is_ok();
+            if !sent {
+                say!("{} stopped watching", spectator.name);
+            }
+            sent
+        });
+    }
+}
+
+/// The people watching a table's games. Clones share the same spectators.
+#[derive(Clone, Default)]
+pub struct Gallery {
+    audience: Arc<Mutex<Audience>>,
+}
+
+impl Gallery {
+    /// Let someone watch, catching them up on who's playing and how many cards they hold.
+    pub fn admit<const PLAYERS: usize>(&self, mut spectator: RemoteSeat) {
+        let mut audience = self.audience.lock().unwrap();
+        let mut catch_up = vec![ServerMessage::<PLAYERS>::Watching { players: PLAYERS }];
+        catch_up.extend(audience.names.iter().enumerate().map(|(seat, name)| {
+            ServerMessage::Seat {
+                seat,
+                name: name.clone(),
+            }
+        }));
+        if !audience.hand_sizes.is_empty() {
+            catch_up.push(ServerMessage::HandSizes(audience.hand_sizes.clone()));
+        }
+        if catch_up
+            .iter()
+            .all(|message| spectator.send(message).is_ok())
+        {
+            say!("{} is watching", spectator.name);
+            audience.spectators.push(spectator);
+        }
+    }
+
+    /// Tell the spectators about a game that's just been dealt, and follow it as it's
+    /// played.
+    pub fn join_game<const PLAYERS: usize>(&self, names: &[String], game: &mut ChoDaiDi<PLAYERS>) {
+        {
+            let mut audience = self.audience.lock().unwrap();
+            audience.names = names.to_vec();
+            audience.hand_sizes = game.hands().iter().map(Cards::len).collect();
+            for (seat, name) in names.iter().enumerate() {
+                audience.broadcast(&ServerMessage::<PLAYERS>::Seat {
+                    seat,
+                    name: name.clone(),
+                });
+            }
+            let hand_sizes = audience.hand_sizes.clone();
+            audience.broadcast(&ServerMessage::<PLAYERS>::HandSizes(hand_sizes));
+            // A dragon wins before anyone can subscribe
+            if let Some(winner) = game.winner() {
+                audience.broadcast(&ServerMessage::<PLAYERS>::Event(GameEvent::GameEnded {
+                    winner,
+                }));
+            }
+        }
+
+        let audience = Arc::clone(&self.audience);
+        game.on_event(move |event| {
+            let mut audience = audience.lock().unwrap();
+            // Sizes are sent first, so a play can be shown along with what's left
+            if let GameEvent::CardsPlayed { player, cards } = &event {
+                let left = audience.hand_sizes[*player].saturating_sub(cards.len());
+                audience.hand_sizes[*player] = left;
+                let hand_sizes = audience.hand_sizes.clone();
+                audience.broadcast(&ServerMessage::<PLAYERS>::HandSizes(hand_sizes));
+            }
+            audience.broadcast(&ServerMessage::Event(event));
+        });
+    }
+}
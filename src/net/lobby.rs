@@ -0,0 +1,652 @@
+//! A lobby where people who connect to a server choose a table to play at.
+//!
+//! Anyone in the lobby may open a table, and hosts it until they get up, when whoever's
+//! been sitting there longest takes over. A table's game starts once every seat is
+//! taken, when its host says so, or after it's been open for [`FILL_TIMEOUT`], with AI
+//! players in any empty seats. Each game is played on its own thread, so the lobby
+//! stays open while it's played.
+//!
+//! Until then, the people at a table may propose and vote on rules to play by. Once the
+//! host confirms them, the proposals with more votes for than against are locked in for
+//! the table's match; otherwise it's played by the server's rules.
+
+use super::{ws, Encoding, Polled, RemoteSeat, TcpTransport, Transport, POLL_INTERVAL};
+use anyhow::{bail, Context};
+use card_games::{
+    protocol::{ClientMessage, ServerMessage},
+    r#match::Rules,
+};
+use std::{
+    collections::BTreeMap,
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long a table waits for people before its empty seats are given to AI players.
+const FILL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Plays a match with the people seated at a table, in seat order, by the rules they
+/// agreed.
+pub type StartGame = dyn Fn(Vec<RemoteSeat>, Rules) -> anyhow::Result<()> + Send + Sync;
+
+/// Someone connected to the lobby.
+struct Guest {
+    seat: RemoteSeat,
+    /// False until they've sent `join`
+    joined: bool,
+}
+
+impl Guest {
+    /// Send a message. A connection that's gone away is noticed when it's next polled,
+    /// so failures are ignored.
+    fn tell<const PLAYERS: usize>(&mut self, message: &ServerMessage<PLAYERS>) {
+        let _ = self.seat.send(message);
+    }
+
+    fn hear<const PLAYERS: usize>(&mut self) -> Heard<PLAYERS> {
+        let polled = self.seat.connection.lock().unwrap().poll();
+        match polled {
+            Ok(Polled::Message(text)) => match self.seat.encoding.decode(&text) {
+                Ok(message) => Heard::Message(message),
+                Err(e) => {
+                    self.tell(&ServerMessage::<PLAYERS>::Error(format!("{e:#}")));
+                    Heard::Nothing
+                }
+            },
+            Ok(Polled::Nothing) => Heard::Nothing,
+            Ok(Polled::Disconnected) | Err(_) => Heard::Left,
+        }
+    }
+}
+
+/// What a [`Guest`] said since they were last polled.
+enum Heard<const PLAYERS: usize> {
+    Message(ClientMessage<PLAYERS>),
+    Nothing,
+    /// They disconnected
+    Left,
+}
+
+/// A table waiting for its game to start.
+struct Room {
+    id: usize,
+    name: String,
+    /// The people sitting at the table, in the order they sat. The first is the host.
+    guests: Vec<Guest>,
+    opened_at: Instant,
+    /// True once the host has asked to start
+    starting: bool,
+    /// The rules proposed at the table, numbered from 1
+    proposals: Vec<Proposal>,
+    /// The rules the host confirmed, if they have
+    agreed: Option<Rules>,
+}
+
+/// A rule that someone at a table proposed playing by.
+struct Proposal {
+    name: String,
+    key: String,
+    value: String,
+    /// How each person voted, by name. The votes of anyone who's left aren't counted.
+    votes: BTreeMap<String, bool>,
+}
+
+struct Lobby<const PLAYERS: usize> {
+    /// People who've connected and been greeted, to be let into the lobby
+    arrivals: Receiver<Guest>,
+    /// People who aren't sitting at a table
+    wandering: Vec<Guest>,
+    rooms: Vec<Room>,
+    next_id: usize,
+    /// The rules that tables play by, unless they agree on others
+    rules: Rules,
+    start_game: Arc<StartGame>,
+}
+
+/// Open a lobby on `port`, running until the server's stopped. If `websocket` is true,
+/// people connect over WebSockets and send JSON messages.
+pub fn run<const PLAYERS: usize>(
+    port: u16,
+    websocket: bool,
+    rules: Rules,
+    start_game: impl Fn(Vec<RemoteSeat>, Rules) -> anyhow::Result<()> + Send + Sync + 'static,
+) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .with_context(|| format!("listening on port {port}"))?;
+    if websocket {
+        say!("The lobby is open at ws://<this machine>:{port}");
+    } else {
+        say!("The lobby is open on port {port}");
+    }
+
+    let (sender, arrivals) = mpsc::channel();
+    thread::spawn(move || welcome_arrivals(listener, websocket, sender));
+
+    let mut lobby = Lobby::<PLAYERS> {
+        arrivals,
+        wandering: Vec::new(),
+        rooms: Vec::new(),
+        next_id: 1,
+        rules,
+        start_game: Arc::new(start_game),
+    };
+    loop {
+        lobby.admit()?;
+        if lobby.wandering.is_empty() && lobby.rooms.is_empty() {
+            // There's no one to poll, which would otherwise pace the loop
+            thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+        lobby.listen();
+        lobby.start_games();
+    }
+}
+
+/// Greet everyone who connects, each on their own thread, and send them to the lobby
+/// once they're ready. A client that's slow to finish its handshake only holds up its
+/// own connection.
+fn welcome_arrivals(listener: TcpListener, websocket: bool, arrivals: Sender<Guest>) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let arrivals = arrivals.clone();
+        thread::spawn(move || {
+            let address = stream
+                .peer_addr()
+                .map_or_else(|_| "someone".to_owned(), |address| address.to_string());
+            match greet(stream, address.clone(), websocket) {
+                // If the lobby's closed, there's nowhere to send them
+                Ok(guest) => {
+                    let _ = arrivals.send(guest);
+                }
+                Err(e) => say!("{address} couldn't join: {e:#}"),
+            }
+        });
+    }
+}
+
+/// Set up the connection of someone who's just arrived.
+fn greet(stream: TcpStream, address: String, websocket: bool) -> anyhow::Result<Guest> {
+    let seat = if websocket {
+        RemoteSeat::new(address, Encoding::Json, Box::new(ws::handshake(stream)?))
+    } else {
+        let transport: Box<dyn Transport> = Box::new(TcpTransport::new(stream)?);
+        RemoteSeat::new(address, Encoding::Lines, transport)
+    };
+
+    Ok(Guest {
+        seat,
+        joined: false,
+    })
+}
+
+impl<const PLAYERS: usize> Lobby<PLAYERS> {
+    /// Let in everyone who's been greeted since the last time.
+    fn admit(&mut self) -> anyhow::Result<()> {
+        loop {
+            match self.arrivals.try_recv() {
+                Ok(guest) => self.wandering.push(guest),
+                Err(TryRecvError::Empty) => return Ok(()),
+                Err(TryRecvError::Disconnected) => bail!("the lobby stopped listening"),
+            }
+        }
+    }
+
+    /// Answer everyone who's said something, in the lobby or at a table.
+    fn listen(&mut self) {
+        let mut i = 0;
+        while i < self.wandering.len() {
+            match self.wandering[i].hear::<PLAYERS>() {
+                Heard::Nothing => i += 1,
+                Heard::Left => {
+                    let guest = self.wandering.remove(i);
+                    if guest.joined {
+                        say!("{} left the lobby", guest.seat.name);
+                    }
+                }
+                Heard::Message(message) => {
+                    let guest = self.wandering.remove(i);
+                    if let Some(guest) = self.wander(guest, message) {
+                        self.wandering.insert(i, guest);
+                        i += 1;
+                    }
+                }
+            }
+        }
+
+        for room in 0..self.rooms.len() {
+            let mut i = 0;
+            while i < self.rooms[room].guests.len() {
+                let refusal = match self.rooms[room].guests[i].hear::<PLAYERS>() {
+                    Heard::Nothing => None,
+                    Heard::Left => {
+                        let guest = self.get_up(room, i);
+                        say!("{} left the lobby", guest.seat.name);
+                        continue;
+                    }
+                    Heard::Message(ClientMessage::Leave) => {
+                        let mut guest = self.get_up(room, i);
+                        for message in self.tables() {
+                            guest.tell(&message);
+                        }
+                        self.wandering.push(guest);
+                        continue;
+                    }
+                    Heard::Message(ClientMessage::List) => {
+                        for message in self.tables() {
+                            self.rooms[room].guests[i].tell(&message);
+                        }
+                        None
+                    }
+                    Heard::Message(ClientMessage::Start) if i == 0 => {
+                        self.rooms[room].starting = true;
+                        None
+                    }
+                    Heard::Message(ClientMessage::Start) => {
+                        Some("only the host can start the game".to_owned())
+                    }
+                    Heard::Message(ClientMessage::Propose { key, value }) => {
+                        self.rooms[room].propose::<PLAYERS>(i, key, value)
+                    }
+                    Heard::Message(ClientMessage::Vote { proposal, agree }) => {
+                        self.rooms[room].vote::<PLAYERS>(i, proposal, agree)
+                    }
+                    Heard::Message(ClientMessage::Confirm) if i == 0 => {
+                        self.rooms[room].confirm::<PLAYERS>(self.rules)
+                    }
+                    Heard::Message(ClientMessage::Confirm) => {
+                        Some("only the host can confirm the rules".to_owned())
+                    }
+                    Heard::Message(
+                        ClientMessage::Join { .. }
+                        | ClientMessage::Create { .. }
+                        | ClientMessage::Sit { .. }
+                        | ClientMessage::Watch { .. },
+                    ) => Some("you're already sitting at a table, so 'leave' it first".to_owned()),
+                    Heard::Message(
+                        ClientMessage::Play(_) | ClientMessage::Pass | ClientMessage::Resume { .. },
+                    ) => Some("the game hasn't started yet".to_owned()),
+                };
+                if let Some(refusal) = refusal {
+                    self.rooms[room].guests[i].tell(&ServerMessage::<PLAYERS>::Error(refusal));
+                }
+                i += 1;
+            }
+        }
+
+        self.rooms.retain(|room| {
+            if room.guests.is_empty() {
+                say!("Table {} closed", room.id);
+            }
+            !room.guests.is_empty()
+        });
+    }
+
+    /// Answer someone who isn't sitting at a table. Returns them if they're still in the
+    /// lobby afterwards.
+    fn wander(&mut self, mut guest: Guest, message: ClientMessage<PLAYERS>) -> Option<Guest> {
+        let refusal = match message {
+            ClientMessage::Join { name } if !guest.joined => {
+                say!("{name} joined the lobby from {}", guest.seat.name);
+                guest.seat.name = name;
+                guest.joined = true;
+                for message in self.tables() {
+                    guest.tell(&message);
+                }
+                None
+            }
+            ClientMessage::Watch { .. } => Some("games in the lobby can't be watched".to_owned()),
+            _ if !guest.joined => Some("join before doing anything else".to_owned()),
+            ClientMessage::Join { .. } => Some("you've already joined".to_owned()),
+            ClientMessage::List => {
+                for message in self.tables() {
+                    guest.tell(&message);
+                }
+                None
+            }
+            ClientMessage::Create { name } => {
+                let id = self.next_id;
+                self.next_id += 1;
+                say!("{} opened table {id}, {name}", guest.seat.name);
+                guest.tell(&ServerMessage::<PLAYERS>::Seated { table: id });
+                guest.tell(&ServerMessage::<PLAYERS>::Host { table: id });
+                self.rooms.push(Room {
+                    id,
+                    name,
+                    guests: vec![guest],
+                    opened_at: Instant::now(),
+                    starting: false,
+                    proposals: Vec::new(),
+                    agreed: None,
+                });
+                return None;
+            }
+            ClientMessage::Sit { table } => match self.rooms.iter_mut().find(|it| it.id == table) {
+                Some(room) if room.guests.len() < PLAYERS => {
+                    say!("{} sat at table {table}", guest.seat.name);
+                    guest.tell(&ServerMessage::<PLAYERS>::Seated { table });
+                    for message in room.negotiation::<PLAYERS>() {
+                        guest.tell(&message);
+                    }
+                    room.guests.push(guest);
+                    return None;
+                }
+                Some(_) => Some(format!("table {table} is full")),
+                None => Some(format!("there's no table {table}")),
+            },
+            ClientMessage::Leave
+            | ClientMessage::Start
+            | ClientMessage::Propose { .. }
+            | ClientMessage::Vote { .. }
+            | ClientMessage::Confirm => Some("you're not sitting at a table".to_owned()),
+            ClientMessage::Resume { .. } => Some("games in the lobby can't be resumed".to_owned()),
+            ClientMessage::Play(_) | ClientMessage::Pass => {
+                Some("you're not playing a game".to_owned())
+            }
+        };
+        if let Some(refusal) = refusal {
+            guest.tell(&ServerMessage::<PLAYERS>::Error(refusal));
+        }
+
+        Some(guest)
+    }
+
+    /// Take someone away from a table. If they were its host, the next person to have sat
+    /// there takes over.
+    fn get_up(&mut self, room: usize, i: usize) -> Guest {
+        let room = &mut self.rooms[room];
+        let guest = room.guests.remove(i);
+        if i == 0 {
+            if let Some(host) = room.guests.first_mut() {
+                say!("{} is now hosting table {}", host.seat.name, room.id);
+                host.tell(&ServerMessage::<PLAYERS>::Host { table: room.id });
+            }
+        }
+
+        guest
+    }
+
+    /// Describe the tables that are waiting for players.
+    fn tables(&self) -> Vec<ServerMessage<PLAYERS>> {
+        let mut messages = vec![ServerMessage::Tables {
+            count: self.rooms.len(),
+        }];
+        messages.extend(self.rooms.iter().map(|room| ServerMessage::Table {
+            id: room.id,
+            name: room.name.clone(),
+            seated: room.guests.len(),
+            seats: PLAYERS,
+        }));
+
+        messages
+    }
+
+    /// Start the games at every table that's ready.
+    fn start_games(&mut self) {
+        let mut i = 0;
+        while i < self.rooms.len() {
+            let room = &self.rooms[i];
+            let ready = room.guests.len() == PLAYERS
+                || room.starting
+                || room.opened_at.elapsed() >= FILL_TIMEOUT;
+            if ready {
+                let room = self.rooms.remove(i);
+                self.start(room);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn start(&self, room: Room) {
+        say!(
+            "The game at table {}, {}, is starting with {} player(s)",
+            room.id,
+            room.name,
+            room.guests.len()
+        );
+        let rules = room.agreed.unwrap_or(self.rules);
+        let seats = room
+            .guests
+            .into_iter()
+            .enumerate()
+            .map(|(seat, mut guest)| {
+                guest.tell(&ServerMessage::<PLAYERS>::Welcome {
+                    seat,
+                    players: PLAYERS,
+                });
+                guest.seat
+            })
+            .collect();
+        let start_game = Arc::clone(&self.start_game);
+        let id = room.id;
+        thread::spawn(move || {
+            if let Err(e) = start_game(seats, rules) {
+                say!("The game at table {id} ended early: {e:#}");
+            }
+        });
+    }
+}
+
+impl Room {
+    fn tell_everyone<const PLAYERS: usize>(&mut self, message: &ServerMessage<PLAYERS>) {
+        for guest in &mut self.guests {
+            guest.tell(message);
+        }
+    }
+
+    /// Describe proposal `id`, counting the votes of everyone still at the table.
+    fn proposal<const PLAYERS: usize>(&self, id: usize) -> ServerMessage<PLAYERS> {
+        let proposal = &self.proposals[id - 1];
+        let (yes, no) = self.tally(proposal);
+        ServerMessage::Proposal {
+            id,
+            name: proposal.name.clone(),
+            key: proposal.key.clone(),
+            value: proposal.value.clone(),
+            yes,
+            no,
+        }
+    }
+
+    /// The number of votes for and against a proposal by the people at the table.
+    fn tally(&self, proposal: &Proposal) -> (usize, usize) {
+        let votes = self
+            .guests
+            .iter()
+            .filter_map(|guest| proposal.votes.get(&guest.seat.name));
+        votes.fold((0, 0), |(yes, no), agree| match agree {
+            true => (yes + 1, no),
+            false => (yes, no + 1),
+        })
+    }
+
+    /// What someone who sits at the table needs to be told to take part in agreeing
+    /// its rules.
+    fn negotiation<const PLAYERS: usize>(&self) -> Vec<ServerMessage<PLAYERS>> {
+        let mut messages: Vec<_> = (1..=self.proposals.len())
+            .map(|id| self.proposal(id))
+            .collect();
+        messages.extend(
+            self.agreed
+                .map(|rules| ServerMessage::Agreed(rules.to_map())),
+        );
+
+        messages
+    }
+
+    /// Propose a rule on behalf of the guest at `i`, who votes for it. Returns why it
+    /// was refused, if it was.
+    fn propose<const PLAYERS: usize>(
+        &mut self,
+        i: usize,
+        key: String,
+        value: String,
+    ) -> Option<String> {
+        if self.agreed.is_some() {
+            return Some("the rules have already been agreed".to_owned());
+        }
+        let setting = BTreeMap::from([(key.clone(), value.clone())]);
+        if let Err(e) = Rules::from_map(&setting) {
+            return Some(format!("{e:#}"));
+        }
+
+        let name = self.guests[i].seat.name.clone();
+        say!("{name} proposed {key}={value} at table {}", self.id);
+        self.proposals.push(Proposal {
+            name: name.clone(),
+            key,
+            value,
+            votes: BTreeMap::from([(name, true)]),
+        });
+        let proposal = self.proposal(self.proposals.len());
+        self.tell_everyone::<PLAYERS>(&proposal);
+        None
+    }
+
+    /// Record the vote of the guest at `i`, replacing any they cast before. Returns why
+    /// it was refused, if it was.
+    fn vote<const PLAYERS: usize>(&mut self, i: usize, id: usize, agree: bool) -> Option<String> {
+        if self.agreed.is_some() {
+            return Some("the rules have already been agreed".to_owned());
+        }
+        let name = self.guests[i].seat.name.clone();
+        let Some(proposal) = id.checked_sub(1).and_then(|it| self.proposals.get_mut(it)) else {
+            return Some(format!("there's no proposal {id}"));
+        };
+        proposal.votes.insert(name, agree);
+        let proposal = self.proposal(id);
+        self.tell_everyone::<PLAYERS>(&proposal);
+        None
+    }
+
+    /// Lock in the proposals with more votes for than against, on top of `rules`. Later
+    /// proposals for the same rule win. Returns why it was refused, if it was.
+    fn confirm<const PLAYERS: usize>(&mut self, rules: Rules) -> Option<String> {
+        if self.agreed.is_some() {
+            return Some("the rules have already been agreed".to_owned());
+        }
+        let mut settings = rules.to_map();
+        for proposal in &self.proposals {
+            let (yes, no) = self.tally(proposal);
+            if yes > no {
+                settings.insert(proposal.key.clone(), proposal.value.clone());
+            }
+        }
+        let agreed = match Rules::from_map(&settings) {
+            Ok(agreed) => agreed,
+            Err(e) => return Some(format!("{e:#}")),
+        };
+
+        say!("The rules at table {} were agreed", self.id);
+        self.agreed = Some(agreed);
+        self.tell_everyone::<PLAYERS>(&ServerMessage::Agreed(agreed.to_map()));
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Guest, Room};
+    use crate::net::{Encoding, Polled, RemoteSeat, Transport};
+    use card_games::{protocol::ServerMessage, r#match::Rules};
+    use std::{
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    /// A connection that keeps what's sent over it.
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Transport for Recorder {
+        fn send(&mut self, text: &str) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(text.to_owned());
+            Ok(())
+        }
+
+        fn receive(&mut self) -> anyhow::Result<Option<String>> {
+            Ok(None)
+        }
+
+        fn poll(&mut self) -> anyhow::Result<Polled> {
+            Ok(Polled::Nothing)
+        }
+    }
+
+    #[test]
+    fn test_negotiation() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let guests = ["A", "B", "C"].map(|name| Guest {
+            seat: RemoteSeat::new(
+                name.to_owned(),
+                Encoding::Lines,
+                Box::new(Recorder(Arc::clone(&sent))),
+            ),
+            joined: true,
+        });
+        let mut room = Room {
+            id: 1,
+            name: "Friday night".to_owned(),
+            guests: guests.into(),
+            opened_at: Instant::now(),
+            starting: false,
+            proposals: Vec::new(),
+            agreed: None,
+        };
+
+        let propose = |room: &mut Room, i, key: &str, value: &str| {
+            room.propose::<4>(i, key.to_owned(), value.to_owned())
+        };
+        assert_eq!(propose(&mut room, 0, "revolutions", "true"), None);
+        assert_eq!(room.vote::<4>(1, 1, false), None);
+        assert_eq!(room.vote::<4>(2, 1, true), None);
+        assert_eq!(propose(&mut room, 1, "instant-wins", "true"), None);
+        assert_eq!(room.vote::<4>(2, 2, false), None);
+        // Votes can be changed, and only count while the voter's at the table
+        assert_eq!(room.vote::<4>(0, 2, true), None);
+        assert_eq!(room.vote::<4>(0, 2, false), None);
+        assert!(propose(&mut room, 2, "suits", "sideways").is_some());
+        assert!(propose(&mut room, 2, "jokers", "true").is_some());
+        assert!(room.vote::<4>(2, 3, true).is_some());
+        assert_eq!(
+            room.proposal::<4>(1),
+            ServerMessage::Proposal {
+                id: 1,
+                name: "A".to_owned(),
+                key: "revolutions".to_owned(),
+                value: "true".to_owned(),
+                yes: 2,
+                no: 1,
+            }
+        );
+        room.guests.remove(2);
+        assert_eq!(room.tally(&room.proposals[0]), (1, 1));
+        room.guests.push(Guest {
+            seat: RemoteSeat::new(
+                "C".to_owned(),
+                Encoding::Lines,
+                Box::new(Recorder(Arc::clone(&sent))),
+            ),
+            joined: true,
+        });
+
+        assert_eq!(room.confirm::<4>(Rules::default()), None);
+        let agreed = Rules::default().revolutions_enabled(true);
+        assert_eq!(room.agreed, Some(agreed));
+        assert_eq!(
+            sent.lock().unwrap().last().unwrap(),
+            &ServerMessage::<4>::Agreed(agreed.to_map()).to_string()
+        );
+        // Someone who sits down afterwards is told what was agreed
+        assert_eq!(room.negotiation::<4>().len(), 3);
+        assert!(propose(&mut room, 0, "rated", "true").is_some());
+        assert!(room.vote::<4>(1, 2, true).is_some());
+        assert!(room.confirm::<4>(Rules::default()).is_some());
+    }
+}
@@ -0,0 +1,119 @@
+//! A tournament played at several tables at once, all hosted by one server, e.g. for
+//! eight people on a LAN across two tables.
+//!
+//! Everyone joins on the one port, as they would for a single game. Once they're all
+//! here, they're dealt out to the tables in the order they joined, so that the first
+//! few to join are spread across them, and each table's match is played on its own
+//! thread. When every match is over, the people who played are ranked by their scores
+//! and everyone is sent the standings.
+
+use super::{gallery::Gallery, ws, Encoding, RemoteSeat, Transport};
+use card_games::{protocol::ServerMessage, r#match::Match};
+use std::{
+    cmp::Reverse,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// Plays a match with the people seated at a table, in seat order, returning it once
+/// it's over. The table's spectators, if any, watch its games.
+pub type PlayTable = dyn Fn(Vec<RemoteSeat>, Gallery) -> anyhow::Result<Match> + Send + Sync;
+
+/// Someone playing in the tournament, kept to send them the standings once every table
+/// has finished.
+struct Entrant {
+    name: String,
+    encoding: Encoding,
+    connection: Arc<Mutex<Box<dyn Transport>>>,
+}
+
+/// Wait for `humans` people for each of `tables` tables to join on `port`, then play
+/// every table's match at once. If `websocket` is true, people connect over WebSockets
+/// and send JSON messages. Anyone who asks to watch sees the first table's games.
+pub fn run<const PLAYERS: usize>(
+    port: u16,
+    websocket: bool,
+    tables: usize,
+    humans: usize,
+    play_table: impl Fn(Vec<RemoteSeat>, Gallery) -> anyhow::Result<Match> + Send + Sync + 'static,
+) -> anyhow::Result<()> {
+    let gallery = Gallery::default();
+    let seats = if websocket {
+        ws::accept_players::<PLAYERS>(port, tables * humans, &gallery)?
+    } else {
+        super::accept_players::<PLAYERS>(port, tables * humans, &gallery)?
+    };
+    let entrants: Vec<_> = seats
+        .iter()
+        .map(|seat| Entrant {
+            name: seat.name.clone(),
+            encoding: seat.encoding,
+            connection: Arc::clone(&seat.connection),
+        })
+        .collect();
+
+    let mut lineups: Vec<Vec<RemoteSeat>> = (0..tables).map(|_| Vec::new()).collect();
+    for (i, seat) in seats.into_iter().enumerate() {
+        lineups[i % tables].push(seat);
+    }
+    let play_table: Arc<PlayTable> = Arc::new(play_table);
+    let games: Vec<_> = lineups
+        .into_iter()
+        .enumerate()
+        .map(|(table, seats)| {
+            let names: Vec<_> = seats.iter().map(|seat| seat.name.clone()).collect();
+            say!("Table {}: {}", table + 1, names.join(", "));
+            let gallery = match table {
+                0 => gallery.clone(),
+                _ => Gallery::default(),
+            };
+            let play_table = Arc::clone(&play_table);
+            thread::spawn(move || play_table(seats, gallery))
+        })
+        .collect();
+
+    let mut standings = Vec::new();
+    for (table, game) in games.into_iter().enumerate() {
+        match game.join() {
+            Ok(Ok(r#match)) => standings.extend(scores(&r#match, humans)),
+            Ok(Err(e)) => say!("The match at table {} ended early: {e:#}", table + 1),
+            Err(_) => say!("The match at table {} crashed", table + 1),
+        }
+    }
+    standings.sort_by_key(|(_, score)| Reverse(*score));
+
+    say!("Tournament standings:");
+    for (place, (name, score)) in standings.iter().enumerate() {
+        say!("\t{}. {name}: {score}", place + 1);
+    }
+    for entrant in entrants {
+        let mut connection = entrant.connection.lock().unwrap();
+        for (place, (name, score)) in standings.iter().enumerate() {
+            let message = ServerMessage::<PLAYERS>::Standing {
+                place: place + 1,
+                name: name.clone(),
+                score: *score,
+            };
+            // Someone who's gone away misses the standings, but everyone else still
+            // gets them
+            if connection.send(&entrant.encoding.encode(&message)).is_err() {
+                say!("{} couldn't be sent the standings", entrant.name);
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The total score of each person in a table's match. They sit in the first `humans`
+/// seats, with the computer in the rest, whose scores don't count.
+fn scores(r#match: &Match, humans: usize) -> Vec<(String, isize)> {
+    r#match
+        .players()
+        .iter()
+        .cloned()
+        .zip(r#match.cumulative_scores())
+        .take(humans)
+        .collect()
+}
@@ -0,0 +1,163 @@
+//! Hosting a game for WebSocket clients, which send and receive JSON messages.
+//!
+//! Each player is given a session token when they join. If they lose their connection,
+//! they can connect again and send it with `resume` to take back their seat.
+//!
+//! Like the TCP server, this uses blocking sockets rather than an async runtime. Every
+//! seat already waits on its own connection while the game loop waits on the seat, so
+//! there's nothing for a runtime to interleave. Connections that arrive once the game
+//! has started are each handled on a thread of their own.
+
+use super::{
+    gallery::Gallery, is_timeout, seat_client, Encoding, Polled, RemoteSeat, Transport,
+    POLL_INTERVAL,
+};
+use anyhow::Context;
+use card_games::protocol::{ClientMessage, ServerMessage};
+use std::{
+    collections::HashMap,
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Sender},
+        Arc,
+    },
+    thread,
+};
+use tungstenite::{Error, Message, WebSocket};
+
+impl Transport for WebSocket<TcpStream> {
+    fn send(&mut self, text: &str) -> anyhow::Result<()> {
+        WebSocket::send(self, Message::Text(text.to_owned())).context("sending a message")
+    }
+
+    fn receive(&mut self) -> anyhow::Result<Option<String>> {
+        loop {
+            match self.read() {
+                Ok(Message::Text(text)) => return Ok(Some(text)),
+                Ok(Message::Close(_)) | Err(Error::ConnectionClosed | Error::AlreadyClosed) => {
+                    return Ok(None)
+                }
+                // Pings are answered by tungstenite, and nothing else is part of the protocol
+                Ok(_) => continue,
+                Err(e) => return Err(e).context("reading a message"),
+            }
+        }
+    }
+
+    fn poll(&mut self) -> anyhow::Result<Polled> {
+        self.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+        // A message cut short by the timeout is kept by tungstenite until the next read
+        let read = self.read();
+        self.get_ref().set_read_timeout(None)?;
+
+        match read {
+            Ok(Message::Text(text)) => Ok(Polled::Message(text)),
+            Ok(Message::Close(_)) | Err(Error::ConnectionClosed | Error::AlreadyClosed) => {
+                Ok(Polled::Disconnected)
+            }
+            Ok(_) => Ok(Polled::Nothing),
+            Err(Error::Io(e)) if is_timeout(&e) => Ok(Polled::Nothing),
+            Err(e) => Err(e).context("reading a message"),
+        }
+    }
+}
+
+/// Finish the WebSocket handshake with someone who's just connected.
+pub(super) fn handshake(stream: TcpStream) -> anyhow::Result<WebSocket<TcpStream>> {
+    tungstenite::accept(stream).map_err(|e| anyhow::anyhow!("WebSocket handshake failed: {e}"))
+}
+
+/// Wait for `count` people to connect to `port` over WebSockets, seating them in the
+/// order they join. Once everyone's joined, anyone who loses their connection may
+/// reconnect until the server exits. Anyone who asks to watch joins `gallery`.
+pub fn accept_players<const PLAYERS: usize>(
+    port: u16,
+    count: usize,
+    gallery: &Gallery,
+) -> anyhow::Result<Vec<RemoteSeat>> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .with_context(|| format!("listening on port {port}"))?;
+    say!("Waiting for {count} player(s) to join at ws://<this machine>:{port}");
+
+    let mut seats = Vec::with_capacity(count);
+    let mut sessions = HashMap::new();
+    while seats.len() < count {
+        let (stream, address) = listener.accept().context("accepting a connection")?;
+        let socket = match handshake(stream) {
+            Ok(socket) => socket,
+            Err(e) => {
+                say!("{address} couldn't join: {e:#}");
+                continue;
+            }
+        };
+        let seat = RemoteSeat::new(address.to_string(), Encoding::Json, Box::new(socket));
+        let Some(mut seat) =
+            seat_client::<PLAYERS>(seat, &address.to_string(), seats.len(), gallery)?
+        else {
+            continue;
+        };
+
+        let token = format!("{:016x}", rand::random::<u64>());
+        let (sender, receiver) = mpsc::channel();
+        sessions.insert(token.clone(), sender);
+        seat.reconnections = Some(receiver);
+        seat.send(&ServerMessage::<PLAYERS>::Session { token })?;
+        seats.push(seat);
+    }
+    let gallery = gallery.clone();
+    thread::spawn(move || route_reconnections::<PLAYERS>(listener, sessions, gallery));
+
+    Ok(seats)
+}
+
+/// Hand each connection that resumes a session to the seat it belongs to, and let
+/// anyone else who connects watch. A client that's slow to say which it wants only
+/// holds up its own connection.
+fn route_reconnections<const PLAYERS: usize>(
+    listener: TcpListener,
+    sessions: HashMap<String, Sender<Box<dyn Transport>>>,
+    gallery: Gallery,
+) {
+    let sessions = Arc::new(sessions);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let sessions = Arc::clone(&sessions);
+        let gallery = gallery.clone();
+        thread::spawn(move || route_connection::<PLAYERS>(stream, &sessions, &gallery));
+    }
+}
+
+fn route_connection<const PLAYERS: usize>(
+    stream: TcpStream,
+    sessions: &HashMap<String, Sender<Box<dyn Transport>>>,
+    gallery: &Gallery,
+) {
+    let Ok(mut socket) = handshake(stream) else {
+        return;
+    };
+    let message = socket
+        .receive()
+        .ok()
+        .flatten()
+        .and_then(|text| Encoding::Json.decode::<ClientMessage<PLAYERS>>(&text).ok());
+    let refusal = match message {
+        Some(ClientMessage::Resume { token }) => match sessions.get(&token) {
+            // The seat picks up the connection when it next hears from its client. If
+            // the game's over, the connection is just dropped.
+            Some(sender) => {
+                let _ = sender.send(Box::new(socket));
+                return;
+            }
+            None => "there's no session with that token",
+        },
+        Some(ClientMessage::Watch { name }) => {
+            gallery.admit::<PLAYERS>(RemoteSeat::new(name, Encoding::Json, Box::new(socket)));
+            return;
+        }
+        _ => "the game has already started, so only 'resume' and 'watch' are accepted",
+    };
+    let error = Encoding::Json.encode(&ServerMessage::<PLAYERS>::Error(refusal.to_owned()));
+    let _ = Transport::send(&mut socket, &error);
+}
@@ -0,0 +1,120 @@
+//! Saving files so that a crash partway through never leaves a corrupted file behind.
+
+use anyhow::Context;
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Replace the contents of the file at `path`.
+///
+/// The contents are written and synced to a temporary file next to `path`, which is
+/// then renamed over it. Either the old or the new contents will be at `path`, even if
+/// the game is killed partway through.
+pub fn write_atomically(path: &Path, contents: &str) -> anyhow::Result<()> {
+    let temp_path = temp_path(path);
+    let mut file = File::create(&temp_path)
+        .with_context(|| format!("creating temporary file {}", temp_path.display()))?;
+    file.write_all(contents.as_bytes())
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("writing temporary file {}", temp_path.display()))?;
+    drop(file);
+
+    fs::rename(&temp_path, path)
+        .with_context(|| format!("replacing {} with {}", path.display(), temp_path.display()))?;
+    // Sync the directory too, so that the rename itself survives a crash
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+
+    Ok(())
+}
+
+/// Keep copies of the file at `path` from before its last `backups` saves, at
+/// `path.1` for the newest through `path.<backups>` for the oldest.
+///
+/// Call this before saving over the file. The file itself is copied rather than moved,
+/// so it's still there if the save fails.
+pub fn rotate_backups(path: &Path, backups: usize) -> anyhow::Result<()> {
+    if backups == 0 || !path.exists() {
+        return Ok(());
+    }
+    for n in (1..backups).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            let to = backup_path(path, n + 1);
+            fs::rename(&from, &to)
+                .with_context(|| format!("moving {} to {}", from.display(), to.display()))?;
+        }
+    }
+    let newest = backup_path(path, 1);
+    fs::copy(path, &newest)
+        .with_context(|| format!("copying {} to {}", path.display(), newest.display()))?;
+
+    Ok(())
+}
+
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    with_suffix(path, &format!(".{n}"))
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".tmp")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backup_path, rotate_backups, temp_path, write_atomically};
+    use std::fs;
+
+    #[test]
+    fn test_interrupted_write_keeps_old_contents() {
+        let dir = std::env::temp_dir().join(format!("dai-di-persist-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("profile.toml");
+        write_atomically(&path, "name = \"Old\"").unwrap();
+
+        // A crash after the temporary file is written, but before the rename, leaves
+        // the old file untouched
+        fs::write(temp_path(&path), "name = \"Ne").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "name = \"Old\"");
+
+        // The next save replaces the leftover temporary file
+        write_atomically(&path, "name = \"New\"").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "name = \"New\"");
+        assert!(!temp_path(&path).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotate_backups() {
+        let dir = std::env::temp_dir().join(format!("dai-di-backups-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stats.json");
+
+        // There's nothing to back up before the first save
+        rotate_backups(&path, 2).unwrap();
+        assert!(!backup_path(&path, 1).exists());
+
+        for save in 1..=4 {
+            rotate_backups(&path, 2).unwrap();
+            write_atomically(&path, &save.to_string()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "4");
+        assert_eq!(fs::read_to_string(backup_path(&path, 1)).unwrap(), "3");
+        assert_eq!(fs::read_to_string(backup_path(&path, 2)).unwrap(), "2");
+        assert!(!backup_path(&path, 3).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
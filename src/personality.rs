@@ -0,0 +1,134 @@
+//! The roster of computer opponents that games are filled from when AI rotation is
+//! enabled.
+
+use crate::profile::Profile;
+use anyhow::Context;
+use card_games::player::ai::{self, Strategy};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+const ROSTER_FILE_NAME: &str = "personalities.toml";
+
+/// A named computer opponent with its own way of playing and talking.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Personality {
+    pub name: String,
+    pub strategy: Strategy,
+    /// How often the personality deliberately makes a worse play, from 0 to 1
+    #[serde(default)]
+    pub error_rate: f64,
+    /// Said to the table when the game starts
+    pub greeting: String,
+}
+
+impl Personality {
+    pub fn to_player(&self) -> ai::Player {
+        let mut player = ai::Player::with_strategy(self.name.as_str(), self.strategy);
+        player.set_error_rate(self.error_rate);
+        player
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Roster {
+    #[serde(rename = "personality")]
+    pub personalities: Vec<Personality>,
+}
+
+impl Default for Roster {
+    fn default() -> Self {
+        let personality = |name: &str, strategy, error_rate, greeting: &str| Personality {
+            name: name.to_owned(),
+            strategy,
+            error_rate,
+            greeting: greeting.to_owned(),
+        };
+
+        Roster {
+            personalities: vec![
+                personality(
+                    "AIshley",
+                    Strategy::Random,
+                    0.0,
+                    "Let's see what the cards say!",
+                ),
+                personality(
+                    "FelAIcity",
+                    Strategy::Advised,
+                    0.1,
+                    "Good luck, you'll need it.",
+                ),
+                personality("AImy", Strategy::Random, 0.0, "Hi everyone!"),
+                personality(
+                    "ChoBot",
+                    Strategy::Advised,
+                    0.0,
+                    "CALCULATING OPTIMAL PLAY.",
+                ),
+                personality(
+                    "Hirayama",
+                    Strategy::Advised,
+                    0.25,
+                    "A pleasure to play with you.",
+                ),
+            ],
+        }
+    }
+}
+
+impl Roster {
+    fn path() -> Option<PathBuf> {
+        Profile::config_dir().map(|dir| dir.join(ROSTER_FILE_NAME))
+    }
+
+    /// Load the roster from the config directory. If there isn't one yet, the default
+    /// roster is saved there so that it can be edited.
+    pub fn load_or_create() -> anyhow::Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Roster::default());
+        };
+        if !path.exists() {
+            let roster = Roster::default();
+            let contents = toml::to_string_pretty(&roster).context("serializing AI roster")?;
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("creating config directory {}", dir.display()))?;
+            }
+            crate::persist::write_atomically(&path, &contents)
+                .with_context(|| format!("writing AI roster to {}", path.display()))?;
+            return Ok(roster);
+        }
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("reading AI roster from {}", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("parsing AI roster at {}", path.display()))
+    }
+
+    /// Find the personality called `name`.
+    pub fn get(&self, name: &str) -> Option<&Personality> {
+        self.personalities.iter().find(|it| it.name == name)
+    }
+
+    /// Randomly choose `count` different personalities, leaving out any whose names are
+    /// `taken` by players already at the table.
+    pub fn choose(&self, count: usize, taken: &[&str]) -> anyhow::Result<Vec<&Personality>> {
+        let available: Vec<_> = self
+            .personalities
+            .iter()
+            .filter(|it| !taken.contains(&it.name.as_str()))
+            .collect();
+        if available.len() < count {
+            anyhow::bail!(
+                "the AI roster needs {count} more personalities but only has {}",
+                available.len()
+            );
+        }
+
+        Ok(available
+            .choose_multiple(&mut rand::thread_rng(), count)
+            .copied()
+            .collect())
+    }
+}
@@ -1,124 +1,340 @@
+use crate::{input::InputSource, transcript};
 use anyhow::Context;
+use card_games::advisor::rank_plays;
 use card_games::collections::SortCardsBy;
-use card_games::{cho_dai_di::ChoDaiDi, collections::Cards, player::ai::Strategy};
+use card_games::hint_format::HintFormatter;
+use card_games::player::human::{self, Shortcut};
+use card_games::render::CardStyle;
+use card_games::score_format::ScoreFormatter;
+use card_games::{
+    cho_dai_di::{self, ChoDaiDi, GameView, FOUR_PLAYERS},
+    collections::Cards,
+    player::ai::{self, AiStrategy, Difficulty},
+};
 use core::fmt;
-use rand::seq::SliceRandom;
-use rand::{rngs::SmallRng, SeedableRng};
 use std::fmt::Write;
 
-pub trait Player {
+pub trait Player<const PLAYERS: usize = FOUR_PLAYERS> {
     fn name(&self) -> &str;
 
-    fn take_turn(&mut self, game: &ChoDaiDi, hand: Cards<ChoDaiDi>) -> anyhow::Result<TurnAction>;
+    /// Returns true if this player is controlled by a person at the keyboard.
+    fn is_human(&self) -> bool {
+        false
+    }
+
+    /// The difficulty preset a computer player is playing at, if any.
+    fn difficulty(&self) -> Option<Difficulty> {
+        None
+    }
+
+    /// Called when a game is dealt, before anyone takes a turn. Seats that show the
+    /// game somewhere else, such as over the network, use this to catch up.
+    fn join_game(
+        &mut self,
+        _seat: usize,
+        _names: &[String],
+        _game: &mut ChoDaiDi<PLAYERS>,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Returns true if the person playing this seat has gone away, e.g. by losing their
+    /// connection. The computer takes their turns until they're back.
+    fn is_absent(&mut self) -> bool {
+        false
+    }
+
+    fn take_turn(
+        &mut self,
+        view: &GameView<'_, PLAYERS>,
+        hand: Cards<ChoDaiDi<PLAYERS>>,
+    ) -> anyhow::Result<TurnAction<PLAYERS>>;
 }
 
-impl fmt::Display for dyn Player {
+impl<const PLAYERS: usize> fmt::Display for dyn Player<PLAYERS> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.name())
     }
 }
 
-pub enum TurnAction {
-    PlayCards(Cards<ChoDaiDi>),
+pub enum TurnAction<const PLAYERS: usize = FOUR_PLAYERS> {
+    PlayCards(Cards<ChoDaiDi<PLAYERS>>),
     Pass,
+    #[cfg(feature = "dev-cheats")]
+    Cheat(Cheat),
+}
+
+/// Commands from the developer cheat console.
+#[cfg(feature = "dev-cheats")]
+pub enum Cheat {
+    /// Give the current player a card
+    Give(card_games::card::Card),
+    /// Show every player's hand
+    Reveal,
+    /// Skip the current player's turn without counting it as a pass
+    Skip,
+}
+
+/// A seat played by the computer, choosing its plays with an [`AiStrategy`].
+pub struct AiSeat<const PLAYERS: usize = FOUR_PLAYERS> {
+    name: String,
+    difficulty: Option<Difficulty>,
+    strategy: Box<dyn AiStrategy<PLAYERS>>,
 }
 
-impl Player for card_games::player::ai::Player {
+impl<const PLAYERS: usize> From<ai::Player> for AiSeat<PLAYERS> {
+    fn from(player: ai::Player) -> Self {
+        AiSeat {
+            name: player.name().to_owned(),
+            difficulty: player.difficulty(),
+            strategy: Box::new(player),
+        }
+    }
+}
+
+impl<const PLAYERS: usize> Player<PLAYERS> for AiSeat<PLAYERS> {
     fn name(&self) -> &str {
-        self.name()
+        &self.name
+    }
+
+    fn difficulty(&self) -> Option<Difficulty> {
+        self.difficulty
     }
 
-    /// An AI player's turn-taking logic.
-    ///
-    /// This is where the AI decides what cards to play.
     fn take_turn(
         &mut self,
-        game: &ChoDaiDi,
-        mut hand: Cards<ChoDaiDi>,
-    ) -> anyhow::Result<TurnAction> {
-        let mut small_rng = SmallRng::from_entropy();
-
-        hand.sort_by_rank();
-
-        match self.strategy() {
-            Strategy::Random => {
-                let possible_plays = game.possible_plays(&hand);
-                if let Some(play) = possible_plays.choose(&mut small_rng) {
-                    tracing::warn!(
-                        "{} possible play(s) found for {}",
-                        possible_plays.len(),
-                        self.name()
-                    );
+        view: &GameView<'_, PLAYERS>,
+        hand: Cards<ChoDaiDi<PLAYERS>>,
+    ) -> anyhow::Result<TurnAction<PLAYERS>> {
+        Ok(
+            match self
+                .strategy
+                .choose_play(view, &hand, &mut rand::thread_rng())
+            {
+                cho_dai_di::TurnAction::Play(cards) => TurnAction::PlayCards(cards),
+                cho_dai_di::TurnAction::Pass => TurnAction::Pass,
+            },
+        )
+    }
+}
 
-                    return Ok(TurnAction::PlayCards(play.clone()));
-                } else {
-                    tracing::warn!("no possible plays found for {}", self.name());
-                }
-            }
+/// A seat played by a person, reading their moves from an [`InputSource`].
+pub struct HumanSeat {
+    player: human::Player,
+    input: Box<dyn InputSource>,
+    /// If true, the keyboard is handed to this seat before each of its turns
+    hand_off: bool,
+    /// If true, the seat's last action didn't end its turn, so its player still has the
+    /// keyboard
+    mid_turn: bool,
+    card_style: CardStyle,
+    score_formatter: ScoreFormatter,
+}
+
+impl HumanSeat {
+    pub fn new(player: human::Player, input: Box<dyn InputSource>) -> Self {
+        HumanSeat {
+            player,
+            input,
+            hand_off: false,
+            mid_turn: false,
+            card_style: CardStyle::default(),
+            score_formatter: ScoreFormatter::default(),
         }
+    }
 
-        Ok(TurnAction::Pass)
+    /// Set whether to wait for this seat's player to take the keyboard before each of
+    /// their turns, and to clear the screen once it's over, so that people sharing a
+    /// keyboard don't see each other's hands.
+    pub fn set_hand_off(&mut self, hand_off: bool) {
+        self.hand_off = hand_off;
     }
-}
 
-impl Player for card_games::player::human::Player {
-    fn name(&self) -> &str {
-        self.name()
+    pub fn set_card_style(&mut self, card_style: CardStyle) {
+        self.card_style = card_style;
     }
 
-    fn take_turn(
+    pub fn set_score_formatter(&mut self, score_formatter: ScoreFormatter) {
+        self.score_formatter = score_formatter;
+    }
+
+    /// Wait for this seat's player to take the keyboard.
+    fn wait_for_player(&mut self) -> anyhow::Result<()> {
+        say!();
+        say!(
+            "Pass the keyboard to {name}. Press Enter when {name} is ready.",
+            name = self.player.name()
+        );
+        self.input
+            .read_line()?
+            .context("ran out of moves: the input for this seat has ended")?;
+
+        Ok(())
+    }
+
+    fn read_action<const PLAYERS: usize>(
         &mut self,
-        game: &ChoDaiDi,
-        mut hand: Cards<ChoDaiDi>,
-    ) -> anyhow::Result<TurnAction> {
-        // Enough capacity for a full hand of cards, plus some extra for the commas and spaces.
-        let mut buf = String::with_capacity(16);
+        view: &GameView<'_, PLAYERS>,
+        mut hand: Cards<ChoDaiDi<PLAYERS>>,
+    ) -> anyhow::Result<TurnAction<PLAYERS>> {
+        let HumanSeat {
+            player,
+            input: source,
+            card_style: style,
+            score_formatter,
+            ..
+        } = self;
+        // Assists are decided by the game rather than the seat, so a rated game can't
+        // be given any
+        let rated = view.is_rated();
         let cards = loop {
-            buf.clear();
-            match self.sort_cards_by() {
-                SortCardsBy::Rank => hand.sort_by_rank(),
-                SortCardsBy::Suit => hand.sort_by_suit(),
+            match player.sort_cards_by() {
+                SortCardsBy::Rank => hand.sort_by_rank_under(&view.ordering()),
+                SortCardsBy::Suit => hand.sort_by_suit_under(&view.ordering()),
             }
-            println!();
-            if let Some(last_play) = game.last_play() {
-                println!("Last play: {last_play}");
+            say!();
+            if let Some(last_play) = view.last_play() {
+                say!("Last play: {}", style.cards(&last_play));
             }
-            let hand_str = hand.iter().fold(String::new(), |mut s, card| {
-                write!(s, "{card}, ").expect("write to string will never fail");
-                s
-            });
-            println!("{}'s hand: {hand_str}", self.name());
+            let hand_str = hand
+                .iter()
+                .enumerate()
+                .fold(String::new(), |mut s, (i, card)| {
+                    write!(s, "[{}] {} ", i + 1, style.card(card))
+                        .expect("write to string will never fail");
+                    s
+                });
+            say_hand!("{}'s hand: {hand_str}", player.name());
             print!("Your play: ");
+            transcript::record_partial("Your play: ");
             // We flush to guarantee that the prompt is displayed before reading input.
             std::io::Write::flush(&mut std::io::stdout())
                 .context("flushing 'Your play: ' prompt")?;
-            std::io::stdin().read_line(&mut buf)?;
-            let input = buf.trim();
+            let line = source
+                .read_line()?
+                .context("ran out of moves: the input for this seat has ended")?;
+            let input = line.trim();
+            if names_cards(player, input) {
+                transcript::record_hand(input);
+            } else {
+                transcript::record(input);
+            }
+            if let Some(shortcut) = player.keybindings().shortcut(input) {
+                match shortcut {
+                    Shortcut::LowestSingle | Shortcut::Hint if rated => {
+                        say!("shortcuts that pick cards aren't available in rated games")
+                    }
+                    Shortcut::LowestSingle => match lowest_single(view, &hand) {
+                        Some(cards) => {
+                            if confirmed(player, rated, source.as_mut(), style, &cards)? {
+                                break cards;
+                            }
+                        }
+                        None => say!("none of your single cards can be played"),
+                    },
+                    Shortcut::Hint => match rank_plays(view, &hand).first() {
+                        Some((play, _, explanation)) => {
+                            say_hand!("Hint: {} ({explanation})", style.cards(play))
+                        }
+                        None => say!("Hint: you have no plays available, so you must pass"),
+                    },
+                    Shortcut::Recap => recap(view, style),
+                    Shortcut::Sort => {
+                        player.toggle_precedence();
+                        say!("hand rearranged by {}", player.sort_cards_by());
+                    }
+                }
+                continue;
+            }
+
             match input {
-                "p" | "pass" => return Ok(TurnAction::Pass),
+                "p" | "pass" => {
+                    if player.explain_passes() && !rated {
+                        explain_pass(view, &hand, style);
+                    }
+                    return Ok(TurnAction::Pass);
+                }
                 "q" | "quit" => {
-                    println!("Quitting immediately. Thanks for playing.");
+                    say!("Quitting immediately. Thanks for playing.");
                     std::process::exit(0);
                 }
                 "" | "help" => {
-                    println!("Enter the space-separated list of the cards you want to play");
-                    println!("For example: '2c 3h 4d 5s 6s' or '7C 7D 7H' or 'jc'");
-                    println!("You may pass your turn: enter 'p' or 'pass'");
-                    println!("You may quit the game: enter 'q' or 'quit'");
-                    println!(
+                    say!("Enter the space-separated list of the cards you want to play");
+                    say!("For example: '2c 3h 4d 5s 6s' or '7C 7D 7H' or 'jc'");
+                    say!("Or enter their numbers from your hand, for example: '1 4 7'");
+                    say!("You may pass your turn: enter 'p' or 'pass'");
+                    if player.confirm_plays() && !rated {
+                        say!("Before a play is made, enter 'y' to confirm it or 'n' or 'undo' to choose again");
+                    }
+                    say!("You may quit the game: enter 'q' or 'quit'");
+                    say!(
                         "You may toggle between sorting by rank and sorting by suit: enter 'sort'"
                     );
+                    let keybindings = player.keybindings();
+                    if rated {
+                        say!(
+                            "Shortcuts: '{}' recap, '{}' sort",
+                            keybindings.recap,
+                            keybindings.sort
+                        );
+                    } else {
+                        say!("You may see what everyone would score if the game ended now: enter 'scores'");
+                        say!("You may list every play you can make: enter 'hints'");
+                        say!(
+                            "Shortcuts: '{}' lowest single, '{}' hint, '{}' recap, '{}' sort",
+                            keybindings.lowest_single,
+                            keybindings.hint,
+                            keybindings.recap,
+                            keybindings.sort
+                        );
+                    }
+                    continue;
+                }
+                "scores" if rated => {
+                    say!("score previews aren't available in rated games");
+                    continue;
+                }
+                "scores" => {
+                    preview_scores(view, score_formatter);
+                    continue;
+                }
+                "hints" if rated => {
+                    say!("hints aren't available in rated games");
+                    continue;
+                }
+                "hints" => {
+                    for line in HintFormatter::default().format(view, &hand, style) {
+                        say_hand!("{line}");
+                    }
                     continue;
                 }
                 "sort" => {
-                    self.toggle_precedence();
-                    println!("hand rearranged by {}", self.sort_cards_by());
+                    player.toggle_precedence();
+                    say!("hand rearranged by {}", player.sort_cards_by());
                     continue;
                 }
-                input => match input.parse::<Cards<ChoDaiDi>>() {
-                    Ok(cards) => break cards,
+                #[cfg(feature = "dev-cheats")]
+                input if input.starts_with(':') && rated => {
+                    say!("cheats aren't available in rated games");
+                    continue;
+                }
+                #[cfg(feature = "dev-cheats")]
+                input if input.starts_with(':') => match parse_cheat(input) {
+                    Ok(cheat) => return Ok(TurnAction::Cheat(cheat)),
+                    Err(e) => {
+                        say!("invalid cheat: {e}");
+                        continue;
+                    }
+                },
+                input => match human::parse_selection(input, &hand) {
+                    Ok(cards) => {
+                        if confirmed(player, rated, source.as_mut(), style, &cards)? {
+                            break cards;
+                        }
+                        continue;
+                    }
                     Err(e) => {
-                        println!("invalid input: {e}");
+                        say!("invalid input: {e}");
                         continue;
                     }
                 },
@@ -128,3 +344,200 @@ impl Player for card_games::player::human::Player {
         Ok(TurnAction::PlayCards(cards))
     }
 }
+
+impl<const PLAYERS: usize> Player<PLAYERS> for HumanSeat {
+    fn name(&self) -> &str {
+        self.player.name()
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+
+    fn take_turn(
+        &mut self,
+        view: &GameView<'_, PLAYERS>,
+        hand: Cards<ChoDaiDi<PLAYERS>>,
+    ) -> anyhow::Result<TurnAction<PLAYERS>> {
+        // A play that's turned down comes back to the same player, who still has the
+        // keyboard and should see why it was turned down
+        if self.hand_off && !self.mid_turn {
+            self.wait_for_player()?;
+        }
+        let action = self.read_action(view, hand.clone())?;
+        self.mid_turn = match &action {
+            TurnAction::PlayCards(cards) => {
+                !cards.iter().all(|card| hand.contains(card)) || view.is_valid_play(cards).is_err()
+            }
+            TurnAction::Pass => false,
+            #[cfg(feature = "dev-cheats")]
+            TurnAction::Cheat(Cheat::Give(_) | Cheat::Reveal) => true,
+            #[cfg(feature = "dev-cheats")]
+            TurnAction::Cheat(Cheat::Skip) => false,
+        };
+        if self.hand_off && !self.mid_turn {
+            clear_screen();
+        }
+
+        Ok(action)
+    }
+}
+
+/// If true, `input` could name some of the player's cards, rather than being a command
+/// or a shortcut.
+fn names_cards(player: &human::Player, input: &str) -> bool {
+    let command = matches!(
+        input,
+        "" | "p" | "pass" | "q" | "quit" | "help" | "scores" | "hints" | "sort"
+    );
+    !command && player.keybindings().shortcut(input).is_none()
+}
+
+/// Ask the player to confirm a play if they've asked to confirm their plays and the game
+/// isn't rated, where plays can't be undone. Returns false if they'd like to undo it.
+fn confirmed<const PLAYERS: usize>(
+    player: &human::Player,
+    rated: bool,
+    source: &mut dyn InputSource,
+    style: &CardStyle,
+    cards: &Cards<ChoDaiDi<PLAYERS>>,
+) -> anyhow::Result<bool> {
+    if !player.confirm_plays() || rated {
+        return Ok(true);
+    }
+    let confirmed = confirm(source, &style.cards(cards))?;
+    if !confirmed {
+        say!("Play undone");
+    }
+
+    Ok(confirmed)
+}
+
+/// Ask the player to confirm a play. Returns false if they'd like to undo it.
+fn confirm(source: &mut dyn InputSource, cards: &str) -> anyhow::Result<bool> {
+    loop {
+        let prompt = format!("You are about to play {cards} — confirm? (y/n) ");
+        print!("{prompt}");
+        transcript::record_hand_partial(&prompt);
+        std::io::Write::flush(&mut std::io::stdout()).context("flushing confirmation prompt")?;
+        let line = source
+            .read_line()?
+            .context("ran out of moves: the input for this seat has ended")?;
+        let input = line.trim();
+        transcript::record(input);
+        match input.to_ascii_lowercase().as_str() {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" | "undo" => return Ok(false),
+            _ => say!("enter 'y' to play these cards or 'n' or 'undo' to choose again"),
+        }
+    }
+}
+
+/// Clear the terminal, e.g. so that a hand isn't left on screen for the next player.
+fn clear_screen() {
+    print!("\x1B[2J\x1B[H");
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+}
+
+/// Find the lowest single card that may be played from a hand.
+fn lowest_single<const PLAYERS: usize>(
+    view: &GameView<'_, PLAYERS>,
+    hand: &Cards<ChoDaiDi<PLAYERS>>,
+) -> Option<Cards<ChoDaiDi<PLAYERS>>> {
+    let singles: Cards<ChoDaiDi<PLAYERS>> = view
+        .possible_plays(hand)
+        .into_iter()
+        .filter(|play| play.len() == 1)
+        .flatten()
+        .collect::<Vec<_>>()
+        .into();
+
+    view.ordering()
+        .lowest_card(&singles)
+        .copied()
+        .map(Cards::from)
+}
+
+/// Summarize the round so far.
+fn recap<const PLAYERS: usize>(view: &GameView<'_, PLAYERS>, style: &CardStyle) {
+    match view.last_play() {
+        Some(last_play) => say!("Last play: {}", style.cards(&last_play)),
+        None => say!("Nothing has been played this round"),
+    }
+    if view.pass_counter() > 0 {
+        say!("Passes since the last play: {}", view.pass_counter());
+    }
+    let hand_sizes = view
+        .hand_sizes()
+        .iter()
+        .map(|size| size.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    say!("Cards left by seat: {hand_sizes}");
+    let upcoming = view
+        .upcoming_turn_order(view.number_of_players() - 1)
+        .iter()
+        .map(|seat| (seat + 1).to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    say!("Seats playing after you: {upcoming}");
+}
+
+/// Show what each player would score if the game ended now.
+fn preview_scores<const PLAYERS: usize>(
+    view: &GameView<'_, PLAYERS>,
+    score_formatter: &ScoreFormatter,
+) {
+    say!("If the game ended now, the scores would be:");
+    for (seat, score) in view.provisional_scores().into_iter().enumerate() {
+        let score = score_formatter.format(score);
+        if seat == view.whose_turn() {
+            say!("\tSeat {} (you):\t{score}", seat + 1);
+        } else {
+            say!("\tSeat {}:\t{score}", seat + 1);
+        }
+    }
+}
+
+/// Tell the player what they're conceding by passing.
+fn explain_pass<const PLAYERS: usize>(
+    view: &GameView<'_, PLAYERS>,
+    hand: &Cards<ChoDaiDi<PLAYERS>>,
+    style: &CardStyle,
+) {
+    let ranked = rank_plays(view, hand);
+    match (view.last_play(), ranked.first()) {
+        (Some(last_play), Some((best, _, _))) => {
+            let others = match ranked.len() - 1 {
+                0 => String::new(),
+                1 => " or 1 other play".to_owned(),
+                n => format!(" or {n} other plays"),
+            };
+            let last_play = style.cards(&last_play);
+            say_hand!(
+                "You could have beaten {last_play} with {}{others}.",
+                style.cards(best)
+            );
+            say!("Passing lets {last_play} stand.");
+        }
+        (None, Some(_)) => {
+            say!("You're leading, so passing gives up your chance to start the round.");
+        }
+        (_, None) => say!("You had no plays available, so passing costs you nothing."),
+    }
+}
+
+/// Parse a cheat console command.
+#[cfg(feature = "dev-cheats")]
+fn parse_cheat(input: &str) -> anyhow::Result<Cheat> {
+    let mut words = input.split_whitespace();
+    match words.next() {
+        Some(":give") => {
+            let card = words.next().context("usage: ':give <card>'")?.parse()?;
+            Ok(Cheat::Give(card))
+        }
+        Some(":reveal") => Ok(Cheat::Reveal),
+        Some(":skip") => Ok(Cheat::Skip),
+        _ => anyhow::bail!("unknown cheat '{input}', try ':give <card>', ':reveal', or ':skip'"),
+    }
+}
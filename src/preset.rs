@@ -0,0 +1,276 @@
+//! Named tables that are set up once and played again and again, e.g. with
+//! `dai-di preset save friday-night` and then `dai-di play --preset friday-night`. Each
+//! preset is kept in its own file in the config directory, so it can be edited by hand.
+
+use crate::{
+    cli::{PresetCommand, TableArgs},
+    profile::Profile,
+    table_setup::{SeatSetup, TableSetup},
+};
+use anyhow::Context;
+use card_games::{
+    cho_dai_di::{ScoringRules, SuitPrecedence, FOUR_PLAYERS},
+    player::ai::Difficulty,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, process};
+
+const PRESETS_DIR_NAME: &str = "presets";
+
+/// Everything needed to start a match at a table: who sits where, how well the computer
+/// plays, the house rules, and how long to pause between deals and turns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preset {
+    /// How well computer players play. If unset, the profile's difficulty is used.
+    pub difficulty: Option<Difficulty>,
+    /// The number of games in the match
+    pub games: usize,
+    /// The seed the match's first game is dealt from, if the same hands should be dealt
+    /// every time
+    pub seed: Option<u64>,
+    /// How long to pause between each card when revealing a newly dealt hand.
+    pub deal_delay_ms: u64,
+    /// How long to pause after each computer player's turn.
+    pub turn_delay_ms: u64,
+    pub rated: bool,
+    pub instant_wins: bool,
+    pub flush_beats_full_house: bool,
+    pub three_of_diamonds_opens_alone: bool,
+    pub must_play_lowest_single_first: bool,
+    #[serde(default)]
+    pub pass_card_penalty: bool,
+    pub suit_precedence: SuitPrecedence,
+    pub scoring: ScoringRules,
+    /// Who sits in each seat, in seat order
+    #[serde(rename = "seat")]
+    pub seats: Vec<SeatSetup>,
+}
+
+impl Preset {
+    /// The table described by `table` and the rules and timers in `profile`, with the
+    /// rules given in `table` on top.
+    pub fn new(table: TableArgs, profile: &Profile) -> anyhow::Result<Self> {
+        table.validate()?;
+        let mut profile = profile.clone();
+        table.rules.apply(&mut profile);
+
+        Ok(Preset {
+            difficulty: table.difficulty,
+            games: table.games,
+            seed: table.seed,
+            deal_delay_ms: profile.deal_delay_ms,
+            turn_delay_ms: profile.turn_delay_ms,
+            rated: profile.rated,
+            instant_wins: profile.instant_wins,
+            flush_beats_full_house: profile.flush_beats_full_house,
+            three_of_diamonds_opens_alone: profile.three_of_diamonds_opens_alone,
+            must_play_lowest_single_first: profile.must_play_lowest_single_first,
+            pass_card_penalty: profile.pass_card_penalty,
+            suit_precedence: profile.suit_precedence,
+            scoring: profile.scoring,
+            seats: TableSetup::new(table.players, table.humans, &profile.name).seats,
+        })
+    }
+
+    pub fn table(&self) -> TableSetup {
+        TableSetup {
+            seats: self.seats.clone(),
+        }
+    }
+
+    /// Play by the preset's rules and timers instead of the ones in `profile`. Nothing's
+    /// saved.
+    pub fn apply(&self, profile: &mut Profile) {
+        profile.deal_delay_ms = self.deal_delay_ms;
+        profile.turn_delay_ms = self.turn_delay_ms;
+        profile.rated = self.rated;
+        profile.instant_wins = self.instant_wins;
+        profile.flush_beats_full_house = self.flush_beats_full_house;
+        profile.three_of_diamonds_opens_alone = self.three_of_diamonds_opens_alone;
+        profile.must_play_lowest_single_first = self.must_play_lowest_single_first;
+        profile.pass_card_penalty = self.pass_card_penalty;
+        profile.suit_precedence = self.suit_precedence;
+        profile.scoring = self.scoring;
+    }
+
+    /// Check that the preset describes a table that can be played at, since it may have
+    /// been edited by hand.
+    fn validate(&self) -> anyhow::Result<()> {
+        if !(2..=FOUR_PLAYERS).contains(&self.seats.len()) {
+            anyhow::bail!("a preset must have from 2 to {FOUR_PLAYERS} seats");
+        }
+        if self.table().humans() == 0 {
+            anyhow::bail!("a preset must have at least one person in its seats");
+        }
+        if self.games == 0 {
+            anyhow::bail!("a preset must have at least 1 game");
+        }
+
+        Ok(())
+    }
+
+    /// Load the preset called `name`.
+    pub fn load(name: &str) -> anyhow::Result<Self> {
+        let path = path(name)?;
+        if !path.exists() {
+            anyhow::bail!("there's no preset called {name}; see them with `dai-di preset list`");
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("reading preset from {}", path.display()))?;
+        let preset: Preset = toml::from_str(&contents)
+            .with_context(|| format!("parsing preset at {}", path.display()))?;
+        preset
+            .validate()
+            .with_context(|| format!("checking preset at {}", path.display()))?;
+
+        Ok(preset)
+    }
+
+    /// Save the preset as `name`, replacing any preset saved with that name before.
+    pub fn save(&self, name: &str) -> anyhow::Result<()> {
+        let path = path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("creating presets directory {}", dir.display()))?;
+        }
+        let contents = toml::to_string_pretty(self).context("serializing preset")?;
+        crate::persist::write_atomically(&path, &contents)
+            .with_context(|| format!("writing preset to {}", path.display()))
+    }
+}
+
+/// The directory presets are saved in.
+fn dir() -> anyhow::Result<PathBuf> {
+    let dir = Profile::config_dir().context("no config directory is available")?;
+    Ok(dir.join(PRESETS_DIR_NAME))
+}
+
+/// Where the preset called `name` is saved. Names are used as file names, so they're
+/// kept to letters, numbers, dashes, and underscores.
+fn path(name: &str) -> anyhow::Result<PathBuf> {
+    let allowed = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || !name.chars().all(allowed) {
+        anyhow::bail!("preset names can only have letters, numbers, dashes, and underscores");
+    }
+    Ok(dir()?.join(format!("{name}.toml")))
+}
+
+/// The names of every saved preset, in alphabetical order.
+fn names() -> anyhow::Result<Vec<String>> {
+    let dir = dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    let entries =
+        fs::read_dir(&dir).with_context(|| format!("reading presets from {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                names.push(name.to_owned());
+            }
+        }
+    }
+    names.sort();
+
+    Ok(names)
+}
+
+pub fn run(command: PresetCommand) -> anyhow::Result<()> {
+    match command {
+        PresetCommand::Save { name, table } => {
+            let profile = Profile::load()?.unwrap_or_default();
+            Preset::new(table, &profile)?.save(&name)?;
+            println!("Saved {name}. Play it with: dai-di play --preset {name}");
+        }
+        PresetCommand::List => {
+            let names = names()?;
+            if names.is_empty() {
+                println!("No presets have been saved yet.");
+            }
+            for name in names {
+                match Preset::load(&name) {
+                    Ok(preset) => print_preset(&name, &preset),
+                    Err(e) => println!("{name}: {e:#}"),
+                }
+            }
+        }
+        PresetCommand::Edit { name } => edit(&name)?,
+        PresetCommand::Delete { name } => {
+            let path = path(&name)?;
+            if !path.exists() {
+                anyhow::bail!("there's no preset called {name}");
+            }
+            fs::remove_file(&path).with_context(|| format!("deleting {}", path.display()))?;
+            println!("Deleted {name}.");
+        }
+    }
+
+    Ok(())
+}
+
+fn print_preset(name: &str, preset: &Preset) {
+    let games = match preset.games {
+        1 => "1 game".to_owned(),
+        games => format!("{games} games"),
+    };
+    let seats: Vec<_> = preset.seats.iter().map(SeatSetup::to_string).collect();
+    println!("{name}: {games} with {}", seats.join(", "));
+
+    let mut rules = Vec::new();
+    if preset.rated {
+        rules.push("rated".to_owned());
+    }
+    if preset.instant_wins {
+        rules.push("instant wins".to_owned());
+    }
+    if preset.flush_beats_full_house {
+        rules.push("a flush beats a full house".to_owned());
+    }
+    if preset.three_of_diamonds_opens_alone {
+        rules.push("the 3♦ opens alone".to_owned());
+    }
+    if preset.must_play_lowest_single_first {
+        rules.push("the lowest single opens".to_owned());
+    }
+    if preset.pass_card_penalty {
+        rules.push("the pass card penalty".to_owned());
+    }
+    if preset.suit_precedence != SuitPrecedence::Standard {
+        rules.push(format!("{} suits", preset.suit_precedence));
+    }
+    if !rules.is_empty() {
+        println!("  Rules: {}", rules.join(", "));
+    }
+}
+
+/// Open the preset called `name` in the editor given by `VISUAL` or `EDITOR`, then check
+/// that it can still be played.
+fn edit(name: &str) -> anyhow::Result<()> {
+    let path = path(name)?;
+    if !path.exists() {
+        anyhow::bail!("there's no preset called {name}");
+    }
+    let Some(editor) = std::env::var_os("VISUAL").or_else(|| std::env::var_os("EDITOR")) else {
+        println!(
+            "Set VISUAL or EDITOR to edit presets from here, or edit {} by hand.",
+            path.display()
+        );
+        return Ok(());
+    };
+    let status = process::Command::new(&editor)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("running {}", editor.to_string_lossy()))?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {status}", editor.to_string_lossy());
+    }
+    Preset::load(name).context("the edited preset can't be played until it's fixed")?;
+    println!("Saved {name}.");
+
+    Ok(())
+}
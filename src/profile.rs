@@ -0,0 +1,345 @@
+use anyhow::Context;
+use card_games::{
+    cho_dai_di::{ScoringRules, SuitPrecedence},
+    collections::SortCardsBy,
+    palette::Palette,
+    player::{ai::Difficulty, human::Keybindings},
+    r#match::Rules,
+    render::CardStyle,
+    score_format::ScoreFormatter,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{IsTerminal, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+const PROFILE_FILE_NAME: &str = "profile.toml";
+
+/// Settings that persist between runs of the game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub sort_cards_by: SortCardsBy,
+    /// How long to pause between each card when revealing a newly dealt hand.
+    pub deal_delay_ms: u64,
+    /// How long to pause after each computer player's turn.
+    pub turn_delay_ms: u64,
+    /// The separator placed between groups of digits in scores. Set to `""` to disable.
+    pub score_digit_separator: String,
+    /// If true, scores are colored by whether they're positive or negative.
+    pub color_scores: bool,
+    /// If true, cards are colored by suit.
+    pub color_cards: bool,
+    /// A unit shown after each score, e.g. "chips"
+    pub score_unit: Option<String>,
+    /// If true, every player's remaining cards are shown when the game ends.
+    pub reveal_hands_at_game_end: bool,
+    /// If true, passing explains which plays were given up.
+    pub explain_passes: bool,
+    /// If true, each play must be confirmed before it's made.
+    pub confirm_plays: bool,
+    /// If true, players draw cards to decide who goes first instead of the holder of
+    /// the 3♦ leading.
+    pub seat_draw: bool,
+    /// The colors used for scores and cards
+    pub palette: Palette,
+    /// The keys used for shortcuts at the play prompt
+    pub keybindings: Keybindings,
+    /// How the cards left in each hand are scored
+    pub scoring: ScoringRules,
+    /// If set, a transcript of each session is saved to this directory
+    pub transcript_dir: Option<PathBuf>,
+    /// If set, your moves are read from this terminal device instead of stdin
+    pub input_terminal: Option<PathBuf>,
+    /// If true, opponents are chosen each game from the roster in `personalities.toml`
+    pub rotate_ai_personalities: bool,
+    /// How well computer players play. If unset, they pick plays at random. This doesn't
+    /// apply to personalities from the roster.
+    pub ai_difficulty: Option<Difficulty>,
+    /// If true, games are rated: hints, shortcuts that pick cards, pass explanations,
+    /// score previews, confirmations, and revealed hands are all turned off.
+    pub rated: bool,
+    /// If true, a player dealt a card of every rank from 3 through A wins instantly
+    pub instant_wins: bool,
+    /// If true, a flush beats a full house instead of the other way around
+    pub flush_beats_full_house: bool,
+    /// If true, the 3♦ must be played on its own when it opens the game
+    pub three_of_diamonds_opens_alone: bool,
+    /// If true, the first play of the game must be the first player's lowest card alone
+    pub must_play_lowest_single_first: bool,
+    /// If true, a player who passes when they could have led must reveal their lowest
+    /// card, or draw a penalty marker once every card they hold is revealed
+    pub pass_card_penalty: bool,
+    /// The order that suits break ties in
+    pub suit_precedence: SuitPrecedence,
+    /// If set, a replay of each game is saved to this directory
+    pub replay_dir: Option<PathBuf>,
+    /// How long to pause after each turn when playing back a replay.
+    pub replay_delay_ms: u64,
+    /// How well the computer plays for someone who's lost their connection to a server
+    pub stand_in_difficulty: Difficulty,
+    /// If set, the computer plays a demo game at the main menu once it's been left alone
+    /// for this many seconds.
+    pub attract_mode_after_secs: Option<u64>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile {
+            name: "Player".to_owned(),
+            sort_cards_by: SortCardsBy::Rank,
+            deal_delay_ms: 0,
+            turn_delay_ms: 0,
+            score_digit_separator: ",".to_owned(),
+            color_scores: true,
+            color_cards: true,
+            score_unit: None,
+            reveal_hands_at_game_end: false,
+            explain_passes: false,
+            confirm_plays: true,
+            seat_draw: false,
+            palette: Palette::Standard,
+            keybindings: Keybindings::default(),
+            scoring: ScoringRules::default(),
+            transcript_dir: None,
+            input_terminal: None,
+            rotate_ai_personalities: false,
+            ai_difficulty: None,
+            rated: false,
+            instant_wins: false,
+            flush_beats_full_house: false,
+            three_of_diamonds_opens_alone: false,
+            must_play_lowest_single_first: false,
+            pass_card_penalty: false,
+            suit_precedence: SuitPrecedence::Standard,
+            replay_dir: None,
+            replay_delay_ms: 1000,
+            stand_in_difficulty: Difficulty::Medium,
+            attract_mode_after_secs: None,
+        }
+    }
+}
+
+impl Profile {
+    /// The house rules games are played by.
+    pub fn rules(&self) -> Rules {
+        Rules::default()
+            .instant_wins_enabled(self.instant_wins)
+            .flush_beats_full_house(self.flush_beats_full_house)
+            .three_of_diamonds_opens_alone(self.three_of_diamonds_opens_alone)
+            .must_play_lowest_single_first(self.must_play_lowest_single_first)
+            .pass_card_penalty(self.pass_card_penalty)
+            .suit_precedence(self.suit_precedence)
+            .scoring(self.scoring)
+            .rated(self.rated)
+    }
+
+    pub fn deal_delay(&self) -> Duration {
+        Duration::from_millis(self.deal_delay_ms)
+    }
+
+    pub fn turn_delay(&self) -> Duration {
+        Duration::from_millis(self.turn_delay_ms)
+    }
+
+    pub fn replay_delay(&self) -> Duration {
+        Duration::from_millis(self.replay_delay_ms)
+    }
+
+    /// How long the main menu waits for someone before playing a demo game, if it does.
+    pub fn attract_mode_after(&self) -> Option<Duration> {
+        self.attract_mode_after_secs.map(Duration::from_secs)
+    }
+
+    pub fn score_formatter(&self) -> ScoreFormatter {
+        ScoreFormatter {
+            digit_separator: self.score_digit_separator.chars().next(),
+            // See https://no-color.org
+            color: self.color_scores && std::env::var_os("NO_COLOR").is_none(),
+            palette: self.palette,
+            unit: self.score_unit.clone(),
+        }
+    }
+
+    pub fn card_style(&self) -> CardStyle {
+        CardStyle {
+            // See https://no-color.org. Piped output is left plain too.
+            color: self.color_cards
+                && std::env::var_os("NO_COLOR").is_none()
+                && std::io::stdout().is_terminal(),
+            palette: self.palette,
+        }
+    }
+
+    /// The directory where the profile and any other settings are stored.
+    pub fn config_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("dai-di"))
+    }
+
+    fn path() -> Option<PathBuf> {
+        Self::config_dir().map(|dir| dir.join(PROFILE_FILE_NAME))
+    }
+
+    /// Load the saved profile. Returns `None` if no profile has been saved yet.
+    pub fn load() -> anyhow::Result<Option<Self>> {
+        let Some(path) = Self::path() else {
+            return Ok(None);
+        };
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("reading profile from {}", path.display()))?;
+        let profile: Profile = toml::from_str(&contents)
+            .with_context(|| format!("parsing profile at {}", path.display()))?;
+        profile
+            .keybindings
+            .validate()
+            .with_context(|| format!("checking keybindings in {}", path.display()))?;
+
+        Ok(Some(profile))
+    }
+
+    /// Save this profile so that it's used the next time the game is started.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let dir = Self::config_dir().context("no config directory is available")?;
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("creating config directory {}", dir.display()))?;
+        let path = dir.join(PROFILE_FILE_NAME);
+        let contents = toml::to_string_pretty(self).context("serializing profile")?;
+        crate::persist::write_atomically(&path, &contents)
+            .with_context(|| format!("writing profile to {}", path.display()))
+    }
+}
+
+/// Run the first-time setup, asking the player for their preferences.
+pub fn onboard() -> anyhow::Result<Profile> {
+    let mut profile = Profile::default();
+
+    println!("Welcome to Dai Di! Let's get you set up.");
+    let name = prompt(&format!("What's your name? [{}] ", profile.name))?;
+    if !name.is_empty() {
+        profile.name = name;
+    }
+
+    let sort = prompt("Sort your hand by rank or by suit? [rank] ")?;
+    profile.sort_cards_by = match sort.to_ascii_lowercase().as_str() {
+        "s" | "suit" => SortCardsBy::Suit,
+        _ => SortCardsBy::Rank,
+    };
+
+    let rendering = prompt(
+        "Show cards in color, in colorblind-safe colors, in high-contrast colors, or as plain text? [color] ",
+    )?;
+    match rendering.to_ascii_lowercase().as_str() {
+        "" | "color" => {}
+        "colorblind-safe" | "colorblind" => profile.palette = Palette::ColorblindSafe,
+        "high-contrast" | "contrast" => profile.palette = Palette::HighContrast,
+        "plain" | "plain text" => {
+            profile.color_cards = false;
+            profile.color_scores = false;
+        }
+        _ => println!("'{rendering}' isn't one of those, so cards are shown in color"),
+    }
+
+    let tutorial = prompt("Would you like a quick rundown of the rules? (y/n) [n] ")?;
+    if matches!(tutorial.to_ascii_lowercase().as_str(), "y" | "yes") {
+        print_tutorial();
+    }
+
+    match profile.save() {
+        Ok(()) => println!("Your preferences have been saved."),
+        Err(e) => println!("Your preferences couldn't be saved: {e:#}"),
+    }
+
+    Ok(profile)
+}
+
+/// Let the player change their most common settings, from the main menu.
+pub fn edit_settings() -> anyhow::Result<()> {
+    let mut profile = Profile::load()?.unwrap_or_default();
+
+    println!("Press Enter to keep a setting as it is.");
+    let name = prompt(&format!("What's your name? [{}] ", profile.name))?;
+    if !name.is_empty() {
+        profile.name = name;
+    }
+
+    let sort = prompt(&format!(
+        "Sort your hand by rank or by suit? [{}] ",
+        profile.sort_cards_by
+    ))?;
+    match sort.to_ascii_lowercase().as_str() {
+        "" => {}
+        "r" | "rank" => profile.sort_cards_by = SortCardsBy::Rank,
+        "s" | "suit" => profile.sort_cards_by = SortCardsBy::Suit,
+        _ => println!(
+            "'{sort}' isn't rank or suit, so your hand is still sorted by {}",
+            profile.sort_cards_by
+        ),
+    }
+
+    let current = profile
+        .ai_difficulty
+        .map_or_else(|| "random".to_owned(), |it| it.to_string());
+    let difficulty = prompt(&format!(
+        "How well should the computer play: easy, medium, hard, or random? [{current}] "
+    ))?;
+    match difficulty.to_ascii_lowercase().as_str() {
+        "" => {}
+        "random" => profile.ai_difficulty = None,
+        other => match other.parse() {
+            Ok(difficulty) => profile.ai_difficulty = Some(difficulty),
+            Err(e) => println!("{e:#}, so the computer still plays at {current}"),
+        },
+    }
+
+    let delay = prompt(&format!(
+        "How many milliseconds should the computer pause after its turns? [{}] ",
+        profile.turn_delay_ms
+    ))?;
+    if !delay.is_empty() {
+        match delay.parse() {
+            Ok(delay) => profile.turn_delay_ms = delay,
+            Err(_) => println!("'{delay}' isn't a number, so the pause is unchanged"),
+        }
+    }
+
+    profile.save()?;
+    if let Some(path) = Profile::path() {
+        println!(
+            "Your settings have been saved. Everything else can be changed in {}",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn prompt(message: &str) -> anyhow::Result<String> {
+    print!("{message}");
+    std::io::stdout()
+        .flush()
+        .with_context(|| format!("flushing '{message}' prompt"))?;
+    let mut buf = String::new();
+    std::io::stdin().read_line(&mut buf)?;
+
+    Ok(buf.trim().to_owned())
+}
+
+fn print_tutorial() {
+    println!();
+    println!("Each player is dealt 13 cards. The first player to play all of their cards wins.");
+    println!("The player holding the 3♦ starts, and their first play must include it.");
+    println!("You may play a single card, a pair, a triplet, or a five-card hand.");
+    println!("Each play must match the size of the last play and beat it.");
+    println!("Twos are the highest rank and threes are the lowest.");
+    println!("Suits break ties: ♦ < ♣ < ♥ < ♠.");
+    println!("If everyone else passes, you may start fresh with any valid play.");
+    println!();
+}
@@ -0,0 +1,335 @@
+//! A quiz on which plays may follow which, to help players learn the rules.
+
+use anyhow::Context;
+use card_games::{
+    card::{Card, STANDARD_DECK},
+    cho_dai_di::{ChoDaiDi, PlayError, SuitPrecedence},
+    collections::Cards,
+    rank::Rank,
+    suit::Suit,
+};
+use rand::{seq::SliceRandom, Rng};
+use std::io::Write;
+
+const NUMBER_OF_QUESTIONS: usize = 10;
+
+pub fn run() -> anyhow::Result<()> {
+    let mut rng = rand::thread_rng();
+    let mut correct = 0;
+
+    println!("Welcome to the rules quiz! Enter 'q' at any time to stop.");
+    for number in 1..=NUMBER_OF_QUESTIONS {
+        println!();
+        let question = if rng.gen_bool(0.5) {
+            Question::can_follow(&mut rng)
+        } else {
+            Question::better_hand(&mut rng)
+        };
+
+        let Some(answered_correctly) = question.ask(number)? else {
+            break;
+        };
+        if answered_correctly {
+            correct += 1;
+        }
+    }
+
+    println!();
+    println!("You answered {correct} out of {NUMBER_OF_QUESTIONS} questions correctly.");
+
+    Ok(())
+}
+
+enum Question {
+    /// Can `play` follow `last_play`?
+    CanFollow {
+        last_play: Cards<ChoDaiDi>,
+        play: Cards<ChoDaiDi>,
+    },
+    /// Which of two five-card hands is higher?
+    BetterHand {
+        a: Cards<ChoDaiDi>,
+        b: Cards<ChoDaiDi>,
+    },
+}
+
+impl Question {
+    fn can_follow(rng: &mut impl Rng) -> Self {
+        let size = *[1, 2, 3, 5].choose(rng).unwrap();
+        let last_play = random_hand(rng, size);
+        // Both plays come from the same deck, so they can't share a card
+        let play = loop {
+            // Occasionally ask about a play that isn't even valid
+            let play = if rng.gen_bool(0.2) {
+                random_cards(rng, size)
+            } else {
+                random_hand(rng, size)
+            };
+            if !shares_a_card(&last_play, &play) {
+                break play;
+            }
+        };
+
+        Question::CanFollow { last_play, play }
+    }
+
+    fn better_hand(rng: &mut impl Rng) -> Self {
+        loop {
+            let a = random_hand(rng, 5);
+            let b = random_hand(rng, 5);
+            // Only ask about hands that could be dealt from one deck and can be compared
+            if !shares_a_card(&a, &b)
+                && (a.may_be_followed_by(&b).is_ok() || b.may_be_followed_by(&a).is_ok())
+            {
+                return Question::BetterHand { a, b };
+            }
+        }
+    }
+
+    /// Ask the question. Returns `None` if the player wants to stop.
+    fn ask(&self, number: usize) -> anyhow::Result<Option<bool>> {
+        let answer = match self {
+            Question::CanFollow { last_play, play } => {
+                println!("Question {number}: the last play was {last_play}.");
+                prompt(&format!("Can it be followed by {play}? (y/n) "))?
+            }
+            Question::BetterHand { a, b } => {
+                println!("Question {number}: which hand is higher?");
+                println!("\t(a) {a}");
+                println!("\t(b) {b}");
+                prompt("Your answer (a/b): ")?
+            }
+        };
+        let Some(answer) = answer else {
+            return Ok(None);
+        };
+
+        let (answered_correctly, feedback) = self.mark(&answer);
+        println!("{feedback}");
+
+        Ok(Some(answered_correctly))
+    }
+
+    /// Mark an answer, returning whether it's correct and what to tell the player.
+    fn mark(&self, answer: &str) -> (bool, String) {
+        match self {
+            Question::CanFollow { last_play, play } => {
+                let says_yes = matches!(answer, "y" | "yes");
+                match last_play.may_be_followed_by(play) {
+                    Ok(()) if says_yes => (true, "Correct!".to_owned()),
+                    Ok(()) => (false, format!("Wrong! {play} beats {last_play}.")),
+                    Err(_) if !says_yes => (true, "Correct!".to_owned()),
+                    Err(e) => (false, format!("Wrong! {}", explain(&e))),
+                }
+            }
+            Question::BetterHand { a, b } => {
+                let b_is_higher = a.may_be_followed_by(b).is_ok();
+                let (higher, lower) = if b_is_higher { (b, a) } else { (a, b) };
+                let answered_correctly = match answer {
+                    "a" => !b_is_higher,
+                    "b" => b_is_higher,
+                    _ => false,
+                };
+
+                if answered_correctly {
+                    (true, "Correct!".to_owned())
+                } else {
+                    let e = higher.may_be_followed_by(lower).unwrap_err();
+                    (false, format!("Wrong! {higher} is higher: {}", explain(&e)))
+                }
+            }
+        }
+    }
+}
+
+fn shares_a_card(a: &Cards<ChoDaiDi>, b: &Cards<ChoDaiDi>) -> bool {
+    a.iter().any(|card| b.contains(card))
+}
+
+/// Print a prompt and read the answer. Returns `None` if the player wants to stop.
+fn prompt(message: &str) -> anyhow::Result<Option<String>> {
+    print!("{message}");
+    std::io::stdout().flush().context("flushing quiz prompt")?;
+    let mut buf = String::new();
+    let read = std::io::stdin().read_line(&mut buf)?;
+    let answer = buf.trim().to_ascii_lowercase();
+
+    // Stop at the end of the input as well, e.g. when answers are piped in
+    if read == 0 || answer == "q" || answer == "quit" {
+        Ok(None)
+    } else {
+        Ok(Some(answer))
+    }
+}
+
+/// Explain why a play was rejected.
+fn explain(e: &PlayError) -> String {
+    match e {
+        PlayError::TooLow {
+            required: Some(card),
+            ..
+        } => format!("{e}; the key card needed to be at least {card}."),
+        PlayError::TooLow { required: None, .. } => format!("{e}, and nothing can beat it."),
+        _ => format!("{e}."),
+    }
+}
+
+/// Pick `size` random cards, which may or may not be a valid hand.
+fn random_cards(rng: &mut impl Rng, size: usize) -> Cards<ChoDaiDi> {
+    STANDARD_DECK
+        .choose_multiple(rng, size)
+        .copied()
+        .collect::<Vec<_>>()
+        .into()
+}
+
+/// Pick a random valid hand of `size` cards.
+fn random_hand(rng: &mut impl Rng, size: usize) -> Cards<ChoDaiDi> {
+    let rank = *Cards::<ChoDaiDi>::RANK_PRECEDENCE.choose(rng).unwrap();
+    let suit = *SuitPrecedence::Standard.suits().choose(rng).unwrap();
+
+    let cards = match size {
+        1..=3 => of_rank(rng, rank, size),
+        5 => match rng.gen_range(0..4) {
+            0 => {
+                let mut cards: Vec<Card> = of_suit(suit);
+                cards.shuffle(rng);
+                cards.truncate(5);
+                cards
+            }
+            1 => {
+                let other_rank = loop {
+                    let other_rank = *Cards::<ChoDaiDi>::RANK_PRECEDENCE.choose(rng).unwrap();
+                    if other_rank != rank {
+                        break other_rank;
+                    }
+                };
+                let mut cards = of_rank(rng, rank, 3);
+                cards.extend(of_rank(rng, other_rank, 2));
+                cards
+            }
+            2 => {
+                let mut cards = of_rank(rng, rank, 4);
+                let extra = loop {
+                    let card = *STANDARD_DECK.choose(rng).unwrap();
+                    if card.rank() != rank {
+                        break card;
+                    }
+                };
+                cards.push(extra);
+                cards
+            }
+            _ => {
+                // Any run of five ranks, with Cho Dai Di's ordering of ranks
+                let start = rng.gen_range(0..=Cards::<ChoDaiDi>::RANK_PRECEDENCE.len() - 6);
+                Cards::<ChoDaiDi>::RANK_PRECEDENCE[start..start + 5]
+                    .iter()
+                    .map(|&rank| of_rank(rng, rank, 1)[0])
+                    .collect()
+            }
+        },
+        _ => unreachable!("hands are always 1, 2, 3, or 5 cards"),
+    };
+
+    cards.into()
+}
+
+fn of_rank(rng: &mut impl Rng, rank: Rank, count: usize) -> Vec<Card> {
+    STANDARD_DECK
+        .iter()
+        .filter(|card| card.rank() == rank)
+        .copied()
+        .collect::<Vec<_>>()
+        .choose_multiple(rng, count)
+        .copied()
+        .collect()
+}
+
+fn of_suit(suit: Suit) -> Vec<Card> {
+    STANDARD_DECK
+        .iter()
+        .filter(|card| card.suit() == suit)
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shares_a_card, Question};
+    use card_games::{cho_dai_di::ChoDaiDi, collections::Cards};
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    fn cards(cards: Vec<&str>) -> Cards<ChoDaiDi> {
+        Cards::try_from(cards).unwrap()
+    }
+
+    #[test]
+    fn test_questions_are_dealt_from_one_deck() {
+        let mut rng = SmallRng::seed_from_u64(518);
+        for _ in 0..500 {
+            match Question::better_hand(&mut rng) {
+                Question::BetterHand { a, b } => {
+                    assert!(!shares_a_card(&a, &b), "{a} and {b}");
+                    assert!(a.classify().is_some() && b.classify().is_some());
+                    assert_eq!((a.len(), b.len()), (5, 5));
+                }
+                Question::CanFollow { .. } => unreachable!(),
+            }
+            match Question::can_follow(&mut rng) {
+                Question::CanFollow { last_play, play } => {
+                    assert!(!shares_a_card(&last_play, &play), "{last_play} and {play}");
+                    assert!(last_play.classify().is_some(), "{last_play}");
+                    assert_eq!(last_play.len(), play.len());
+                }
+                Question::BetterHand { .. } => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_mark_can_follow() {
+        let question = Question::CanFollow {
+            last_play: cards(vec!["3D"]),
+            play: cards(vec!["4C"]),
+        };
+        assert_eq!(question.mark("y"), (true, "Correct!".to_owned()));
+        assert!(question.mark("yes").0);
+        assert_eq!(
+            question.mark("n"),
+            (false, "Wrong! 4♣ beats 3♦.".to_owned())
+        );
+
+        let question = Question::CanFollow {
+            last_play: cards(vec!["9S", "9H"]),
+            play: cards(vec!["5D", "5C"]),
+        };
+        assert!(question.mark("n").0);
+        let (correct, feedback) = question.mark("y");
+        assert!(!correct);
+        assert!(feedback.starts_with("Wrong!"), "{feedback}");
+    }
+
+    #[test]
+    fn test_mark_better_hand() {
+        let flush = cards(vec!["3H", "6H", "8H", "JH", "KH"]);
+        let straight = cards(vec!["4D", "5C", "6S", "7D", "8C"]);
+        let question = Question::BetterHand {
+            a: flush.clone(),
+            b: straight.clone(),
+        };
+        assert_eq!(question.mark("a"), (true, "Correct!".to_owned()));
+        let (correct, feedback) = question.mark("b");
+        assert!(!correct);
+        assert!(
+            feedback.starts_with(&format!("Wrong! {flush} is higher")),
+            "{feedback}"
+        );
+        assert!(!question.mark("c").0);
+
+        let question = Question::BetterHand {
+            a: straight,
+            b: flush,
+        };
+        assert!(question.mark("b").0);
+    }
+}
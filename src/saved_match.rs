@@ -0,0 +1,97 @@
+//! Saving a match between its games, so that it can be resumed from the main menu after
+//! quitting partway through.
+
+use crate::{player::Player, profile::Profile};
+use anyhow::Context;
+use card_games::{player::ai::Difficulty, r#match::Match};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+const SAVED_MATCH_FILE_NAME: &str = "saved-match.toml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedMatch {
+    /// The version of the game that saved the match. Matches saved before versions were
+    /// recorded have none, and aren't checked.
+    #[serde(default)]
+    pub version: String,
+    /// The [fingerprint](card_games::r#match::Rules::fingerprint) of the match's rules
+    #[serde(default)]
+    pub rules: String,
+    pub r#match: Match,
+    /// Who's playing each seat
+    #[serde(rename = "seat")]
+    pub seats: Vec<SavedSeat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSeat {
+    /// If true, the seat's played by a person at the keyboard
+    pub human: bool,
+    /// How well the computer plays the seat, if it was set up with a difficulty
+    pub difficulty: Option<Difficulty>,
+}
+
+fn path() -> Option<PathBuf> {
+    Profile::config_dir().map(|dir| dir.join(SAVED_MATCH_FILE_NAME))
+}
+
+/// Save a match that's still being played, replacing any match saved before.
+pub fn save<const PLAYERS: usize>(
+    r#match: &Match,
+    players: &[Box<dyn Player<PLAYERS>>],
+) -> anyhow::Result<()> {
+    let saved = SavedMatch {
+        version: card_games::VERSION.to_owned(),
+        rules: r#match.rules().fingerprint(),
+        r#match: r#match.clone(),
+        seats: players
+            .iter()
+            .map(|player| SavedSeat {
+                human: player.is_human(),
+                difficulty: player.difficulty(),
+            })
+            .collect(),
+    };
+    let dir = Profile::config_dir().context("no config directory is available")?;
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("creating config directory {}", dir.display()))?;
+    let path = dir.join(SAVED_MATCH_FILE_NAME);
+    let contents = toml::to_string_pretty(&saved).context("serializing match")?;
+    crate::persist::write_atomically(&path, &contents)
+        .with_context(|| format!("saving match to {}", path.display()))
+}
+
+/// Load the saved match. Returns `None` if there isn't one.
+pub fn load() -> anyhow::Result<Option<SavedMatch>> {
+    let Some(path) = path().filter(|path| path.exists()) else {
+        return Ok(None);
+    };
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("reading saved match from {}", path.display()))?;
+    let saved: SavedMatch = toml::from_str(&contents)
+        .with_context(|| format!("parsing saved match at {}", path.display()))?;
+    if !saved.version.is_empty() {
+        saved
+            .r#match
+            .rules()
+            .check_fingerprint(&saved.rules, &saved.version)
+            .with_context(|| {
+                format!(
+                    "the saved match at {} can't be resumed, so delete it to start a new one",
+                    path.display()
+                )
+            })?;
+    }
+
+    Ok(Some(saved))
+}
+
+/// Forget the saved match, once it's been played to the end.
+pub fn remove() -> anyhow::Result<()> {
+    match path().filter(|path| path.exists()) {
+        Some(path) => fs::remove_file(&path)
+            .with_context(|| format!("removing saved match {}", path.display())),
+        None => Ok(()),
+    }
+}
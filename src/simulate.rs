@@ -0,0 +1,246 @@
+//! Pitting computer players' strategies against each other over many games, run with
+//! `dai-di simulate`.
+
+use crate::cli::SimulateArgs;
+use anyhow::Context;
+use card_games::{
+    cho_dai_di::FOUR_PLAYERS,
+    dataset,
+    player::ai::{Strategy, Weights},
+    r#match::Rules,
+    simulation::{self, Scenario, SeatResults, Summary},
+};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+
+/// How many games are played at a time when there's a weights file to check for changes
+/// or a dataset to write between batches
+const BATCH_GAMES: usize = 500;
+
+pub fn run(args: SimulateArgs) -> anyhow::Result<()> {
+    let SimulateArgs {
+        games,
+        seed,
+        strategies,
+        weights,
+        dataset: dataset_path,
+        rules,
+        scenario,
+        output,
+    } = args;
+    if let Some(path) = scenario {
+        let output = output.unwrap_or_else(|| path.with_extension("results.toml"));
+        return run_scenario(&path, &output);
+    }
+    let seed = seed.unwrap_or_else(rand::random);
+    // The profile's house rules apply, so the results match the games you'd play
+    let mut profile = crate::profile::Profile::load()?.unwrap_or_default();
+    rules.apply(&mut profile);
+    let rules = profile.rules();
+
+    let mut weights = weights.map(WeightsFile::new);
+    if let Some(weights) = &weights {
+        if !strategies.contains(&Strategy::Weighted) {
+            anyhow::bail!("only weighted seats play by --weights, so give some with --strategies");
+        }
+        println!(
+            "Weighted seats play by {}, which is read again whenever it changes.",
+            weights.path.display()
+        );
+    }
+
+    let mut dataset = match &dataset_path {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("creating dataset {}", path.display()))?;
+            let mut writer = BufWriter::new(file);
+            dataset::write_header(&mut writer).context("writing the dataset")?;
+            println!("Writing every turn to {}.", path.display());
+            Some(writer)
+        }
+        None => None,
+    };
+
+    let started_at = Instant::now();
+    let weights_file = weights.as_mut();
+    let writer = dataset.as_mut().map(|writer| writer as &mut dyn Write);
+    let summary = match strategies.len() {
+        2 => simulate::<2>(strategies, rules, games, seed, weights_file, writer)?,
+        3 => simulate::<3>(strategies, rules, games, seed, weights_file, writer)?,
+        FOUR_PLAYERS => {
+            simulate::<FOUR_PLAYERS>(strategies, rules, games, seed, weights_file, writer)?
+        }
+        n => anyhow::bail!("games must have from 2 to {FOUR_PLAYERS} players, not {n}"),
+    };
+    let elapsed = started_at.elapsed();
+    if let (Some(mut writer), Some(path)) = (dataset, dataset_path) {
+        writer.flush().context("writing the dataset")?;
+        println!("Wrote the dataset to {}", path.display());
+    }
+
+    println!(
+        "Simulated {games} games in {:.1}s ({:.1} games per second)",
+        elapsed.as_secs_f64(),
+        games as f64 / elapsed.as_secs_f64()
+    );
+    println!("Dealt from seed {seed}, so '--seed {seed}' deals the same hands again.");
+    println!();
+    print_summary(&summary);
+
+    Ok(())
+}
+
+/// Play `games` games dealt from seeds counting up from `seed`. If there's a weights
+/// file or a dataset, the games are played in batches. The weights file is read again
+/// before any batch if it's changed, keeping the standings from the games played so
+/// far, and every turn of each batch is written to the dataset.
+fn simulate<const PLAYERS: usize>(
+    strategies: Vec<Strategy>,
+    rules: Rules,
+    games: usize,
+    seed: u64,
+    mut weights_file: Option<&mut WeightsFile>,
+    mut dataset: Option<&mut dyn Write>,
+) -> anyhow::Result<Summary> {
+    let strategies: [Strategy; PLAYERS] = strategies.try_into().unwrap();
+    if weights_file.is_none() && dataset.is_none() {
+        return simulation::simulate_with_seed(strategies, rules, games, seed);
+    }
+
+    let mut weights = match weights_file.as_deref_mut() {
+        Some(weights_file) => weights_file.read()?,
+        None => Weights::default(),
+    };
+    let mut summary = Summary::new(&strategies);
+    while summary.games() < games {
+        if let Some(weights_file) = weights_file
+            .as_deref_mut()
+            .filter(|weights_file| weights_file.has_changed())
+        {
+            // A file that's saved with a mistake in it, or half-saved, doesn't end the run
+            match weights_file.read() {
+                Ok(changed) => {
+                    println!();
+                    println!("After {} games:", summary.games());
+                    print_summary(&summary);
+                    println!("Reloaded {}.", weights_file.path.display());
+                    weights = changed;
+                }
+                Err(e) => println!("Kept playing by the weights from before: {e:#}"),
+            }
+        }
+        let batch = BATCH_GAMES.min(games - summary.games());
+        let first_seed = seed.wrapping_add(summary.games() as u64);
+        let played = match dataset.as_deref_mut() {
+            Some(mut dataset) => {
+                let (played, records) =
+                    simulation::simulate_recording(strategies, &weights, rules, batch, first_seed)?;
+                dataset::write_records(&mut dataset, &records).context("writing the dataset")?;
+                played
+            }
+            None => {
+                simulation::simulate_with_weights(strategies, &weights, rules, batch, first_seed)?
+            }
+        };
+        summary.merge(&played);
+    }
+    println!();
+
+    Ok(summary)
+}
+
+/// A file of [`Weights`], kept track of so that it can be read again once it's changed.
+struct WeightsFile {
+    path: PathBuf,
+    /// When the file was last changed as of the last time it was read
+    modified: Option<SystemTime>,
+}
+
+impl WeightsFile {
+    fn new(path: PathBuf) -> Self {
+        WeightsFile {
+            path,
+            modified: None,
+        }
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    fn has_changed(&self) -> bool {
+        self.modified() != self.modified
+    }
+
+    fn read(&mut self) -> anyhow::Result<Weights> {
+        // Noted first, so that a file that can't be read isn't read again until it changes
+        self.modified = self.modified();
+        let contents = fs::read_to_string(&self.path)
+            .with_context(|| format!("reading weights from {}", self.path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("reading weights from {}", self.path.display()))
+    }
+}
+
+/// Play the scenario saved at `path` and write its results to `output`. Only the
+/// scenario's rules apply, not the profile's, so anyone can repeat the run.
+fn run_scenario(path: &Path, output: &Path) -> anyhow::Result<()> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("reading scenario {}", path.display()))?;
+    let scenario: Scenario = toml::from_str(&contents)
+        .with_context(|| format!("reading scenario {}", path.display()))?;
+
+    let started_at = Instant::now();
+    let results = scenario.run()?;
+    let elapsed = started_at.elapsed();
+    let serialized = toml::to_string(&results).context("serializing the results")?;
+    crate::persist::write_atomically(output, &serialized)?;
+
+    let games = results.scenario.games * results.scenario.seeds.len();
+    println!(
+        "Simulated {games} games in {:.1}s ({:.1} games per second)",
+        elapsed.as_secs_f64(),
+        games as f64 / elapsed.as_secs_f64()
+    );
+    println!();
+    print_seats(&results.seats);
+    println!();
+    println!(
+        "Games lasted {:.1} turns on average.",
+        results.average_turns
+    );
+    println!("Wrote the results to {}", output.display());
+
+    Ok(())
+}
+
+fn print_seats(seats: &[SeatResults]) {
+    println!(
+        "Seat  {:<16}  {:>8}  {:>13}",
+        "Strategy", "Win rate", "Average score"
+    );
+    for (seat, results) in seats.iter().enumerate() {
+        println!(
+            "{:<4}  {:<16}  {:>7.1}%  {:>+13.2}",
+            seat + 1,
+            results.strategy.to_string(),
+            results.win_rate * 100.0,
+            results.average_score
+        );
+    }
+}
+
+fn print_summary(summary: &Summary) {
+    print_seats(&SeatResults::of(summary));
+    println!();
+    println!(
+        "Games lasted {:.1} turns on average.",
+        summary.average_turns()
+    );
+}
@@ -0,0 +1,127 @@
+//! Keeping everyone's record between runs of the game, viewed with `dai-di stats`.
+
+use crate::profile::Profile;
+use anyhow::Context;
+use card_games::{
+    cho_dai_di::ChoDaiDi,
+    collections::Cards,
+    stats::{self, Stats},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+const STATS_FILE_NAME: &str = "stats.json";
+/// How many earlier versions of the stats to keep, as `stats.json.1` and so on
+const STATS_BACKUPS: usize = 3;
+
+/// Held while the stats are updated, since a server plays several games at once
+static SAVING: Mutex<()> = Mutex::new(());
+
+fn path() -> Option<PathBuf> {
+    Profile::config_dir().map(|dir| dir.join(STATS_FILE_NAME))
+}
+
+/// Load the stats saved in the config directory, or empty stats if none have been saved.
+pub fn load() -> anyhow::Result<Stats> {
+    let Some(path) = path().filter(|path| path.exists()) else {
+        return Ok(Stats::default());
+    };
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("reading stats from {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("parsing stats at {}", path.display()))
+}
+
+/// Add a finished game to the saved stats. `names` are the names of the players in each
+/// seat.
+pub fn record_game<const PLAYERS: usize>(
+    names: &[String],
+    game: &ChoDaiDi<PLAYERS>,
+) -> anyhow::Result<()> {
+    let _saving = SAVING.lock().unwrap();
+    let mut stats = load()?;
+    stats.record_game(names, game)?;
+
+    save(&stats)
+}
+
+/// Save the stats to the config directory, keeping the last few versions as backups.
+fn save(stats: &Stats) -> anyhow::Result<()> {
+    let dir = Profile::config_dir().context("no config directory is available")?;
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("creating config directory {}", dir.display()))?;
+    let path = dir.join(STATS_FILE_NAME);
+    let contents = serde_json::to_string_pretty(stats).context("serializing stats")?;
+    crate::persist::rotate_backups(&path, STATS_BACKUPS)?;
+    crate::persist::write_atomically(&path, &contents)
+        .with_context(|| format!("writing stats to {}", path.display()))
+}
+
+pub fn run(import: Option<&Path>) -> anyhow::Result<()> {
+    if let Some(path) = import {
+        import_ledger(path)?;
+        println!();
+    }
+    print_stats(&load()?);
+
+    Ok(())
+}
+
+/// Add the games in a ledger of paper games to the saved stats.
+fn import_ledger(path: &Path) -> anyhow::Result<()> {
+    let file =
+        fs::File::open(path).with_context(|| format!("opening ledger {}", path.display()))?;
+    let games =
+        stats::read_ledger(file).with_context(|| format!("reading ledger {}", path.display()))?;
+
+    let _saving = SAVING.lock().unwrap();
+    let mut stats = load()?;
+    for game in &games {
+        stats.record_ledger_game(game);
+    }
+    save(&stats)?;
+    println!("Imported {} games from {}", games.len(), path.display());
+
+    Ok(())
+}
+
+/// Show everyone's record, from the highest rated player to the lowest.
+pub fn print_stats(stats: &Stats) {
+    let leaderboard = stats.leaderboard();
+    if leaderboard.is_empty() {
+        println!("No games have been finished yet.");
+        return;
+    }
+    let n = leaderboard
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Player".len());
+
+    println!(
+        "{:<n$}  {:>6}  {:>4}  {:>6}  {:>5}  {:>10}  Best hand",
+        "Player", "Rating", "Wins", "Losses", "Rated", "Cards left"
+    );
+    for (name, player) in leaderboard {
+        let best_hand = match &player.best_hand {
+            Some(hand) => {
+                let mut cards = Cards::<ChoDaiDi>::from(hand.cards.clone());
+                cards.sort_by_rank();
+                format!("{} ({cards})", hand.combination)
+            }
+            None => "-".to_owned(),
+        };
+        // Games imported from a ledger don't say how many cards were left
+        let cards_left = match player.games() > player.imported_games {
+            true => format!("{:.1}", player.average_cards_left()),
+            false => "-".to_owned(),
+        };
+        println!(
+            "{name:<n$}  {:>6.0}  {:>4}  {:>6}  {:>5}  {cards_left:>10}  {best_hand}",
+            player.rating, player.wins, player.losses, player.rated_games,
+        );
+    }
+}
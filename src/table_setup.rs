@@ -0,0 +1,221 @@
+//! Choosing who sits where before a game. The last table set up is remembered, so it can
+//! be kept as it is next time.
+
+use crate::{personality::Roster, profile::Profile, prompt};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, path::PathBuf};
+
+const TABLE_FILE_NAME: &str = "table.toml";
+
+/// Who sits in a seat.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum SeatSetup {
+    /// A person at this keyboard
+    Human { name: String },
+    /// The computer, playing as the personality from the roster with this name. If it's
+    /// unset, any computer player sits here.
+    Computer { personality: Option<String> },
+}
+
+impl fmt::Display for SeatSetup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeatSetup::Human { name } => write!(f, "{name}"),
+            SeatSetup::Computer {
+                personality: Some(name),
+            } => write!(f, "{name} (computer)"),
+            SeatSetup::Computer { personality: None } => write!(f, "any computer player"),
+        }
+    }
+}
+
+/// Who sits in each seat, in seat order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSetup {
+    #[serde(rename = "seat")]
+    pub seats: Vec<SeatSetup>,
+}
+
+impl TableSetup {
+    /// People in the first `humans` of `players` seats, and the computer in the rest.
+    pub fn new(players: usize, humans: usize, player_name: &str) -> Self {
+        let seats = (0..players)
+            .map(|seat| match seat {
+                0 if humans > 0 => SeatSetup::Human {
+                    name: player_name.to_owned(),
+                },
+                seat if seat < humans => SeatSetup::Human {
+                    name: format!("Player {}", seat + 1),
+                },
+                _ => SeatSetup::Computer { personality: None },
+            })
+            .collect();
+
+        TableSetup { seats }
+    }
+
+    pub fn humans(&self) -> usize {
+        self.seats
+            .iter()
+            .filter(|seat| matches!(seat, SeatSetup::Human { .. }))
+            .count()
+    }
+
+    fn path() -> Option<PathBuf> {
+        Profile::config_dir().map(|dir| dir.join(TABLE_FILE_NAME))
+    }
+
+    /// Load the last table that was set up. Returns `None` if there isn't one.
+    pub fn load() -> anyhow::Result<Option<Self>> {
+        let Some(path) = Self::path().filter(|path| path.exists()) else {
+            return Ok(None);
+        };
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("reading table from {}", path.display()))?;
+        let setup = toml::from_str(&contents)
+            .with_context(|| format!("parsing table at {}", path.display()))?;
+
+        Ok(Some(setup))
+    }
+
+    /// Save this table, so that it's offered the next time a game's started.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let dir = Profile::config_dir().context("no config directory is available")?;
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("creating config directory {}", dir.display()))?;
+        let path = dir.join(TABLE_FILE_NAME);
+        let contents = toml::to_string_pretty(self).context("serializing table")?;
+        crate::persist::write_atomically(&path, &contents)
+            .with_context(|| format!("writing table to {}", path.display()))
+    }
+}
+
+/// Ask the person at the keyboard who's sitting where, starting from the last table set
+/// up with the same number of seats, or `default` if there isn't one. The table chosen is
+/// saved for next time.
+pub fn choose(default: TableSetup) -> anyhow::Result<TableSetup> {
+    let mut setup = TableSetup::load()?
+        .filter(|last| last.seats.len() == default.seats.len())
+        .unwrap_or(default);
+
+    say!("Seats:");
+    for (seat, setup) in setup.seats.iter().enumerate() {
+        say!("\t{}. {setup}", seat + 1);
+    }
+    let keep = prompt("Keep these seats? (y/n) [y] ")?.unwrap_or_default();
+    if matches!(keep.to_ascii_lowercase().as_str(), "n" | "no") {
+        let roster = Roster::load_or_create()?;
+        say!("For each seat, enter a person's name, or 'computer' for the computer to play it. Press Enter to keep a seat as it is.");
+        for seat in 0..setup.seats.len() {
+            setup.seats[seat] = choose_seat(&setup, seat, &roster)?;
+        }
+    }
+
+    if let Err(e) = setup.save() {
+        say!("The seats couldn't be saved for next time: {e:#}");
+    }
+
+    Ok(setup)
+}
+
+fn choose_seat(setup: &TableSetup, seat: usize, roster: &Roster) -> anyhow::Result<SeatSetup> {
+    let current = &setup.seats[seat];
+    // Everyone at the table needs a different name
+    let taken = |name: &str| {
+        setup.seats.iter().enumerate().any(|(other, it)| {
+            other != seat
+                && match it {
+                    SeatSetup::Human { name: other } => other.eq_ignore_ascii_case(name),
+                    SeatSetup::Computer {
+                        personality: Some(other),
+                    } => other.eq_ignore_ascii_case(name),
+                    SeatSetup::Computer { personality: None } => false,
+                }
+        })
+    };
+
+    loop {
+        let Some(answer) = prompt(&format!("Seat {} [{current}] ", seat + 1))? else {
+            return Ok(current.clone());
+        };
+        match answer.to_ascii_lowercase().as_str() {
+            "" => return Ok(current.clone()),
+            "computer" | "ai" => {
+                let names: Vec<_> = roster
+                    .personalities
+                    .iter()
+                    .map(|it| it.name.as_str())
+                    .filter(|name| !taken(name))
+                    .collect();
+                return choose_personality(&names);
+            }
+            _ if taken(&answer) => say!("someone else at the table is called {answer}"),
+            _ => return Ok(SeatSetup::Human { name: answer }),
+        }
+    }
+}
+
+fn choose_personality(names: &[&str]) -> anyhow::Result<SeatSetup> {
+    let question = format!(
+        "Which computer player: {}? Press Enter for any. ",
+        names.join(", ")
+    );
+    loop {
+        let answer = prompt(&question)?.unwrap_or_default();
+        if answer.is_empty() {
+            return Ok(SeatSetup::Computer { personality: None });
+        }
+        match names.iter().find(|name| name.eq_ignore_ascii_case(&answer)) {
+            Some(name) => {
+                return Ok(SeatSetup::Computer {
+                    personality: Some((*name).to_owned()),
+                })
+            }
+            None => say!("there's no computer player called {answer} to choose"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SeatSetup, TableSetup};
+
+    #[test]
+    fn test_new() {
+        let setup = TableSetup::new(4, 2, "Tess");
+        assert_eq!(setup.humans(), 2);
+        assert_eq!(
+            setup
+                .seats
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "Tess",
+                "Player 2",
+                "any computer player",
+                "any computer player"
+            ]
+        );
+        assert_eq!(TableSetup::new(3, 0, "Tess").humans(), 0);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let setup = TableSetup {
+            seats: vec![
+                SeatSetup::Computer {
+                    personality: Some("ChoBot".to_owned()),
+                },
+                SeatSetup::Human {
+                    name: "Tess".to_owned(),
+                },
+                SeatSetup::Computer { personality: None },
+            ],
+        };
+        let serialized = toml::to_string(&setup).unwrap();
+        assert_eq!(toml::from_str::<TableSetup>(&serialized).unwrap(), setup);
+    }
+}
@@ -0,0 +1,148 @@
+//! Saving a text transcript of a session.
+//!
+//! Output that should appear in the transcript is printed with [`say!`] instead of
+//! `println!`. Lines that show a player's hand are printed with [`say_hand!`] so that
+//! they can be left out when other people at the table shouldn't see them. That includes
+//! hints, and the cards a player types before they're played, which are recorded with
+//! [`record_hand`].
+
+use anyhow::Context;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Mutex,
+};
+
+static TRANSCRIPT: Mutex<Option<Transcript>> = Mutex::new(None);
+
+struct Transcript {
+    writer: BufWriter<File>,
+    /// If true, lines showing a player's hand are left out
+    hide_hands: bool,
+    /// Text that's been printed without a trailing newline, like a prompt
+    pending: String,
+}
+
+/// Print a line and record it in the transcript.
+macro_rules! say {
+    () => {
+        $crate::transcript::say("")
+    };
+    ($($arg:tt)*) => {
+        $crate::transcript::say(&format!($($arg)*))
+    };
+}
+
+/// Print a line that shows a player's hand. It's only recorded in the transcript if
+/// there's no one else at the table to hide it from.
+macro_rules! say_hand {
+    ($($arg:tt)*) => {
+        $crate::transcript::say_hand(&format!($($arg)*))
+    };
+}
+
+/// Start recording a transcript to a new file at `path`.
+///
+/// If `hide_hands` is true, lines that show a player's hand are replaced with a
+/// placeholder so that the transcript can't be used to see other players' cards.
+pub fn start(path: &Path, hide_hands: bool) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("creating transcript directory {}", dir.display()))?;
+    }
+    let file =
+        File::create(path).with_context(|| format!("creating transcript at {}", path.display()))?;
+
+    *TRANSCRIPT.lock().unwrap() = Some(Transcript {
+        writer: BufWriter::new(file),
+        hide_hands,
+        pending: String::new(),
+    });
+
+    Ok(())
+}
+
+pub fn say(line: &str) {
+    println!("{line}");
+    record(line);
+}
+
+pub fn say_hand(line: &str) {
+    println!("{line}");
+    record_hand(line);
+}
+
+fn hides_hands() -> bool {
+    TRANSCRIPT
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|transcript| transcript.hide_hands)
+}
+
+/// Record a line that shows some of a player's cards without printing it. It's replaced
+/// with a placeholder if hands are hidden.
+pub fn record_hand(line: &str) {
+    if hides_hands() {
+        record("(hand hidden)");
+    } else {
+        record(line);
+    }
+}
+
+/// Record text that's been printed without a newline, such as a prompt. It's written
+/// along with the next recorded line.
+pub fn record_partial(text: &str) {
+    if let Some(transcript) = TRANSCRIPT.lock().unwrap().as_mut() {
+        transcript.pending.push_str(text);
+    }
+}
+
+/// Like [`record_partial`], for text that shows some of a player's cards.
+pub fn record_hand_partial(text: &str) {
+    if hides_hands() {
+        record_partial("(hand hidden) ");
+    } else {
+        record_partial(text);
+    }
+}
+
+/// Record a line in the transcript without printing it.
+pub fn record(line: &str) {
+    let mut guard = TRANSCRIPT.lock().unwrap();
+    let Some(transcript) = guard.as_mut() else {
+        return;
+    };
+
+    let pending = std::mem::take(&mut transcript.pending);
+    let result = writeln!(
+        transcript.writer,
+        "{}{}",
+        strip_colors(&pending),
+        strip_colors(line)
+    )
+    // Flush every line so that the transcript is complete even if the game exits abruptly
+    .and_then(|()| transcript.writer.flush());
+    if let Err(e) = result {
+        tracing::warn!("stopped saving the transcript: {e}");
+        *guard = None;
+    }
+}
+
+/// Remove the escape sequences used to color terminal output, which would only be
+/// noise in a text file.
+fn strip_colors(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Color sequences end with 'm', e.g. "\x1b[31m"
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
@@ -0,0 +1,196 @@
+//! Playing games through the binary, with the human seat's moves read from a script.
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+/// The seed of a deal where the human seat holds the 3♦, so its first move is the
+/// game's first.
+const SEED_WITH_THREE_OF_DIAMONDS: &str = "5";
+
+/// A directory to use as the home directory, so that the player's real profile isn't
+/// read or changed.
+fn home_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("dai-di-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run_script(name: &str, script: &str, args: &[&str]) -> Output {
+    let home = home_dir(name);
+    let output = run_script_in(&home, script, args);
+    let _ = std::fs::remove_dir_all(&home);
+
+    output
+}
+
+/// Run a script with `home` as the home directory, leaving behind whatever it saves.
+fn run_script_in(home: &Path, script: &str, args: &[&str]) -> Output {
+    let script_path = home.join("moves.txt");
+    std::fs::write(&script_path, script).unwrap();
+
+    Command::new(env!("CARGO_BIN_EXE_dai-di"))
+        .arg("--script")
+        .arg(&script_path)
+        .args(["--no-color", "--quiet"])
+        .args(args)
+        .env("HOME", home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .env_remove("DAI_DI_PLAYER_NAME")
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_script_plays_a_whole_game() {
+    // Open with the 3♦, then play the lowest single where one can be played and pass
+    // otherwise, for more turns than the game can last
+    let mut script = "3d # typed rather than picked with a shortcut\n".to_owned();
+    script.push_str(&"l\npass\n".repeat(60));
+
+    let output = run_script("script", &script, &["--seed", SEED_WITH_THREE_OF_DIAMONDS]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(
+        output.status.success(),
+        "{stdout}{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("Player plays 3♦"), "{stdout}");
+    // Plays from a script go ahead without asking for a confirmation
+    assert!(!stdout.contains("confirm?"), "{stdout}");
+    assert!(stdout.contains("Congratulations"), "{stdout}");
+}
+
+#[test]
+fn test_rated_script_has_no_assists() {
+    let script = "l\nhints\nscores\nq\n";
+
+    let output = run_script(
+        "rated",
+        script,
+        &["--seed", SEED_WITH_THREE_OF_DIAMONDS, "--rated"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{stdout}");
+    assert!(
+        stdout.contains("shortcuts that pick cards aren't available in rated games"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("hints aren't available in rated games"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("score previews aren't available in rated games"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("Player plays"), "{stdout}");
+}
+
+#[test]
+fn test_hot_seat_transcript_hides_typed_cards_and_hints() {
+    let home = home_dir("transcript");
+    let profile_dir = home.join(".config").join("dai-di");
+    std::fs::create_dir_all(&profile_dir).unwrap();
+    let transcript_dir = home.join("transcripts");
+    std::fs::write(
+        profile_dir.join("profile.toml"),
+        format!(
+            "transcript_dir = {:?}\n",
+            transcript_dir.display().to_string()
+        ),
+    )
+    .unwrap();
+
+    // The first player opens, then the second asks for hints, tries a card they don't
+    // hold, and quits
+    let script = "3d\nhints\n5s\nq\n";
+    let output = run_script_in(
+        &home,
+        script,
+        &["--seed", SEED_WITH_THREE_OF_DIAMONDS, "--humans", "2"],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("Singles that beat 3♦"), "{stdout}");
+
+    let transcript = std::fs::read_dir(&transcript_dir)
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect::<String>();
+    let _ = std::fs::remove_dir_all(&home);
+
+    assert!(transcript.contains("Player plays 3♦"), "{transcript}");
+    assert!(transcript.contains("Your play: hints"), "{transcript}");
+    assert!(!transcript.contains("Your play: 3d"), "{transcript}");
+    assert!(!transcript.contains("Your play: 5s"), "{transcript}");
+    assert!(!transcript.contains("Singles that beat"), "{transcript}");
+    assert!(!transcript.contains("'s hand:"), "{transcript}");
+}
+
+#[test]
+fn test_script_that_runs_out() {
+    // Blank lines and comments aren't moves
+    let script = "# open with the lowest card\n\n3d # the 3♦\n";
+    let output = run_script(
+        "script-runs-out",
+        script,
+        &["--seed", SEED_WITH_THREE_OF_DIAMONDS],
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("Player plays 3♦"), "{stdout}");
+    assert!(!output.status.success(), "{stdout}");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("ran out of moves"),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_replay_code_plays_back() {
+    let home = home_dir("replay-code");
+    let profile_dir = home.join(".config").join("dai-di");
+    std::fs::create_dir_all(&profile_dir).unwrap();
+    std::fs::write(
+        profile_dir.join("profile.toml"),
+        format!(
+            "replay_dir = {:?}\nreplay_delay_ms = 0\n",
+            home.join("replays").display().to_string()
+        ),
+    )
+    .unwrap();
+
+    let mut script = "3d\n".to_owned();
+    script.push_str(&"l\npass\n".repeat(60));
+    let output = run_script_in(&home, &script, &["--seed", SEED_WITH_THREE_OF_DIAMONDS]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    let code = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Share it with: dai-di replay --code "))
+        .unwrap_or_else(|| panic!("no replay code in {stdout}"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dai-di"))
+        .args(["replay", "--no-color", "--code", code])
+        .env("HOME", &home)
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    let _ = std::fs::remove_dir_all(&home);
+    let replayed = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{replayed}");
+    assert!(replayed.contains("Player plays 3♦"), "{replayed}");
+    assert!(
+        replayed.contains("That's the end of the replay."),
+        "{replayed}"
+    );
+}